@@ -0,0 +1,285 @@
+use bon::Builder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+use crate::{FogBugzClient, ResponseError};
+
+/// Kind of account created by `newPerson` (`nType`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize_repr, Deserialize_repr)]
+#[repr(u8)]
+pub enum PersonType {
+    #[default]
+    Normal = 0,
+    Administrator = 1,
+    Community = 2,
+    Virtual = 3,
+}
+
+/// Project created by `newProject`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProjectResponse {
+    #[serde(rename = "ixProject")]
+    pub project_id: u32,
+}
+
+/// Area created by `newArea`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AreaResponse {
+    #[serde(rename = "ixArea")]
+    pub area_id: u32,
+}
+
+/// Person created or changed by `newPerson` / `editPerson`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PersonResponse {
+    #[serde(rename = "ixPerson")]
+    pub person_id: u32,
+}
+
+/// Request to create a project
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct NewProjectRequest {
+    /// Project name (required)
+    #[serde(rename = "sProject")]
+    #[builder(into)]
+    name: String,
+    /// Primary contact (optional; defaults to the logged on user)
+    #[serde(
+        rename = "ixPersonPrimaryContact",
+        skip_serializing_if = "Option::is_none"
+    )]
+    owner_id: Option<u32>,
+    /// Make this the inbox project new mail is filed into (optional)
+    #[serde(rename = "fInbox", skip_serializing_if = "Option::is_none")]
+    is_inbox: Option<bool>,
+    /// Allow public submissions (optional)
+    #[serde(rename = "fAllowPublicSubmit", skip_serializing_if = "Option::is_none")]
+    allow_public_submit: Option<bool>,
+    /// Permission group the project belongs to (optional)
+    #[serde(rename = "ixGroup", skip_serializing_if = "Option::is_none")]
+    group_id: Option<u32>,
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl NewProjectRequest {
+    pub async fn send(&self) -> Result<ProjectResponse, ResponseError> {
+        let mut response = self.client.send_command("newProject", self).await?;
+        self.client.invalidate_metadata_cache();
+        Ok(serde_json::from_value(response["data"]["project"].take())?)
+    }
+}
+
+/// Request to create an area in a project
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct NewAreaRequest {
+    /// Project to add the area to (required)
+    #[serde(rename = "ixProject")]
+    project_id: u32,
+    /// Area name (required)
+    #[serde(rename = "sArea")]
+    #[builder(into)]
+    name: String,
+    /// Person new cases in the area are assigned to (optional; -1 for the project's
+    /// primary contact)
+    #[serde(
+        rename = "ixPersonPrimaryContact",
+        skip_serializing_if = "Option::is_none"
+    )]
+    owner_id: Option<i32>,
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl NewAreaRequest {
+    pub async fn send(&self) -> Result<AreaResponse, ResponseError> {
+        let mut response = self.client.send_command("newArea", self).await?;
+        self.client.invalidate_metadata_cache();
+        Ok(serde_json::from_value(response["data"]["area"].take())?)
+    }
+}
+
+/// Request to create a person
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct NewPersonRequest {
+    /// Login email (required)
+    #[serde(rename = "sEmail")]
+    #[builder(into)]
+    email: String,
+    /// Full name (required)
+    #[serde(rename = "sFullName")]
+    #[builder(into)]
+    full_name: String,
+    /// Account type (defaults to a normal user)
+    #[serde(rename = "nType")]
+    #[builder(default)]
+    person_type: PersonType,
+    /// Initial password (optional)
+    #[serde(rename = "sPassword", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    password: Option<String>,
+    /// Phone number (optional)
+    #[serde(rename = "sPhone", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    phone: Option<String>,
+    /// Windows time zone key, or `*` for the site's (optional)
+    #[serde(rename = "sTimeZoneKey", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    timezone: Option<String>,
+    /// Send email notifications (optional)
+    #[serde(rename = "fNotify", skip_serializing_if = "Option::is_none")]
+    notify: Option<bool>,
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl NewPersonRequest {
+    pub async fn send(&self) -> Result<PersonResponse, ResponseError> {
+        let mut response = self.client.send_command("newPerson", self).await?;
+        self.client.invalidate_metadata_cache();
+        Ok(serde_json::from_value(response["data"]["person"].take())?)
+    }
+}
+
+/// Request to change a person's details; unset fields are left as they are
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct EditPersonRequest {
+    /// Person to edit (required)
+    #[serde(rename = "ixPerson")]
+    person_id: u32,
+    /// New login email (optional)
+    #[serde(rename = "sEmail", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    email: Option<String>,
+    /// New full name (optional)
+    #[serde(rename = "sFullName", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    full_name: Option<String>,
+    /// New account type (optional)
+    #[serde(rename = "nType", skip_serializing_if = "Option::is_none")]
+    person_type: Option<PersonType>,
+    /// New password (optional)
+    #[serde(rename = "sPassword", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    password: Option<String>,
+    /// New phone number (optional)
+    #[serde(rename = "sPhone", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    phone: Option<String>,
+    /// New time zone key (optional)
+    #[serde(rename = "sTimeZoneKey", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    timezone: Option<String>,
+    /// Send email notifications (optional)
+    #[serde(rename = "fNotify", skip_serializing_if = "Option::is_none")]
+    notify: Option<bool>,
+    /// Reactivate (`true`) or deactivate (`false`) the account (optional)
+    #[serde(rename = "fActive", skip_serializing_if = "Option::is_none")]
+    active: Option<bool>,
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl EditPersonRequest {
+    pub async fn send(&self) -> Result<PersonResponse, ResponseError> {
+        let mut response = self.client.send_command("editPerson", self).await?;
+        self.client.invalidate_metadata_cache();
+        match response["data"]["person"].take() {
+            Value::Null => Ok(PersonResponse {
+                person_id: self.person_id,
+            }),
+            person => Ok(serde_json::from_value(person)?),
+        }
+    }
+}
+
+/// Request to delete (deactivate) a person; their cases and history are kept
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct DeletePersonRequest {
+    /// Person to delete (required)
+    #[serde(rename = "ixPerson")]
+    person_id: u32,
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl DeletePersonRequest {
+    pub async fn send(&self) -> Result<(), ResponseError> {
+        self.client.send_command("deletePerson", self).await?;
+        self.client.invalidate_metadata_cache();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::mock_server::MockServer;
+
+    #[test]
+    fn test_admin_response_deserialization() {
+        let mut response = serde_json::json!({
+            "data": { "person": { "ixPerson": 42, "sEmail": "new@example.com" } }
+        });
+        let person: PersonResponse =
+            serde_json::from_value(response["data"]["person"].take()).unwrap();
+        assert_eq!(person.person_id, 42);
+
+        let area: AreaResponse =
+            serde_json::from_value(serde_json::json!({ "ixArea": 7 })).unwrap();
+        assert_eq!(area.area_id, 7);
+        assert_eq!(
+            serde_json::to_value(PersonType::Community).unwrap(),
+            serde_json::json!(2)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_admin_writes_invalidate_metadata_cache() {
+        let server = MockServer::start(|payload| match payload["cmd"].as_str() {
+            Some("newProject") => serde_json::json!({
+                "data": { "project": { "ixProject": 5 } }, "errors": []
+            }),
+            Some("deletePerson") => serde_json::json!({ "data": {}, "errors": [] }),
+            _ => serde_json::json!({ "data": { "projects": [] }, "errors": [] }),
+        })
+        .await;
+        let api = FogBugzClient::builder()
+            .url(server.url())
+            .api_key("token")
+            .metadata_cache_ttl(Duration::from_secs(60))
+            .build();
+        let list_projects_sent = || {
+            server
+                .requests()
+                .iter()
+                .filter(|payload| payload["cmd"] == "listProjects")
+                .count()
+        };
+
+        api.list_projects().await.unwrap();
+        api.list_projects().await.unwrap();
+        assert_eq!(list_projects_sent(), 1);
+
+        api.new_project("Tools").build().send().await.unwrap();
+        api.list_projects().await.unwrap();
+        assert_eq!(list_projects_sent(), 2);
+
+        api.delete_person(3).build().send().await.unwrap();
+        api.list_projects().await.unwrap();
+        assert_eq!(list_projects_sent(), 3);
+    }
+}
@@ -1,61 +1,465 @@
-use reqwest::Url;
+use std::{future::Future, time::Duration};
+
+use reqwest::{StatusCode, Url, multipart::Form};
 use serde::Serialize;
 use serde_json::Value;
+use thiserror::Error;
+
+use crate::{FogBugzClient, ResponseError, auth::AuthError, case_management::AttachmentUpload};
+
+/// Whether a command only reads data. Anything not known to be read-only is
+/// treated as mutating, so new commands are blocked on read-only clients by default.
+/// Bare `view` marks a case as read, so only `viewCase`, `viewPerson` and the like count.
+pub(crate) fn is_read_only_command(cmd: &str) -> bool {
+    cmd.starts_with("list")
+        || (cmd.starts_with("view") && cmd != "view")
+        || matches!(cmd, "search" | "logon" | "logoff")
+}
+
+/// FogBugz error code for a bad email/password combination
+const CODE_LOGON_FAILED: &str = "1";
+/// FogBugz error code for a missing, invalid or expired token
+const CODE_NOT_LOGGED_ON: &str = "3";
+/// FogBugz error code for a required argument missing from the command
+const CODE_MISSING_ARGUMENT: &str = "4";
+/// FogBugz error code for editing a case that doesn't exist
+const CODE_CASE_NOT_FOUND: &str = "5";
+/// FogBugz error code for an action the user may not perform on the case
+const CODE_NOT_PERMITTED: &str = "6";
+
+/// An error reported by FogBugz, classified by its error code or HTTP status
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FogBugzApiError {
+    #[error("Logon failed: {message}")]
+    LogonFailed { message: String },
+    #[error("Invalid or expired token: {message}")]
+    InvalidToken { message: String },
+    #[error("Missing argument: {message}")]
+    MissingArgument { message: String },
+    #[error("Case not found: {message}")]
+    CaseNotFound { message: String },
+    #[error("Permission denied: {message}")]
+    PermissionDenied { message: String },
+    #[error("Rate limited: {message}")]
+    RateLimited { message: String },
+    /// Non-success HTTP status without a FogBugz error code
+    #[error("Server error {status}: {message}")]
+    ServerError { status: u16, message: String },
+    #[error("Error {}: {message}", code.as_deref().unwrap_or("without code"))]
+    Other {
+        code: Option<String>,
+        message: String,
+    },
+}
+
+impl FogBugzApiError {
+    /// Classify the first entry of `errors` in a FogBugz response
+    pub(crate) fn from_json(status: StatusCode, json: &Value) -> Self {
+        let error = &json["errors"][0];
+        // Codes come back as strings or numbers
+        let code = match &error["code"] {
+            Value::String(code) => Some(code.clone()),
+            Value::Number(code) => Some(code.to_string()),
+            _ => None,
+        };
+        let message = error["message"]
+            .as_str()
+            .map(str::to_string)
+            .unwrap_or_else(|| json.to_string());
+        match code.as_deref() {
+            Some(CODE_LOGON_FAILED) => Self::LogonFailed { message },
+            Some(CODE_NOT_LOGGED_ON) => Self::InvalidToken { message },
+            Some(CODE_MISSING_ARGUMENT) => Self::MissingArgument { message },
+            Some(CODE_CASE_NOT_FOUND) => Self::CaseNotFound { message },
+            Some(CODE_NOT_PERMITTED) => Self::PermissionDenied { message },
+            _ => Self::from_status(status, &message).unwrap_or(Self::Other { code, message }),
+        }
+    }
+
+    /// Classify an error by HTTP status alone, for bodies that aren't FogBugz JSON
+    fn from_status(status: StatusCode, message: &str) -> Option<Self> {
+        let lower = message.to_lowercase();
+        let message = message.to_string();
+        if status == StatusCode::TOO_MANY_REQUESTS
+            || lower.contains("too busy")
+            || lower.contains("too many requests")
+        {
+            return Some(Self::RateLimited { message });
+        }
+        match status {
+            StatusCode::UNAUTHORIZED => Some(Self::InvalidToken { message }),
+            StatusCode::FORBIDDEN => Some(Self::PermissionDenied { message }),
+            status if !status.is_success() => Some(Self::ServerError {
+                status: status.as_u16(),
+                message,
+            }),
+            _ => None,
+        }
+    }
+
+    /// The message FogBugz gave for the error
+    pub fn message(&self) -> &str {
+        match self {
+            Self::LogonFailed { message }
+            | Self::InvalidToken { message }
+            | Self::MissingArgument { message }
+            | Self::CaseNotFound { message }
+            | Self::PermissionDenied { message }
+            | Self::RateLimited { message }
+            | Self::ServerError { message, .. }
+            | Self::Other { message, .. } => message,
+        }
+    }
+
+    /// The FogBugz error code, if the error had one
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            Self::LogonFailed { .. } => Some(CODE_LOGON_FAILED),
+            Self::InvalidToken { .. } => Some(CODE_NOT_LOGGED_ON),
+            Self::MissingArgument { .. } => Some(CODE_MISSING_ARGUMENT),
+            Self::CaseNotFound { .. } => Some(CODE_CASE_NOT_FOUND),
+            Self::PermissionDenied { .. } => Some(CODE_NOT_PERMITTED),
+            Self::RateLimited { .. } | Self::ServerError { .. } => None,
+            Self::Other { code, .. } => code.as_deref(),
+        }
+    }
+}
+
+/// Run `send` inside a `fogbugz.command` span recording the command, target case,
+/// duration and FogBugz error code; just runs it without the `tracing` feature
+pub(crate) async fn traced<Fut>(
+    cmd: &str,
+    params: &Value,
+    send: Fut,
+) -> Result<Value, ResponseError>
+where
+    Fut: Future<Output = Result<Value, ResponseError>>,
+{
+    #[cfg(feature = "tracing")]
+    {
+        use tracing::{Instrument, field::Empty};
+
+        let case_id = match &params["ixBug"] {
+            Value::String(id) => id.parse().ok(),
+            id => id.as_u64(),
+        };
+        let span = tracing::info_span!(
+            "fogbugz.command",
+            cmd,
+            case_id,
+            duration_ms = Empty,
+            error_code = Empty,
+        );
+        let started = std::time::Instant::now();
+        let result = send.instrument(span.clone()).await;
+        span.record("duration_ms", started.elapsed().as_millis() as u64);
+        match &result {
+            Err(ResponseError::FogbugzError(error)) => {
+                span.record("error_code", error.code().unwrap_or("none"));
+                tracing::warn!(parent: &span, %error, "FogBugz command failed");
+            }
+            Err(error) => tracing::warn!(parent: &span, %error, "FogBugz command failed"),
+            Ok(_) => {}
+        }
+        result
+    }
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = (cmd, params);
+        send.await
+    }
+}
+
+/// Turn a failed HTTP response into a `FogbugzError`, whether or not the body is JSON
+pub(crate) async fn error_from_response(response: reqwest::Response) -> ResponseError {
+    let status = response.status();
+    let body = match response.text().await {
+        Ok(body) => body,
+        Err(e) => return e.into(),
+    };
+    error_from_body(status, body).into()
+}
+
+/// Classify the body of a failed HTTP response, whether or not it is JSON
+pub(crate) fn error_from_body(status: StatusCode, body: String) -> FogBugzApiError {
+    match serde_json::from_str::<Value>(&body) {
+        Ok(json) => FogBugzApiError::from_json(status, &json),
+        Err(_) => {
+            FogBugzApiError::from_status(status, body.trim()).unwrap_or(FogBugzApiError::Other {
+                code: None,
+                message: body,
+            })
+        }
+    }
+}
 
-use crate::{FogBugzClient, ResponseError};
+/// Turn errors reported in a successful response into `FogbugzError`
+pub(crate) fn check_errors(status: StatusCode, json: Value) -> Result<Value, ResponseError> {
+    match json["errors"].as_array() {
+        Some(errors) if !errors.is_empty() => Err(FogBugzApiError::from_json(status, &json).into()),
+        _ => Ok(json),
+    }
+}
+
+/// Decode a FogBugz response, turning reported errors into `FogbugzError`
+async fn parse_response(response: reqwest::Response) -> Result<Value, ResponseError> {
+    if !response.status().is_success() {
+        return Err(error_from_response(response).await);
+    }
+    let status = response.status();
+    check_errors(status, response.json().await?)
+}
+
+/// Longest `q` FogBugz reliably accepts; longer queries fail with an opaque server error
+pub const MAX_QUERY_LENGTH: usize = 2000;
+
+/// Split a query that is just a list of case IDs (`1,2,3`, `1 OR 2`, ...) into
+/// `1,2,3` queries no longer than `max_len`. Returns `None` for any other query.
+pub(crate) fn split_id_query(query: &str, max_len: usize) -> Option<Vec<String>> {
+    let ids: Vec<&str> = query
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty() && !token.eq_ignore_ascii_case("OR"))
+        .collect();
+    if ids.is_empty() || !ids.iter().all(|id| id.bytes().all(|b| b.is_ascii_digit())) {
+        return None;
+    }
+
+    let mut batches = Vec::new();
+    let mut batch = String::new();
+    for id in ids {
+        if !batch.is_empty() && batch.len() + 1 + id.len() > max_len {
+            batches.push(std::mem::take(&mut batch));
+        }
+        if !batch.is_empty() {
+            batch.push(',');
+        }
+        batch.push_str(id);
+    }
+    batches.push(batch);
+    Some(batches)
+}
+
+/// The `q` of each search sent for `query`: the query itself, or batches of a
+/// plain case ID list longer than `MAX_QUERY_LENGTH`
+pub(crate) fn search_batches(query: &str) -> Result<Vec<String>, ResponseError> {
+    if query.len() <= MAX_QUERY_LENGTH {
+        return Ok(vec![query.to_string()]);
+    }
+    split_id_query(query, MAX_QUERY_LENGTH).ok_or(ResponseError::TooLongQuery {
+        length: query.len(),
+        max: MAX_QUERY_LENGTH,
+    })
+}
 
 impl FogBugzClient {
-    /// Send a command to the FogBugz JSON API
+    /// Enforce the read-only flag and command policy before sending `cmd`
+    pub(crate) fn check_command(&self, cmd: &str) -> Result<(), ResponseError> {
+        if self.read_only && !is_read_only_command(cmd) {
+            return Err(ResponseError::ReadOnly(cmd.to_string()));
+        }
+        if !self.policy.permits(cmd) {
+            return Err(ResponseError::CommandNotAllowed(cmd.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Send a command to the FogBugz JSON API.
+    /// If a `logon` session expired, it is renewed once and the command retried;
+    /// transient failures are retried according to the client's `retry_policy`.
     pub(crate) async fn send_command<T: Serialize>(
         &self,
         cmd: &str,
         params: T,
     ) -> Result<Value, ResponseError> {
-        let url = Url::parse(&self.url)?.join("f/api/0/jsonapi")?;
+        let params = serde_json::to_value(params)?;
+        let send = self.with_session_renewal(cmd, || {
+            self.with_retries(|| self.send_command_once(cmd, params.clone()))
+        });
+        traced(cmd, &params, send).await
+    }
+
+    /// Send a command with file attachments as a multipart form
+    /// (falls back to a plain JSON command when there are none)
+    pub(crate) async fn send_command_multipart<T: Serialize>(
+        &self,
+        cmd: &str,
+        params: T,
+        attachments: &[AttachmentUpload],
+    ) -> Result<Value, ResponseError> {
+        if attachments.is_empty() {
+            return self.send_command(cmd, params).await;
+        }
+        let params = serde_json::to_value(params)?;
+        let send = self.with_session_renewal(cmd, || {
+            self.with_retries(|| self.send_multipart_once(cmd, &params, attachments))
+        });
+        traced(cmd, &params, send).await
+    }
 
+    /// Run `send`, renewing an expired `logon` session once and retrying
+    async fn with_session_renewal<F, Fut>(&self, cmd: &str, send: F) -> Result<Value, ResponseError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<Value, ResponseError>>,
+    {
+        match send().await {
+            Err(ResponseError::FogbugzError(FogBugzApiError::InvalidToken { message }))
+                if cmd != "logoff" =>
+            {
+                if !self.renew_session().await? {
+                    return Err(FogBugzApiError::InvalidToken { message }.into());
+                }
+                match send().await {
+                    Err(ResponseError::FogbugzError(FogBugzApiError::InvalidToken { .. })) => {
+                        Err(AuthError::SessionExpired.into())
+                    }
+                    result => result,
+                }
+            }
+            result => result,
+        }
+    }
+
+    pub(crate) fn api_url(&self) -> Result<Url, ResponseError> {
+        Ok(Url::parse(&self.url)?.join("f/api/0/jsonapi")?)
+    }
+
+    pub(crate) async fn acquire_permit(&self) {
+        if let Some(bucket) = &self.rate_limit {
+            bucket.acquire().await;
+        }
         #[cfg(feature = "leaky-bucket")]
         if let Some(ref limiter) = self.limiter {
             limiter.acquire_one().await;
         }
+    }
+
+    /// Send a command once, without renewing an expired session
+    pub(crate) async fn send_command_once(
+        &self,
+        cmd: &str,
+        mut payload: Value,
+    ) -> Result<Value, ResponseError> {
+        self.check_command(cmd)?;
+        let url = self.api_url()?;
+        self.acquire_permit().await;
 
         // Build the request payload
-        let mut payload = serde_json::to_value(params)?;
         payload["cmd"] = cmd.into();
-        payload["token"] = self.api_key.clone().into();
-
-        let response = self
-            .client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .json(&payload)
-            .send()
-            .await?;
-
-        if response.status().is_success() {
-            let json: Value = response.json().await?;
-
-            // Check for API errors in response
-            if let Some(errors) = json.get("errors") {
-                if let Some(errors_array) = errors.as_array() {
-                    if !errors_array.is_empty() {
-                        return Err(ResponseError::FogbugzError(json));
-                    }
-                }
+        if cmd != "logon" {
+            payload["token"] = self.token().into();
+        }
+        let (headers, started) = self.prepare_request(cmd, &mut payload);
+
+        let result = async {
+            let response = self
+                .client
+                .post(url)
+                .header("Content-Type", "application/json")
+                .headers(headers)
+                .json(&payload)
+                .send()
+                .await?;
+            parse_response(response).await
+        }
+        .await;
+        self.finish_request(cmd, started, &result);
+        result
+    }
+
+    /// Send a multipart command once; attachments go in `File1`..`FileN`
+    async fn send_multipart_once(
+        &self,
+        cmd: &str,
+        params: &Value,
+        attachments: &[AttachmentUpload],
+    ) -> Result<Value, ResponseError> {
+        self.check_command(cmd)?;
+        let url = self.api_url()?;
+        self.acquire_permit().await;
+
+        let mut payload = params.clone();
+        payload["cmd"] = cmd.into();
+        payload["token"] = self.token().into();
+        payload["nFileCount"] = attachments.len().to_string().into();
+        let (headers, started) = self.prepare_request(cmd, &mut payload);
+
+        let mut form = Form::new();
+        if let Some(params) = payload.as_object() {
+            for (name, value) in params {
+                let value = match value {
+                    Value::Null => continue,
+                    Value::String(value) => value.clone(),
+                    other => other.to_string(),
+                };
+                form = form.text(name.clone(), value);
             }
+        }
+        for (index, attachment) in attachments.iter().enumerate() {
+            form = form.part(format!("File{}", index + 1), attachment.to_part()?);
+        }
+
+        let result = async {
+            let response = self
+                .client
+                .post(url)
+                .headers(headers)
+                .multipart(form)
+                .send()
+                .await?;
+            parse_response(response).await
+        }
+        .await;
+        self.finish_request(cmd, started, &result);
+        result
+    }
 
-            Ok(json)
-        } else {
-            let json: Value = response.json().await?;
-            Err(ResponseError::FogbugzError(json))
+    /// Shortest average gap between requests the rate limiter allows, if one is configured
+    pub(crate) fn min_request_interval(&self) -> Option<Duration> {
+        #[cfg(feature = "leaky-bucket")]
+        if let Some(ref limiter) = self.limiter {
+            return Some(limiter.interval() / limiter.refill().max(1) as u32);
         }
+        self.rate_limit
+            .as_ref()
+            .map(|bucket| bucket.limit().interval())
     }
 
-    /// Send a search command (internal API method)
+    /// Send a search command (internal API method).
+    /// Queries longer than `MAX_QUERY_LENGTH` are split into several searches if they
+    /// are plain case ID lists, and rejected with `TooLongQuery` otherwise.
     pub(crate) async fn send_search<T: Serialize>(
         &self,
         params: T,
     ) -> Result<Value, ResponseError> {
-        self.send_command("search", params).await
+        let mut params = serde_json::to_value(params)?;
+        let query = params["q"].as_str().unwrap_or_default();
+        if query.len() <= MAX_QUERY_LENGTH {
+            return self.send_command("search", params).await;
+        }
+        let batches = search_batches(query)?;
+
+        let mut merged: Option<Value> = None;
+        for batch in batches {
+            params["q"] = batch.into();
+            let mut response = self.send_command("search", &params).await?;
+            match &mut merged {
+                None => merged = Some(response),
+                Some(merged) => {
+                    if let (Value::Array(cases), Value::Array(more)) = (
+                        &mut merged["data"]["cases"],
+                        response["data"]["cases"].take(),
+                    ) {
+                        cases.extend(more);
+                    }
+                }
+            }
+        }
+        let mut merged = merged.unwrap_or_default();
+        let count = merged["data"]["cases"].as_array().map_or(0, Vec::len);
+        merged["data"]["count"] = count.into();
+        merged["data"]["totalHits"] = count.into();
+        Ok(merged)
     }
 
     /// Send a listCases command (internal API method)
@@ -75,7 +479,118 @@ impl FogBugzClient {
 
 #[cfg(test)]
 mod tests {
-    use crate::FogBugzClient;
+    use reqwest::StatusCode;
+
+    use super::FogBugzApiError;
+    use crate::{FogBugzClient, ResponseError};
+
+    #[test]
+    fn test_response_error_classification() {
+        let error = |status: StatusCode, json: serde_json::Value| {
+            ResponseError::from(FogBugzApiError::from_json(status, &json))
+        };
+        let busy = error(StatusCode::SERVICE_UNAVAILABLE, serde_json::json!({}));
+        assert!(busy.is_retryable() && !busy.is_auth_error() && !busy.is_not_found());
+
+        let expired = error(
+            StatusCode::OK,
+            serde_json::json!({ "errors": [{ "message": "Not logged in", "code": "3" }] }),
+        );
+        assert!(expired.is_auth_error() && !expired.is_retryable());
+
+        let missing = error(
+            StatusCode::OK,
+            serde_json::json!({ "errors": [{ "message": "No such case", "code": 5 }] }),
+        );
+        assert!(missing.is_not_found() && !missing.is_retryable());
+        assert!(ResponseError::PersonNotFound("Ann".to_string()).is_not_found());
+        assert!(!ResponseError::ReadOnly("new".to_string()).is_retryable());
+    }
+
+    #[test]
+    fn test_api_error_classification() {
+        let expired = serde_json::json!({
+            "errors": [{ "message": "Not logged in", "detail": null, "code": "3" }]
+        });
+        assert_eq!(
+            FogBugzApiError::from_json(StatusCode::OK, &expired),
+            FogBugzApiError::InvalidToken {
+                message: "Not logged in".to_string()
+            }
+        );
+
+        let bad_password = serde_json::json!({
+            "errors": [{ "message": "Incorrect password or username", "code": 1 }]
+        });
+        assert!(matches!(
+            FogBugzApiError::from_json(StatusCode::OK, &bad_password),
+            FogBugzApiError::LogonFailed { .. }
+        ));
+
+        let busy = serde_json::json!({ "errors": [{ "message": "Server too busy" }] });
+        assert!(matches!(
+            FogBugzApiError::from_json(StatusCode::OK, &busy),
+            FogBugzApiError::RateLimited { .. }
+        ));
+
+        let unknown = serde_json::json!({ "errors": [{ "message": "Odd", "code": 42 }] });
+        let error = FogBugzApiError::from_json(StatusCode::OK, &unknown);
+        assert_eq!(error.message(), "Odd");
+        assert_eq!(error.code(), Some("42"));
+        assert!(matches!(error, FogBugzApiError::Other { code: Some(code), .. } if code == "42"));
+        assert_eq!(
+            FogBugzApiError::from_json(StatusCode::OK, &bad_password).code(),
+            Some("1")
+        );
+
+        assert!(matches!(
+            FogBugzApiError::from_status(StatusCode::BAD_GATEWAY, "<html>"),
+            Some(FogBugzApiError::ServerError { status: 502, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_read_only_client_rejects_mutations() {
+        let api = FogBugzClient::new("http://localhost:1", "token").read_only();
+        assert!(api.is_read_only());
+
+        let result = api
+            .send_command("edit", serde_json::json!({"ixBug": 1}))
+            .await;
+        assert!(matches!(result, Err(ResponseError::ReadOnly(cmd)) if cmd == "edit"));
+        assert!(super::is_read_only_command("listPeople"));
+        assert!(super::is_read_only_command("viewPerson"));
+        assert!(!super::is_read_only_command("newInterval"));
+        assert!(!super::is_read_only_command("view"));
+    }
+
+    #[test]
+    fn test_split_id_query() {
+        assert_eq!(
+            super::split_id_query("1,22,333 OR 4", 6),
+            Some(vec!["1,22".to_string(), "333,4".to_string()])
+        );
+        assert_eq!(super::split_id_query("status:Active", 100), None);
+        assert_eq!(super::split_id_query("1,2 project:Web", 100), None);
+    }
+
+    #[tokio::test]
+    async fn test_too_long_query_is_rejected() {
+        let api = FogBugzClient::new("http://localhost:1", "token");
+        let query = format!("title:\"{}\"", "x".repeat(super::MAX_QUERY_LENGTH));
+        let result = api.send_search(serde_json::json!({ "q": query })).await;
+        assert!(matches!(result, Err(ResponseError::TooLongQuery { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_policy_blocks_denied_commands() {
+        let api = FogBugzClient::new("http://localhost:1", "token")
+            .with_policy(crate::policy::CommandPolicy::allow(["search"]));
+        let result = api.send_command("listPeople", serde_json::json!({})).await;
+        assert!(
+            matches!(result, Err(ResponseError::CommandNotAllowed(cmd)) if cmd == "listPeople")
+        );
+    }
 
     #[tokio::test]
     async fn test_api_client_search() {
@@ -0,0 +1,546 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Component, Path, PathBuf},
+};
+
+use chrono::{DateTime, Utc};
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use thiserror::Error;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+use crate::{
+    FogBugzClient, ResponseError,
+    case_details::{Attachment, Event},
+    case_management::{AttachmentUpload, ResolveAndCloseError},
+    enums::Column,
+    filter::FogBugzSearchBuilder,
+};
+
+/// Case columns kept in the archive, besides any custom fields
+const ARCHIVE_COLUMNS: &[Column] = &[
+    Column::CaseId,
+    Column::Title,
+    Column::Project,
+    Column::ProjectId,
+    Column::Area,
+    Column::AreaId,
+    Column::Category,
+    Column::CategoryName,
+    Column::Status,
+    Column::StatusName,
+    Column::Priority,
+    Column::PriorityName,
+    Column::IsOpen,
+    Column::PersonAssignedTo,
+    Column::PersonAssignedToId,
+    Column::PersonOpenedById,
+    Column::PersonResolvedById,
+    Column::PersonClosedById,
+    Column::Milestone,
+    Column::MilestoneId,
+    Column::Tags,
+    Column::ParentId,
+    Column::Children,
+    Column::OriginalId,
+    Column::Duplicates,
+    Column::RelatedCases,
+    Column::CustomerEmail,
+    Column::Version,
+    Column::Computer,
+    Column::DateOpened,
+    Column::DateResolved,
+    Column::DateClosed,
+    Column::DateDue,
+    Column::LastUpdated,
+    Column::HoursElapsed,
+    Column::HoursCurrentEstimate,
+    Column::HoursOriginalEstimate,
+    Column::CustomFields,
+    Column::Events,
+];
+
+/// Cases per search page; every case carries its full event history
+const PAGE_SIZE: u32 = 50;
+
+#[derive(Debug, Error)]
+pub enum ArchiveError {
+    #[error("Writing archive failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+    #[error(transparent)]
+    Source(#[from] ResponseError),
+    #[error(transparent)]
+    Close(#[from] ResolveAndCloseError),
+    #[error("Bundle path {0} is not a relative path inside the bundle")]
+    UnsafePath(String),
+}
+
+/// An attachment file in the archive, relative to the bundle directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedAttachment {
+    pub case_id: u64,
+    pub event_id: Option<u64>,
+    pub file_name: String,
+    pub path: String,
+    pub bytes: u64,
+    /// Why the file couldn't be downloaded; `path` doesn't exist then
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// `manifest.json` of an archive bundle, written last so its presence marks a
+/// complete export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    pub project_id: u32,
+    pub project: Option<String>,
+    pub exported_at: DateTime<Utc>,
+    /// File with one case per line, events included
+    pub cases_file: String,
+    pub cases: usize,
+    pub events: usize,
+    pub attachments: Vec<ArchivedAttachment>,
+}
+
+impl ArchiveManifest {
+    /// Attachments that couldn't be downloaded
+    pub fn failed_attachments(&self) -> impl Iterator<Item = &ArchivedAttachment> {
+        self.attachments
+            .iter()
+            .filter(|attachment| attachment.error.is_some())
+    }
+}
+
+/// File name safe to create on any platform; FogBugz keeps names as uploaded
+fn sanitize_file_name(name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let name = name.trim_matches([' ', '.']);
+    if name.is_empty() {
+        "attachment".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Bundle-relative path of an attachment: `attachments/<case>/<id>-<name>`,
+/// using the attachment's position in the case when its URL has no ID
+fn attachment_path(case_id: u64, index: usize, attachment: &Attachment) -> String {
+    let id = attachment
+        .attachment_id()
+        .map_or_else(|| format!("n{index}"), |id| id.to_string());
+    format!(
+        "attachments/{case_id}/{id}-{}",
+        sanitize_file_name(&attachment.file_name)
+    )
+}
+
+/// The attachments of a case's events, with the event each belongs to
+fn case_attachments(case: &Value) -> Vec<(Option<u64>, Attachment)> {
+    case["events"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .flat_map(|event| {
+            let event_id = event["ixBugEvent"].as_u64();
+            let attachments: Vec<Attachment> =
+                serde_json::from_value(event["attachments"].clone()).unwrap_or_default();
+            attachments
+                .into_iter()
+                .map(move |attachment| (event_id, attachment))
+        })
+        .collect()
+}
+
+/// What `import_archive` created
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    /// New case ID for each archived case ID
+    pub cases: BTreeMap<u64, u64>,
+    /// Archived event IDs already carried over, opening events included
+    pub events: BTreeSet<u64>,
+    /// Archived case IDs whose new case was closed again
+    pub closed: BTreeSet<u64>,
+    /// Events carried over as edits of the new cases
+    pub comments: usize,
+    pub attachments: usize,
+}
+
+/// An import that stopped partway, with what it created before the error
+#[derive(Debug, Error)]
+#[error("Import stopped after {} cases: {source}", report.cases.len())]
+pub struct ImportError {
+    /// Pass to `resume_import_archive` to carry on without duplicating cases
+    pub report: ImportReport,
+    pub source: ArchiveError,
+}
+
+/// `relative` inside `bundle`. Bundles come from other instances, so paths
+/// from the manifest must not point outside it.
+fn bundle_path(bundle: &Path, relative: &str) -> Result<PathBuf, ArchiveError> {
+    let path = Path::new(relative);
+    let inside = path
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)));
+    if relative.is_empty() || !inside {
+        return Err(ArchiveError::UnsafePath(relative.to_string()));
+    }
+    Ok(bundle.join(path))
+}
+
+/// Events of an archived case worth carrying over: the opening one, and any
+/// later one with text or attachments
+fn imported_events(case: &Value) -> Vec<Event> {
+    let mut events = case["events"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|event| serde_json::from_value::<Event>(event.clone()).ok());
+    let opened = events.next();
+    opened
+        .into_iter()
+        .chain(events.filter(|event| {
+            !event.content.trim().is_empty()
+                || event
+                    .attachments
+                    .as_ref()
+                    .is_some_and(|attachments| !attachments.is_empty())
+        }))
+        .collect()
+}
+
+/// Text of an archived event as posted on the imported case, headed with
+/// where, when and by whom it originally happened
+fn imported_text(original_case_id: u64, event: &Event) -> String {
+    let header = format!(
+        "[Imported from case {original_case_id}: {} on {} UTC]",
+        event.description,
+        event.datetime.format("%Y-%m-%d %H:%M")
+    );
+    if event.content.trim().is_empty() {
+        header
+    } else {
+        format!("{header}\n\n{}", event.content)
+    }
+}
+
+/// The archived files of one event, ready to attach again
+async fn bundle_uploads(
+    bundle: &Path,
+    manifest: &ArchiveManifest,
+    case_id: u64,
+    event_id: u64,
+) -> Result<Vec<AttachmentUpload>, ArchiveError> {
+    let mut uploads = Vec::new();
+    for attachment in &manifest.attachments {
+        if attachment.case_id != case_id
+            || attachment.event_id != Some(event_id)
+            || attachment.error.is_some()
+        {
+            continue;
+        }
+        let content = tokio::fs::read(bundle_path(bundle, &attachment.path)?).await?;
+        uploads.push(AttachmentUpload::new(&attachment.file_name, content));
+    }
+    Ok(uploads)
+}
+
+impl FogBugzClient {
+    /// Export every case of a project, with its events and attachment files,
+    /// into `dir`, e.g. before decommissioning the project:
+    /// `cases.jsonl` (one case per line), `attachments/<case>/...` and
+    /// `manifest.json`. Attachments that fail to download are listed in the
+    /// manifest with the error instead of aborting the export.
+    pub async fn archive_project(
+        &self,
+        project_id: u32,
+        dir: impl AsRef<Path>,
+    ) -> Result<ArchiveManifest, ArchiveError> {
+        let dir = dir.as_ref();
+        tokio::fs::create_dir_all(dir).await?;
+        let project = self
+            .list_projects()
+            .await?
+            .into_iter()
+            .find(|project| project.id == project_id)
+            .map(|project| project.name);
+
+        let mut manifest = ArchiveManifest {
+            project_id,
+            project,
+            exported_at: Utc::now(),
+            cases_file: "cases.jsonl".to_string(),
+            cases: 0,
+            events: 0,
+            attachments: Vec::new(),
+        };
+        let search = self
+            .search()
+            .query(FogBugzSearchBuilder::new().project_id(project_id).build())
+            .cols(ARCHIVE_COLUMNS.iter().map(ToString::to_string).collect())
+            .max(PAGE_SIZE)
+            .build();
+        let mut cases_file = tokio::fs::File::create(dir.join(&manifest.cases_file)).await?;
+        let mut pages = std::pin::pin!(search.paged());
+        while let Some(page) = pages.try_next().await? {
+            for case in page {
+                let mut line = serde_json::to_vec(&case)?;
+                line.push(b'\n');
+                cases_file.write_all(&line).await?;
+                manifest.cases += 1;
+                manifest.events += case["events"].as_array().map_or(0, Vec::len);
+
+                let case_id = case["ixBug"].as_u64().unwrap_or_default();
+                for (index, (event_id, attachment)) in
+                    case_attachments(&case).into_iter().enumerate()
+                {
+                    let path = attachment_path(case_id, index, &attachment);
+                    let (bytes, error) =
+                        match self.archive_attachment(&attachment, &dir.join(&path)).await {
+                            Ok(bytes) => (bytes, None),
+                            Err(error) => (0, Some(error.to_string())),
+                        };
+                    manifest.attachments.push(ArchivedAttachment {
+                        case_id,
+                        event_id,
+                        file_name: attachment.file_name,
+                        path,
+                        bytes,
+                        error,
+                    });
+                }
+            }
+        }
+        cases_file.shutdown().await?;
+
+        let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+        tokio::fs::write(dir.join("manifest.json"), manifest_json).await?;
+        Ok(manifest)
+    }
+
+    /// Recreate the cases of an `archive_project` bundle in `target_project`,
+    /// e.g. on another FogBugz instance. Each case is created from its opening
+    /// event; later comments and attachments are added as edits, with the
+    /// original author and time at the top of the text. Cases closed in the
+    /// archive are resolved and closed again. On failure the error carries the
+    /// report so far, for `resume_import_archive`.
+    pub async fn import_archive(
+        &self,
+        bundle: impl AsRef<Path>,
+        target_project: u32,
+    ) -> Result<ImportReport, ImportError> {
+        self.resume_import_archive(bundle, target_project, ImportReport::default())
+            .await
+    }
+
+    /// Continue an import that stopped, skipping the cases and events `report`
+    /// already lists
+    pub async fn resume_import_archive(
+        &self,
+        bundle: impl AsRef<Path>,
+        target_project: u32,
+        mut report: ImportReport,
+    ) -> Result<ImportReport, ImportError> {
+        match self
+            .import_cases(bundle.as_ref(), target_project, &mut report)
+            .await
+        {
+            Ok(()) => Ok(report),
+            Err(source) => Err(ImportError { report, source }),
+        }
+    }
+
+    async fn import_cases(
+        &self,
+        bundle: &Path,
+        target_project: u32,
+        report: &mut ImportReport,
+    ) -> Result<(), ArchiveError> {
+        let manifest: ArchiveManifest =
+            serde_json::from_slice(&tokio::fs::read(bundle.join("manifest.json")).await?)?;
+        let cases_file = tokio::fs::File::open(bundle_path(bundle, &manifest.cases_file)?).await?;
+        let mut lines = BufReader::new(cases_file).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let case: Value = serde_json::from_str(&line)?;
+            let original_id = case["ixBug"].as_u64().unwrap_or_default();
+            let mut events = imported_events(&case).into_iter();
+            let opened = events.next();
+            let case_id = match report.cases.get(&original_id) {
+                Some(&case_id) => case_id,
+                None => {
+                    let (description, attachments) = match &opened {
+                        Some(opened) => (
+                            imported_text(original_id, opened),
+                            bundle_uploads(bundle, &manifest, original_id, opened.event_id).await?,
+                        ),
+                        None => (String::new(), Vec::new()),
+                    };
+                    let tags = case["tags"].as_array().map(|tags| {
+                        tags.iter()
+                            .filter_map(Value::as_str)
+                            .collect::<Vec<_>>()
+                            .join(",")
+                    });
+                    let attachment_count = attachments.len();
+                    let created = self
+                        .new_case()
+                        .title(case["sTitle"].as_str().unwrap_or_default().to_string())
+                        .description(description)
+                        .project_id(u64::from(target_project))
+                        .maybe_category(serde_json::from_value(case["ixCategory"].clone()).ok())
+                        .maybe_tags(tags.filter(|tags| !tags.is_empty()))
+                        .attachments(attachments)
+                        .build()
+                        .send()
+                        .await?;
+                    report.cases.insert(original_id, created.case_id);
+                    report.events.extend(opened.map(|opened| opened.event_id));
+                    report.attachments += attachment_count;
+                    created.case_id
+                }
+            };
+
+            for event in events {
+                if report.events.contains(&event.event_id) {
+                    continue;
+                }
+                let attachments =
+                    bundle_uploads(bundle, &manifest, original_id, event.event_id).await?;
+                let attachment_count = attachments.len();
+                self.edit_case()
+                    .case_id(case_id)
+                    .event(imported_text(original_id, &event))
+                    .attachments(attachments)
+                    .build()
+                    .send()
+                    .await?;
+                report.events.insert(event.event_id);
+                report.comments += 1;
+                report.attachments += attachment_count;
+            }
+            if case["fOpen"] == Value::Bool(false) && !report.closed.contains(&original_id) {
+                self.resolve_and_close(case_id, None, None).await?;
+                report.closed.insert(original_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Stream one attachment to `path`, returning its size
+    async fn archive_attachment(
+        &self,
+        attachment: &Attachment,
+        path: &Path,
+    ) -> Result<u64, ArchiveError> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut chunks = std::pin::pin!(self.download_attachment_stream(attachment).await?);
+        let mut file = tokio::fs::File::create(path).await?;
+        let mut bytes = 0;
+        while let Some(chunk) = chunks.try_next().await? {
+            file.write_all(&chunk).await?;
+            bytes += chunk.len() as u64;
+        }
+        file.shutdown().await?;
+        Ok(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_paths() {
+        let case = serde_json::json!({
+            "ixBug": 42,
+            "events": [
+                { "ixBugEvent": 7, "attachments": [
+                    { "sFileName": "../secret:log.txt",
+                      "sURL": "default.asp?pg=pgDownload&amp;ixBugEvent=7&amp;ixAttachment=301" }
+                ]},
+                { "ixBugEvent": 8 },
+                { "ixBugEvent": 9, "attachments": [
+                    { "sFileName": "..", "sURL": "default.asp?pg=pgDownload" }
+                ]}
+            ]
+        });
+        let paths: Vec<(Option<u64>, String)> = case_attachments(&case)
+            .iter()
+            .enumerate()
+            .map(|(index, (event_id, attachment))| {
+                (*event_id, attachment_path(42, index, attachment))
+            })
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                (Some(7), "attachments/42/301-_secret_log.txt".to_string()),
+                (Some(9), "attachments/42/n1-attachment".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_imported_events() {
+        let case = serde_json::json!({
+            "ixBug": 42,
+            "events": [
+                { "ixBugEvent": 1, "evt": 1, "evtDescription": "Opened by Jane Doe",
+                  "dt": "2019-03-04T09:30:00Z", "ixPerson": 3, "sPerson": "Jane Doe",
+                  "ixPersonAssignedTo": 3, "s": "" },
+                { "ixBugEvent": 2, "evt": 3, "evtDescription": "Assigned to John Roe by Jane Doe",
+                  "dt": "2019-03-04T09:31:00Z", "ixPerson": 3, "sPerson": "Jane Doe",
+                  "ixPersonAssignedTo": 4, "s": "" },
+                { "ixBugEvent": 3, "evt": 2, "evtDescription": "Edited by John Roe",
+                  "dt": "2019-03-05T14:00:00Z", "ixPerson": 4, "sPerson": "John Roe",
+                  "ixPersonAssignedTo": 4, "s": "Can't reproduce on 2.1" }
+            ]
+        });
+        let texts: Vec<String> = imported_events(&case)
+            .iter()
+            .map(|event| imported_text(42, event))
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                "[Imported from case 42: Opened by Jane Doe on 2019-03-04 09:30 UTC]",
+                "[Imported from case 42: Edited by John Roe on 2019-03-05 14:00 UTC]\n\nCan't reproduce on 2.1",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bundle_path() {
+        let bundle = Path::new("/tmp/bundle");
+        assert_eq!(
+            bundle_path(bundle, "attachments/42/301-log.txt").unwrap(),
+            Path::new("/tmp/bundle/attachments/42/301-log.txt")
+        );
+        for path in [
+            "",
+            "/etc/passwd",
+            "../secret",
+            "attachments/../../secret",
+            "./cases.jsonl",
+        ] {
+            assert!(
+                matches!(bundle_path(bundle, path), Err(ArchiveError::UnsafePath(_))),
+                "{path}"
+            );
+        }
+    }
+}
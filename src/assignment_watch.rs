@@ -0,0 +1,275 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Duration,
+};
+
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use futures::{Stream, stream};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    FogBugzClient, ResponseError,
+    change_feed::edited_since,
+    enums::Column,
+    shutdown::{ShutdownSignal, sleep_unless_shutdown},
+};
+
+/// A case's assignee changed between two polls
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AssignmentChanged {
+    pub case_id: u64,
+    /// Previous assignee; `None` if the case is new to the watched search
+    pub from: Option<String>,
+    pub to: String,
+    pub to_id: Option<u64>,
+    /// `dtLastUpdated` of the case when the change was seen
+    pub updated: Option<DateTime<Utc>>,
+}
+
+/// Who a case is assigned to
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Assignee {
+    id: Option<u64>,
+    name: String,
+}
+
+/// Assignee of every case seen so far
+type Assignments = HashMap<u64, Assignee>;
+
+/// Record a poll's cases in `assignments` and return the cases whose assignee
+/// changed, ordered by case ID. Assignees are compared by `ixPersonAssignedTo`,
+/// so renaming a person doesn't count as reassigning their cases.
+fn diff_assignments(assignments: &mut Assignments, cases: &[Value]) -> Vec<AssignmentChanged> {
+    let mut changes = Vec::new();
+    for case in cases {
+        let Some(case_id) = case["ixBug"].as_u64() else {
+            continue;
+        };
+        let assignee = Assignee {
+            id: case[Column::PersonAssignedToId.as_ref()].as_u64(),
+            name: case[Column::PersonAssignedTo.as_ref()]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        };
+        let previous = assignments.insert(case_id, assignee.clone());
+        if previous.as_ref().map(|previous| previous.id) != Some(assignee.id) {
+            changes.push(AssignmentChanged {
+                case_id,
+                from: previous.map(|previous| previous.name),
+                to: assignee.name,
+                to_id: assignee.id,
+                updated: case[Column::LastUpdated.as_ref()]
+                    .as_str()
+                    .and_then(|updated| updated.parse().ok()),
+            });
+        }
+    }
+    changes.sort_by_key(|change| change.case_id);
+    changes
+}
+
+/// What the watcher knows after its first poll
+struct WatchState {
+    assignments: Assignments,
+    /// When the last successful poll started
+    polled_at: DateTime<Utc>,
+}
+
+/// Polls a search for assignee changes only, fetching just the assignment
+/// columns. The first poll records the current assignees without reporting them;
+/// later polls only fetch the cases edited since the previous one.
+#[derive(Debug, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct AssignmentWatcher {
+    /// Search query of the cases to watch, e.g. a project or a saved filter's query (required)
+    #[builder(into)]
+    query: String,
+    /// Pause between polls
+    #[builder(default = Duration::from_secs(60))]
+    interval: Duration,
+    /// Ends the watch once fired (optional)
+    shutdown: Option<ShutdownSignal>,
+    /// API instance
+    client: FogBugzClient,
+}
+
+impl AssignmentWatcher {
+    async fn poll(&self, since: Option<DateTime<Utc>>) -> Result<Vec<Value>, ResponseError> {
+        let query = match since {
+            Some(since) => format!("{} ({})", edited_since(since), self.query),
+            None => self.query.clone(),
+        };
+        self.client
+            .search()
+            .query(query)
+            .cols(vec![
+                Column::CaseId.to_string(),
+                Column::PersonAssignedTo.to_string(),
+                Column::PersonAssignedToId.to_string(),
+                Column::LastUpdated.to_string(),
+            ])
+            .build()
+            .send_all()
+            .await
+    }
+
+    /// Assignment changes as they are found, until shut down. A failed poll, the
+    /// first one included, yields its error and the watcher carries on after the
+    /// interval. On shutdown, the changes of the poll in flight are still handed
+    /// out before the stream ends.
+    pub fn stream(&self) -> impl Stream<Item = Result<AssignmentChanged, ResponseError>> + '_ {
+        stream::unfold(
+            (None::<WatchState>, VecDeque::new(), false),
+            move |(mut state, mut pending, mut polled)| async move {
+                loop {
+                    if let Some(change) = pending.pop_front() {
+                        return Some((Ok(change), (state, pending, polled)));
+                    }
+                    let stop = if polled {
+                        sleep_unless_shutdown(self.interval, self.shutdown.as_ref()).await
+                    } else {
+                        self.shutdown
+                            .as_ref()
+                            .is_some_and(ShutdownSignal::is_shutdown)
+                    };
+                    if stop {
+                        return None;
+                    }
+                    let polled_at = Utc::now();
+                    polled = true;
+                    match self.poll(state.as_ref().map(|state| state.polled_at)).await {
+                        Ok(cases) => match &mut state {
+                            Some(state) => {
+                                pending.extend(diff_assignments(&mut state.assignments, &cases));
+                                state.polled_at = polled_at;
+                            }
+                            None => {
+                                let mut assignments = Assignments::new();
+                                diff_assignments(&mut assignments, &cases);
+                                state = Some(WatchState {
+                                    assignments,
+                                    polled_at,
+                                });
+                            }
+                        },
+                        Err(e) => return Some((Err(e), (state, pending, polled))),
+                    }
+                }
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assignee(id: u64, name: &str) -> Assignee {
+        Assignee {
+            id: Some(id),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_diff_assignments() {
+        let mut assignments =
+            Assignments::from([(1, assignee(7, "Jane")), (2, assignee(8, "Bob"))]);
+        let cases = [
+            serde_json::json!({"ixBug": 2, "sPersonAssignedTo": "Jane", "ixPersonAssignedTo": 7, "dtLastUpdated": "2024-03-04T10:00:00Z"}),
+            serde_json::json!({"ixBug": 1, "sPersonAssignedTo": "Jane Doe", "ixPersonAssignedTo": 7}),
+            serde_json::json!({"ixBug": 3, "sPersonAssignedTo": "Bob", "ixPersonAssignedTo": 8}),
+        ];
+        let changes = diff_assignments(&mut assignments, &cases);
+        assert_eq!(assignments.len(), 3);
+        assert_eq!(assignments[&1], assignee(7, "Jane Doe"));
+        assert_eq!(
+            changes,
+            vec![
+                AssignmentChanged {
+                    case_id: 2,
+                    from: Some("Bob".to_string()),
+                    to: "Jane".to_string(),
+                    to_id: Some(7),
+                    updated: Some("2024-03-04T10:00:00Z".parse().unwrap()),
+                },
+                AssignmentChanged {
+                    case_id: 3,
+                    from: None,
+                    to: "Bob".to_string(),
+                    to_id: Some(8),
+                    updated: None,
+                },
+            ]
+        );
+
+        // Cases not edited since the last poll keep their assignee
+        let cases =
+            [serde_json::json!({"ixBug": 3, "sPersonAssignedTo": "Jane", "ixPersonAssignedTo": 7})];
+        let changes = diff_assignments(&mut assignments, &cases);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].from.as_deref(), Some("Bob"));
+        assert_eq!(assignments.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_later_polls_only_fetch_edited_cases() {
+        use std::sync::{Arc, Mutex};
+
+        use crate::interceptor::RequestContext;
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let log = sent.clone();
+        let client = FogBugzClient::new("http://localhost:1", "token").with_interceptor(
+            move |request: &mut RequestContext<'_>| {
+                log.lock().unwrap().push(request.payload["q"].clone());
+            },
+        );
+        let watcher = AssignmentWatcher::builder()
+            .query("project:Inbox OR project:Ops")
+            .client(client)
+            .build();
+        assert!(watcher.poll(None).await.is_err());
+        let since = "2024-03-04T00:30:00Z".parse().unwrap();
+        assert!(watcher.poll(Some(since)).await.is_err());
+
+        let sent = sent.lock().unwrap();
+        assert!(sent[0].as_str().unwrap().starts_with("project:Inbox"));
+        assert!(
+            sent.last()
+                .unwrap()
+                .as_str()
+                .unwrap()
+                .starts_with("edited:\"03/03/2024..now\" (project:Inbox OR project:Ops)")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_polls_wait_for_the_interval() {
+        use std::time::Instant;
+
+        use futures::StreamExt;
+
+        use crate::mock_server::MockServer;
+
+        let server = MockServer::start(
+            |_| serde_json::json!({ "errors": [{ "message": "Server busy", "code": 99 }] }),
+        )
+        .await;
+        let watcher = AssignmentWatcher::builder()
+            .query("project:Inbox")
+            .interval(Duration::from_millis(50))
+            .client(FogBugzClient::new(server.url(), "token"))
+            .build();
+        let mut changes = Box::pin(watcher.stream());
+
+        assert!(changes.next().await.unwrap().is_err());
+        let failed = Instant::now();
+        assert!(changes.next().await.unwrap().is_err());
+        assert!(failed.elapsed() >= Duration::from_millis(50));
+        assert_eq!(server.requests().len(), 2);
+    }
+}
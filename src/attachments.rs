@@ -0,0 +1,165 @@
+use bytes::Bytes;
+use futures::{Stream, stream};
+use reqwest::Url;
+
+use crate::{FogBugzClient, ResponseError, case_details::Attachment};
+
+impl Attachment {
+    /// Value of a query parameter in the attachment's download URL
+    fn url_param(&self, name: &str) -> Option<u64> {
+        let url = self.url.replace("&amp;", "&");
+        let query = url.split_once('?')?.1;
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .and_then(|(_, value)| value.parse().ok())
+    }
+
+    /// `ixAttachment`, taken from the download URL
+    pub fn attachment_id(&self) -> Option<u64> {
+        self.url_param("ixAttachment")
+    }
+
+    /// `ixBugEvent` of the event the file is attached to, taken from the download URL
+    pub fn event_id(&self) -> Option<u64> {
+        self.url_param("ixBugEvent")
+    }
+}
+
+impl FogBugzClient {
+    /// Absolute download URL for an attachment, authenticated with the client's token.
+    /// FogBugz returns attachment URLs relative to the site and HTML-escaped.
+    /// URLs pointing off the site are rejected rather than handed the token.
+    pub fn attachment_url(&self, attachment: &Attachment) -> Result<Url, ResponseError> {
+        let mut url = self.attachment_site_url(attachment)?;
+        if url.origin() != Url::parse(&self.url)?.origin() {
+            return Err(ResponseError::AttachmentError(format!(
+                "{} points to another site: {}",
+                attachment.file_name, url
+            )));
+        }
+        url.query_pairs_mut().append_pair("token", &self.token());
+        Ok(url)
+    }
+
+    /// Absolute attachment URL without a token, for sharing with people who log on
+    pub(crate) fn attachment_site_url(
+        &self,
+        attachment: &Attachment,
+    ) -> Result<Url, ResponseError> {
+        let relative = attachment.url.replace("&amp;", "&");
+        Ok(Url::parse(&self.url)?.join(&relative)?)
+    }
+
+    async fn request_attachment(
+        &self,
+        attachment: &Attachment,
+    ) -> Result<reqwest::Response, ResponseError> {
+        let url = self.attachment_url(attachment)?;
+        self.acquire_permit().await;
+        // reqwest follows FogBugz's redirects to the file storage; ending up on
+        // the logon page means the token was rejected
+        let headers = self.sign_request(&mut serde_json::Value::Null);
+        let response = self.client.get(url).headers(headers).send().await?;
+        if !response.status().is_success() {
+            return Err(ResponseError::AttachmentError(format!(
+                "downloading {} returned {}",
+                attachment.file_name,
+                response.status()
+            )));
+        }
+        if response
+            .url()
+            .query()
+            .is_some_and(|query| query.contains("pg=pgLogon"))
+        {
+            return Err(ResponseError::AttachmentError(format!(
+                "{}: not authorized, redirected to logon",
+                attachment.file_name
+            )));
+        }
+        Ok(response)
+    }
+
+    /// Download an attachment into memory
+    pub async fn download_attachment(
+        &self,
+        attachment: &Attachment,
+    ) -> Result<Bytes, ResponseError> {
+        let response = self.request_attachment(attachment).await?;
+        Ok(response.bytes().await?)
+    }
+
+    /// Download an attachment chunk by chunk, for files too large to hold in memory
+    pub async fn download_attachment_stream(
+        &self,
+        attachment: &Attachment,
+    ) -> Result<impl Stream<Item = Result<Bytes, ResponseError>> + use<>, ResponseError> {
+        let response = self.request_attachment(attachment).await?;
+        let chunks = stream::try_unfold(response, |mut response| async move {
+            let chunk = response.chunk().await?;
+            Ok::<_, ResponseError>(chunk.map(|chunk| (chunk, response)))
+        });
+        Ok(chunks)
+    }
+
+    /// Delete an attachment from its event, on sites that expose `deleteAttachment`.
+    /// FogBugz records the deletion as a `DeletedAttachment` event on the case.
+    pub async fn delete_attachment(
+        &self,
+        case_id: u64,
+        attachment: &Attachment,
+    ) -> Result<(), ResponseError> {
+        let (Some(event_id), Some(attachment_id)) =
+            (attachment.event_id(), attachment.attachment_id())
+        else {
+            return Err(ResponseError::AttachmentError(format!(
+                "{}: attachment URL has no ixBugEvent/ixAttachment",
+                attachment.file_name
+            )));
+        };
+        let params = serde_json::json!({
+            "ixBug": case_id,
+            "ixBugEvent": event_id,
+            "ixAttachment": attachment_id,
+        });
+        self.send_command("deleteAttachment", params).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attachment_url() {
+        let api = FogBugzClient::new("https://example.fogbugz.com/", "secret");
+        let attachment = Attachment {
+            file_name: "log.txt".to_string(),
+            url: "default.asp?pg=pgDownload&amp;pgType=pgFile&amp;ixBugEvent=12&amp;ixAttachment=34&amp;sFileName=log.txt".to_string(),
+        };
+        assert_eq!(
+            api.attachment_url(&attachment).unwrap().as_str(),
+            "https://example.fogbugz.com/default.asp?pg=pgDownload&pgType=pgFile&ixBugEvent=12&ixAttachment=34&sFileName=log.txt&token=secret"
+        );
+        assert_eq!(attachment.event_id(), Some(12));
+        assert_eq!(attachment.attachment_id(), Some(34));
+
+        for url in [
+            "https://attacker.example/steal?ixAttachment=34",
+            "//attacker.example/steal",
+            "http://example.fogbugz.com/default.asp?ixAttachment=34",
+        ] {
+            let attachment = Attachment {
+                file_name: "log.txt".to_string(),
+                url: url.to_string(),
+            };
+            assert!(matches!(
+                api.attachment_url(&attachment),
+                Err(ResponseError::AttachmentError(_))
+            ));
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::sync::{Arc, RwLock};
+
+use thiserror::Error;
+
+use crate::{FogBugzClient, ResponseError, api_client::FogBugzApiError};
+
+#[derive(Debug, Error)]
+pub enum AuthError {
+    #[error("Incorrect email or password")]
+    InvalidCredentials,
+    #[error("Session expired and could not be renewed; log on again")]
+    SessionExpired,
+    #[error("Logon response did not contain a token")]
+    MissingToken,
+}
+
+/// Token obtained with `logon`, plus the credentials used to renew it
+#[derive(Clone)]
+pub(crate) struct Session {
+    token: String,
+    email: String,
+    password: String,
+}
+
+/// Session state shared by all clones of a client
+pub(crate) type SharedSession = Arc<RwLock<Option<Session>>>;
+
+/// API key set with `set_api_key`, shared by all clones of a client
+pub(crate) type SharedApiKey = Arc<RwLock<Option<String>>>;
+
+impl FogBugzClient {
+    /// Token sent with every command: the session token after `logon`, the API key otherwise
+    pub(crate) fn token(&self) -> String {
+        self.session
+            .read()
+            .ok()
+            .and_then(|session| session.as_ref().map(|session| session.token.clone()))
+            .unwrap_or_else(|| self.current_api_key())
+    }
+
+    /// The API key commands are sent with: the latest one passed to `set_api_key`,
+    /// or the one the client was built with
+    pub fn current_api_key(&self) -> String {
+        self.rotated_api_key
+            .read()
+            .ok()
+            .and_then(|key| key.clone())
+            .unwrap_or_else(|| self.api_key.clone())
+    }
+
+    /// Replace the API key for this client and all of its clones, e.g. when a
+    /// long-running service rotates its token. Requests already sent keep the old key;
+    /// the HTTP connection pool is kept.
+    pub fn set_api_key(&self, api_key: impl Into<String>) {
+        if let Ok(mut key) = self.rotated_api_key.write() {
+            *key = Some(api_key.into());
+        }
+    }
+
+    /// Whether commands use a session token obtained with `logon`
+    pub fn is_logged_on(&self) -> bool {
+        self.session
+            .read()
+            .map(|session| session.is_some())
+            .unwrap_or(false)
+    }
+
+    /// Log on with email and password instead of an API token. The session token
+    /// is shared by all clones of this client and renewed automatically when it
+    /// expires, so the credentials are kept in memory until `logoff`.
+    pub async fn logon(
+        &self,
+        email: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Result<(), ResponseError> {
+        let email = email.into();
+        let password = password.into();
+        let token = self.request_token(&email, &password).await?;
+        if let Ok(mut session) = self.session.write() {
+            *session = Some(Session {
+                token,
+                email,
+                password,
+            });
+        }
+        Ok(())
+    }
+
+    /// End the session started by `logon` and forget the credentials
+    pub async fn logoff(&self) -> Result<(), ResponseError> {
+        if !self.is_logged_on() {
+            return Ok(());
+        }
+        let result = self.send_command("logoff", serde_json::json!({})).await;
+        if let Ok(mut session) = self.session.write() {
+            *session = None;
+        }
+        result.map(|_| ())
+    }
+
+    async fn request_token(&self, email: &str, password: &str) -> Result<String, ResponseError> {
+        let params = serde_json::json!({
+            "email": email,
+            "password": password,
+        });
+        let response = match self.send_command_once("logon", params).await {
+            Err(ResponseError::FogbugzError(FogBugzApiError::LogonFailed { .. })) => {
+                return Err(AuthError::InvalidCredentials.into());
+            }
+            response => response?,
+        };
+        response["data"]["token"]
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| AuthError::MissingToken.into())
+    }
+
+    /// Log on again with the stored credentials after the session token expired.
+    /// Returns false if there is no session to renew.
+    pub(crate) async fn renew_session(&self) -> Result<bool, ResponseError> {
+        let credentials = self.session.read().ok().and_then(|session| {
+            session
+                .as_ref()
+                .map(|session| (session.email.clone(), session.password.clone()))
+        });
+        let Some((email, password)) = credentials else {
+            return Ok(false);
+        };
+        let token = self.request_token(&email, &password).await?;
+        if let Ok(mut session) = self.session.write()
+            && let Some(session) = session.as_mut()
+        {
+            session.token = token;
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_token_replaces_api_key() {
+        let api = FogBugzClient::new("https://example.com", "api_key");
+        assert_eq!(api.token(), "api_key");
+
+        let clone = api.clone();
+        *api.session.write().unwrap() = Some(Session {
+            token: "session".to_string(),
+            email: "jane@example.com".to_string(),
+            password: "secret".to_string(),
+        });
+        // Clones share the session
+        assert!(clone.is_logged_on());
+        assert_eq!(clone.token(), "session");
+    }
+
+    #[test]
+    fn test_rotated_api_key_is_shared() {
+        let api = FogBugzClient::new("https://example.com", "old_key");
+        let clone = api.read_only();
+        api.set_api_key("new_key");
+        assert_eq!(clone.token(), "new_key");
+        assert_eq!(clone.current_api_key(), "new_key");
+        assert_eq!(clone.api_key, "old_key");
+    }
+}
@@ -0,0 +1,208 @@
+use bon::Builder;
+use futures::{StreamExt, stream};
+
+use crate::{
+    FogBugzClient, ResponseError,
+    case_management::CaseResponse,
+    progress::{ProgressCallback, ProgressTracker},
+};
+
+/// Field changes applied by `BatchOperation::Edit`; unset fields are left alone
+#[derive(Debug, Clone, Default)]
+pub struct BatchEdit {
+    pub project_id: Option<u64>,
+    pub area: Option<String>,
+    pub priority: Option<u64>,
+    pub milestone: Option<u64>,
+    pub add_tags: Vec<String>,
+    pub remove_tags: Vec<String>,
+}
+
+/// The operation applied to every case of a batch
+#[derive(Debug, Clone)]
+pub enum BatchOperation {
+    /// Only post the batch comment
+    Comment,
+    Edit(BatchEdit),
+    Assign {
+        person_id: u64,
+    },
+    /// Resolve, with the category's default status if `status_id` is `None`
+    Resolve {
+        status_id: Option<u64>,
+    },
+    Close,
+    Reactivate,
+}
+
+/// What happened to one case of a batch
+#[derive(Debug)]
+pub struct BatchResult {
+    pub case_id: u64,
+    pub result: Result<CaseResponse, ResponseError>,
+}
+
+#[derive(Debug)]
+pub struct BatchReport {
+    /// One result per case, in the order the cases were given
+    pub results: Vec<BatchResult>,
+}
+
+impl BatchReport {
+    pub fn succeeded(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.result.is_ok())
+            .count()
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &BatchResult> {
+        self.results.iter().filter(|result| result.result.is_err())
+    }
+}
+
+/// Apply the same operation to many cases, a few at a time. Requests still go
+/// through the client's rate limiter, so `concurrency` only bounds how many are
+/// in flight; a failure on one case doesn't stop the others.
+#[derive(Debug, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct BatchEditRequest {
+    /// Cases to change (required)
+    #[builder(into)]
+    case_ids: Vec<u64>,
+    /// Operation to apply (required)
+    operation: BatchOperation,
+    /// Comment posted with the operation on every case (optional)
+    #[builder(into)]
+    comment: Option<String>,
+    /// Requests in flight at once
+    #[builder(default = 4)]
+    concurrency: usize,
+    /// Called after each case is processed
+    #[builder(into)]
+    on_progress: Option<ProgressCallback>,
+    /// API instance
+    client: FogBugzClient,
+}
+
+impl BatchEditRequest {
+    async fn apply(&self, case_id: u64) -> Result<CaseResponse, ResponseError> {
+        let comment = self.comment.clone();
+        match &self.operation {
+            BatchOperation::Comment => {
+                self.client
+                    .edit_case()
+                    .case_id(case_id)
+                    .maybe_event(comment)
+                    .build()
+                    .send()
+                    .await
+            }
+            BatchOperation::Edit(edit) => {
+                let add_tags: Vec<&str> = edit.add_tags.iter().map(String::as_str).collect();
+                let remove_tags: Vec<&str> = edit.remove_tags.iter().map(String::as_str).collect();
+                self.client
+                    .edit_case()
+                    .case_id(case_id)
+                    .maybe_event(comment)
+                    .maybe_project_id(edit.project_id)
+                    .maybe_area(edit.area.clone())
+                    .maybe_priority(edit.priority)
+                    .maybe_milestone(edit.milestone)
+                    .add_tags(&add_tags)
+                    .remove_tags(&remove_tags)
+                    .build()
+                    .send()
+                    .await
+            }
+            BatchOperation::Assign { person_id } => {
+                self.client
+                    .assign_case()
+                    .case_id(case_id)
+                    .assigned_to_id(*person_id)
+                    .maybe_event(comment)
+                    .build()
+                    .send()
+                    .await
+            }
+            BatchOperation::Resolve { status_id } => {
+                self.client
+                    .resolve_case()
+                    .case_id(case_id)
+                    .maybe_status_id(*status_id)
+                    .maybe_event(comment)
+                    .build()
+                    .send()
+                    .await
+            }
+            BatchOperation::Close => {
+                self.client
+                    .close_case()
+                    .case_id(case_id)
+                    .maybe_event(comment)
+                    .build()
+                    .send()
+                    .await
+            }
+            BatchOperation::Reactivate => {
+                self.client
+                    .reactivate_case()
+                    .case_id(case_id)
+                    .maybe_event(comment)
+                    .build()
+                    .send()
+                    .await
+            }
+        }
+    }
+
+    /// Apply the operation to every case
+    pub async fn send(&self) -> BatchReport {
+        let mut tracker = ProgressTracker::new(
+            self.on_progress.as_ref(),
+            self.case_ids.len(),
+            self.client.min_request_interval(),
+        );
+        let mut results = Vec::with_capacity(self.case_ids.len());
+        let mut pending = stream::iter(&self.case_ids)
+            .map(|&case_id| async move {
+                BatchResult {
+                    case_id,
+                    result: self.apply(case_id).await,
+                }
+            })
+            .buffered(self.concurrency.max(1));
+        while let Some(result) = pending.next().await {
+            tracker.advance(format_args!("case {}", result.case_id));
+            results.push(result);
+        }
+        BatchReport { results }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_batch_reports_each_case_in_order() {
+        // A read-only client rejects every write without touching the network
+        let api = FogBugzClient::new("https://example.com", "test_key").read_only();
+        let report = api
+            .batch_edit()
+            .case_ids(vec![3, 1, 2])
+            .operation(BatchOperation::Close)
+            .concurrency(2)
+            .build()
+            .send()
+            .await;
+        let ids: Vec<u64> = report.results.iter().map(|result| result.case_id).collect();
+        assert_eq!(ids, vec![3, 1, 2]);
+        assert_eq!(report.succeeded(), 0);
+        assert!(
+            report
+                .failed()
+                .all(|result| matches!(result.result, Err(ResponseError::ReadOnly(_))))
+        );
+    }
+}
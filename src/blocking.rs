@@ -0,0 +1,186 @@
+use reqwest::blocking::{Client, Response};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    FogBugzClient, ResponseError,
+    api_client::{check_errors, error_from_body, search_batches},
+    policy::CommandPolicy,
+    retry::is_transient,
+};
+
+/// A FogBugz client for code that isn't async, sending commands with
+/// `reqwest::blocking` and no async runtime of its own. It takes the URL, token,
+/// read-only flag, command policy, `rate_limit`, retry policy and interceptors of
+/// the `FogBugzClient` it is made from; a `leaky-bucket` limiter is not applied
+/// and an expired `logon` session is not renewed.
+/// Must not be used from inside an async runtime.
+#[derive(Debug, Clone)]
+pub struct FogBugzBlockingClient {
+    client: FogBugzClient,
+    http: Client,
+}
+
+impl FogBugzBlockingClient {
+    pub fn new(url: impl Into<String>, api_key: impl Into<String>) -> Self {
+        Self::from_client(FogBugzClient::new(url, api_key))
+    }
+
+    pub fn new_from_env() -> Self {
+        Self::from_client(FogBugzClient::new_from_env())
+    }
+
+    /// Blocking client with the configuration of an async client
+    pub fn from_client(client: FogBugzClient) -> Self {
+        Self {
+            client,
+            http: Client::new(),
+        }
+    }
+
+    /// The async client the configuration comes from
+    pub fn client(&self) -> &FogBugzClient {
+        &self.client
+    }
+
+    /// Copy of this client that rejects mutating commands with `ResponseError::ReadOnly`
+    pub fn read_only(&self) -> Self {
+        Self {
+            client: self.client.read_only(),
+            http: self.http.clone(),
+        }
+    }
+
+    /// Copy of this client restricted to the commands `policy` permits
+    pub fn with_policy(&self, policy: CommandPolicy) -> Self {
+        Self {
+            client: self.client.with_policy(policy),
+            http: self.http.clone(),
+        }
+    }
+
+    /// Send a command to the FogBugz JSON API.
+    /// Transient failures are retried according to the client's `retry_policy`.
+    pub fn send_command<T: Serialize>(&self, cmd: &str, params: T) -> Result<Value, ResponseError> {
+        let params = serde_json::to_value(params)?;
+        let mut retry = 0;
+        loop {
+            let result = self.send_command_once(cmd, params.clone());
+            match (&self.client.retry_policy, &result) {
+                (Some(policy), Err(error)) if retry < policy.max_retries && is_transient(error) => {
+                    std::thread::sleep(policy.delay(retry));
+                    retry += 1;
+                }
+                _ => return result,
+            }
+        }
+    }
+
+    /// Cases matching `query` with the given columns. Queries longer than
+    /// `MAX_QUERY_LENGTH` are split into several searches if they are plain case
+    /// ID lists, and rejected with `TooLongQuery` otherwise.
+    pub fn search(&self, query: &str, cols: &[&str]) -> Result<Vec<Value>, ResponseError> {
+        let mut cases = Vec::new();
+        for batch in search_batches(query)? {
+            let mut response = self.send_command(
+                "search",
+                serde_json::json!({
+                    "q": batch,
+                    "cols": cols,
+                }),
+            )?;
+            if let Value::Array(more) = response["data"]["cases"].take() {
+                cases.extend(more);
+            }
+        }
+        Ok(cases)
+    }
+
+    /// Send a command once, without retrying
+    fn send_command_once(&self, cmd: &str, mut payload: Value) -> Result<Value, ResponseError> {
+        self.client.check_command(cmd)?;
+        let url = self.client.api_url()?;
+        if let Some(bucket) = &self.client.rate_limit {
+            bucket.acquire_blocking();
+        }
+
+        payload["cmd"] = cmd.into();
+        if cmd != "logon" {
+            payload["token"] = self.client.token().into();
+        }
+        let (headers, started) = self.client.prepare_request(cmd, &mut payload);
+
+        let result = self
+            .http
+            .post(url)
+            .header("Content-Type", "application/json")
+            .headers(headers)
+            .json(&payload)
+            .send()
+            .map_err(ResponseError::from)
+            .and_then(parse_response);
+        self.client.finish_request(cmd, started, &result);
+        result
+    }
+}
+
+/// Decode a FogBugz response, turning reported errors into `FogbugzError`
+fn parse_response(response: Response) -> Result<Value, ResponseError> {
+    let status = response.status();
+    if !status.is_success() {
+        return Err(error_from_body(status, response.text()?).into());
+    }
+    check_errors(status, response.json()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api_client::MAX_QUERY_LENGTH, mock_server::MockServer};
+
+    #[test]
+    fn test_blocking_client_checks_commands() {
+        let api = FogBugzBlockingClient::new("https://example.com", "test_key");
+        assert!(matches!(
+            api.read_only().send_command("edit", serde_json::json!({ "ixBug": 1 })),
+            Err(ResponseError::ReadOnly(cmd)) if cmd == "edit"
+        ));
+        assert!(matches!(
+            api.with_policy(CommandPolicy::deny(["search"])).search("1", &["sTitle"]),
+            Err(ResponseError::CommandNotAllowed(cmd)) if cmd == "search"
+        ));
+        assert!(api.read_only().client().is_read_only());
+    }
+
+    #[test]
+    fn test_blocking_search_splits_long_id_lists() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let server = runtime.block_on(MockServer::start(|payload| {
+            let cases: Vec<Value> = payload["q"]
+                .as_str()
+                .unwrap()
+                .split(',')
+                .map(|id| serde_json::json!({ "ixBug": id.parse::<u64>().unwrap() }))
+                .collect();
+            serde_json::json!({ "data": { "cases": cases }, "errors": [] })
+        }));
+        let api = FogBugzBlockingClient::new(server.url(), "token");
+        let ids: Vec<u64> = (1..=500).map(|id| 100_000 + id).collect();
+        let query = ids.iter().map(u64::to_string).collect::<Vec<_>>().join(",");
+        assert!(query.len() > MAX_QUERY_LENGTH);
+
+        let cases = api.search(&query, &["ixBug"]).unwrap();
+        let found: Vec<u64> = cases
+            .iter()
+            .filter_map(|case| case["ixBug"].as_u64())
+            .collect();
+        assert_eq!(found, ids);
+        let requests = server.requests();
+        assert!(requests.len() > 1);
+        assert!(
+            requests
+                .iter()
+                .all(|payload| payload["q"].as_str().unwrap().len() <= MAX_QUERY_LENGTH)
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use strum::Display;
+
+use crate::ResponseError;
+
+/// Safety limits for operations that may fetch many cases or send many requests,
+/// so an overly broad filter fails fast instead of pulling the whole tracker
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Budget {
+    pub max_cases: Option<usize>,
+    pub max_requests: Option<usize>,
+}
+
+impl Budget {
+    pub fn max_cases(max_cases: usize) -> Self {
+        Self {
+            max_cases: Some(max_cases),
+            ..Default::default()
+        }
+    }
+
+    pub fn max_requests(max_requests: usize) -> Self {
+        Self {
+            max_requests: Some(max_requests),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_max_cases(self, max_cases: usize) -> Self {
+        Self {
+            max_cases: Some(max_cases),
+            ..self
+        }
+    }
+
+    pub fn with_max_requests(self, max_requests: usize) -> Self {
+        Self {
+            max_requests: Some(max_requests),
+            ..self
+        }
+    }
+
+    /// `max` to ask FogBugz for: one more than the case budget, so going over
+    /// the budget is detected without downloading everything
+    pub(crate) fn cap_max(&self, max: Option<u32>) -> Option<u32> {
+        let Some(max_cases) = self.max_cases else {
+            return max;
+        };
+        let cap = u32::try_from(max_cases.saturating_add(1)).unwrap_or(u32::MAX);
+        Some(max.map_or(cap, |max| max.min(cap)))
+    }
+}
+
+/// Which budget limit was exceeded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Display)]
+pub enum BudgetLimit {
+    #[strum(to_string = "cases")]
+    Cases,
+    #[strum(to_string = "requests")]
+    Requests,
+}
+
+/// Counts cases and requests of one operation against a `Budget`
+#[derive(Debug, Default)]
+pub(crate) struct BudgetTracker {
+    budget: Budget,
+    cases: usize,
+    requests: usize,
+}
+
+impl BudgetTracker {
+    pub(crate) fn new(budget: Option<Budget>) -> Self {
+        Self {
+            budget: budget.unwrap_or_default(),
+            ..Default::default()
+        }
+    }
+
+    /// Account for a request about to be sent
+    pub(crate) fn request(&mut self) -> Result<(), ResponseError> {
+        self.requests += 1;
+        check(
+            BudgetLimit::Requests,
+            self.requests,
+            self.budget.max_requests,
+        )
+    }
+
+    /// Account for cases received
+    pub(crate) fn cases(&mut self, count: usize) -> Result<(), ResponseError> {
+        self.cases += count;
+        check(BudgetLimit::Cases, self.cases, self.budget.max_cases)
+    }
+}
+
+fn check(limit: BudgetLimit, used: usize, max: Option<usize>) -> Result<(), ResponseError> {
+    match max {
+        Some(max) if used > max => Err(ResponseError::BudgetExceeded { limit, max }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_tracker() {
+        let mut tracker = BudgetTracker::new(Some(Budget::max_cases(10).with_max_requests(2)));
+        assert!(tracker.request().is_ok());
+        assert!(tracker.cases(10).is_ok());
+        assert!(matches!(
+            tracker.cases(1),
+            Err(ResponseError::BudgetExceeded {
+                limit: BudgetLimit::Cases,
+                max: 10
+            })
+        ));
+        assert!(tracker.request().is_ok());
+        assert!(tracker.request().is_err());
+
+        let mut unlimited = BudgetTracker::new(None);
+        assert!(unlimited.cases(1_000_000).is_ok());
+    }
+
+    #[test]
+    fn test_cap_max() {
+        assert_eq!(Budget::default().cap_max(Some(50)), Some(50));
+        assert_eq!(Budget::max_cases(100).cap_max(None), Some(101));
+        assert_eq!(Budget::max_cases(100).cap_max(Some(50)), Some(50));
+    }
+}
@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+
+use bon::Builder;
+use serde_json::Value;
+
+use crate::{
+    FogBugzClient, ResponseError,
+    enums::Column,
+    progress::{ProgressCallback, ProgressTracker},
+    search::SearchRequest,
+    template::{Template, TemplateError},
+};
+
+/// Post a templated comment to many cases.
+///
+/// Placeholders are filled per case from its fields: `{{case_id}}`, `{{title}}`,
+/// `{{project}}`, `{{area}}` and `{{assigned_to}}`. Requests go through the
+/// client's rate limiter like any other command.
+#[derive(Debug, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct CommentCampaign {
+    /// Cases to comment on (required)
+    #[builder(into)]
+    case_ids: Vec<u64>,
+    /// Comment template (required)
+    #[builder(into)]
+    template: Template,
+    /// Render comments without posting them
+    #[builder(default)]
+    dry_run: bool,
+    /// Called after each case is processed
+    #[builder(into)]
+    on_progress: Option<ProgressCallback>,
+    /// API instance
+    client: FogBugzClient,
+}
+
+/// What happened to a single case in a campaign
+#[derive(Debug)]
+pub enum CampaignOutcome {
+    Posted,
+    DryRun,
+    CaseNotFound,
+    TemplateFailed(TemplateError),
+    Failed(ResponseError),
+}
+
+#[derive(Debug)]
+pub struct CampaignResult {
+    pub case_id: u64,
+    /// The rendered comment, if rendering succeeded
+    pub comment: Option<String>,
+    pub outcome: CampaignOutcome,
+}
+
+#[derive(Debug)]
+pub struct CampaignReport {
+    pub dry_run: bool,
+    pub results: Vec<CampaignResult>,
+}
+
+impl CampaignReport {
+    pub fn posted(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| matches!(result.outcome, CampaignOutcome::Posted))
+            .count()
+    }
+
+    pub fn failed(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| {
+                !matches!(
+                    result.outcome,
+                    CampaignOutcome::Posted | CampaignOutcome::DryRun
+                )
+            })
+            .count()
+    }
+}
+
+/// Template variables for a case as returned by search
+pub(crate) fn case_variables(case: &Value) -> HashMap<String, String> {
+    let text = |key: &str| match &case[key] {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    };
+    HashMap::from([
+        ("case_id".to_string(), text("ixBug")),
+        ("title".to_string(), text("sTitle")),
+        ("project".to_string(), text("sProject")),
+        ("area".to_string(), text("sArea")),
+        ("assigned_to".to_string(), text("sPersonAssignedTo")),
+    ])
+}
+
+impl CommentCampaign {
+    /// Render and (unless in dry-run mode) post the comment on every case
+    pub async fn send(&self) -> Result<CampaignReport, ResponseError> {
+        let mut results = Vec::with_capacity(self.case_ids.len());
+        if self.case_ids.is_empty() {
+            return Ok(CampaignReport {
+                dry_run: self.dry_run,
+                results,
+            });
+        }
+
+        let response = SearchRequest::for_cases(&self.client, &self.case_ids)
+            .with_cols(&[
+                Column::CaseId,
+                Column::Title,
+                Column::Project,
+                Column::Area,
+                Column::PersonAssignedTo,
+            ])
+            .send()
+            .await?;
+        let cases: HashMap<u64, &Value> = response["data"]["cases"]
+            .as_array()
+            .map(|cases| {
+                cases
+                    .iter()
+                    .filter_map(|case| Some((case["ixBug"].as_u64()?, case)))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut tracker = ProgressTracker::new(
+            self.on_progress.as_ref(),
+            self.case_ids.len(),
+            self.client.min_request_interval(),
+        );
+        for &case_id in &self.case_ids {
+            let result = self
+                .process_case(case_id, cases.get(&case_id).copied())
+                .await;
+            tracker.advance(format_args!("case {}", case_id));
+            results.push(result);
+        }
+
+        Ok(CampaignReport {
+            dry_run: self.dry_run,
+            results,
+        })
+    }
+
+    async fn process_case(&self, case_id: u64, case: Option<&Value>) -> CampaignResult {
+        let Some(case) = case else {
+            return CampaignResult {
+                case_id,
+                comment: None,
+                outcome: CampaignOutcome::CaseNotFound,
+            };
+        };
+        let comment = match self.template.render(&case_variables(case)) {
+            Ok(comment) => comment,
+            Err(e) => {
+                return CampaignResult {
+                    case_id,
+                    comment: None,
+                    outcome: CampaignOutcome::TemplateFailed(e),
+                };
+            }
+        };
+        let outcome = if self.dry_run {
+            CampaignOutcome::DryRun
+        } else {
+            match self
+                .client
+                .edit_case()
+                .case_id(case_id)
+                .event(comment.clone())
+                .build()
+                .send()
+                .await
+            {
+                Ok(_) => CampaignOutcome::Posted,
+                Err(e) => CampaignOutcome::Failed(e),
+            }
+        };
+        CampaignResult {
+            case_id,
+            comment: Some(comment),
+            outcome,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case_variables_render() {
+        let case = serde_json::json!({
+            "ixBug": 62020,
+            "sTitle": "Fix login page",
+            "sProject": "Widget Factory",
+            "sArea": "Misc",
+            "sPersonAssignedTo": "Jane Doe"
+        });
+        let template = Template::new("{{assigned_to}}: please triage #{{case_id}} ({{title}})");
+        assert_eq!(
+            template.render(&case_variables(&case)).unwrap(),
+            "Jane Doe: please triage #62020 (Fix login page)"
+        );
+    }
+}
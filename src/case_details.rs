@@ -1,12 +1,17 @@
+use std::{collections::HashMap, fmt};
+
 use bon::Builder;
 use chrono::{DateTime, Utc};
-use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::{
     FogBugzClient, ResponseError,
-    enums::{Category, Column, Priority, Status},
+    api_client::FogBugzApiError,
+    changes::{Changes, ChangesLanguage, parse_changes_in},
+    custom_fields::deserialize_custom_fields,
+    enums::{Category, Column, Priority, RequiredColumns, Status, add_required_columns},
+    search::SearchRequest,
 };
 
 #[derive(Debug, Serialize, Builder)]
@@ -33,6 +38,16 @@ impl<S: case_details_request_builder::State> CaseDetailsRequestBuilder<S> {
         self.cols = Some(cols.iter().map(|s| s.to_string()).collect());
         self
     }
+    /// Also fetch a custom field by its column name (see `custom_fields`)
+    pub fn custom_field(mut self, column: impl Into<String>) -> Self {
+        self.cols.get_or_insert_with(Vec::new).push(column.into());
+        self
+    }
+    /// Add the columns `T` needs to deserialize, keeping any already requested
+    pub fn require_columns_for<T: RequiredColumns>(mut self) -> Self {
+        add_required_columns::<T>(self.cols.get_or_insert_with(Vec::new));
+        self
+    }
     pub fn default_cols(mut self) -> Self {
         self.cols = Some(vec![
             Column::CaseId.to_string(),
@@ -65,7 +80,7 @@ pub struct Attachment {
     pub url: String,
 }
 
-#[derive(Debug, strum::Display)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::Display)]
 pub enum EventType {
     Opened = 1,
     Edited = 2,
@@ -128,6 +143,8 @@ impl<'de> Deserialize<'de> for EventType {
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Event {
+    #[serde(rename = "ixBugEvent", default)]
+    pub event_id: u64,
     #[serde(rename = "evt")]
     pub event_type: EventType,
     #[serde(rename = "evtDescription")]
@@ -143,6 +160,22 @@ pub struct Event {
     pub attachments: Option<Vec<Attachment>>,
     #[serde(rename = "s")]
     pub content: String,
+    /// Field changes made by this event, as English text
+    #[serde(rename = "sChanges", default, skip_serializing_if = "Option::is_none")]
+    pub changes_text: Option<String>,
+}
+
+impl Event {
+    /// Field changes made by this event, parsed from `sChanges` assuming an English site
+    pub fn changes(&self) -> Changes {
+        self.changes_in(Some(ChangesLanguage::English))
+    }
+
+    /// Field changes made by this event, parsed with the rules for the site language
+    /// (see `FogBugzClient::changes_language`)
+    pub fn changes_in(&self, language: Option<ChangesLanguage>) -> Changes {
+        parse_changes_in(self.changes_text.as_deref().unwrap_or_default(), language)
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -164,47 +197,173 @@ pub struct CaseDetails {
     #[serde(rename = "ixCategory")]
     pub category: Category,
     pub events: Vec<Event>,
-    #[serde(rename = "customFields", skip_serializing_if = "Option::is_none")]
-    pub custom_fields: Option<Vec<String>>,
+    /// Requested custom field columns (`plugin_customfields_*`) and their values
+    #[serde(flatten, deserialize_with = "deserialize_custom_fields")]
+    pub custom_fields: HashMap<String, serde_json::Value>,
+}
+
+impl RequiredColumns for CaseDetails {
+    fn required_columns() -> &'static [Column] {
+        &[
+            Column::CaseId,
+            Column::Title,
+            Column::Project,
+            Column::IsOpen,
+            Column::Area,
+            Column::Status,
+            Column::Priority,
+            Column::Category,
+            Column::Events,
+        ]
+    }
+}
+
+impl fmt::Display for CaseDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} [{}/{}] {} ({}, {}, {}, {})",
+            self.case_id,
+            self.project,
+            self.area,
+            self.title,
+            self.status,
+            self.category,
+            self.priority,
+            if self.is_open { "open" } else { "closed" }
+        )
+    }
 }
 
 impl CaseDetailsRequest {
     pub async fn send(&self) -> Result<CaseDetails, ResponseError> {
-        let url = Url::parse(&self.client.url)?.join("api/search")?;
-        #[cfg(feature = "leaky-bucket")]
-        if let Some(ref limiter) = self.client.limiter {
-            limiter.acquire_one().await;
+        let mut json = self.client.send_command("search", self).await?;
+        if let serde_json::Value::Array(events) = &mut json["data"]["cases"][0]["events"] {
+            events.retain(|event| matches!(event, serde_json::Value::Object(_)));
         }
-        let mut body = serde_json::to_value(self)?;
-        body["token"] = self.client.api_key.clone().into();
-        let response = self
-            .client
-            .client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .bearer_auth(&self.client.api_key)
-            .json(&body)
+        let case_details =
+            ResponseError::deserialize_cases::<CaseDetails>(json["data"]["cases"][0].take())?;
+        Ok(case_details)
+    }
+}
+
+/// Request for just the events of a case, filtered client-side, for changelog-style
+/// consumers that don't need the rest of the case
+#[derive(Debug, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct EventsRequest {
+    case_id: u64,
+    /// Only these event types (all if empty)
+    #[builder(default)]
+    event_types: Vec<EventType>,
+    /// Only events at or after this time
+    since: Option<DateTime<Utc>>,
+    /// Only events before this time
+    until: Option<DateTime<Utc>>,
+    client: FogBugzClient,
+}
+
+impl EventsRequest {
+    fn keep(&self, event: &Event) -> bool {
+        (self.event_types.is_empty() || self.event_types.contains(&event.event_type))
+            && self.since.is_none_or(|since| event.datetime >= since)
+            && self.until.is_none_or(|until| event.datetime < until)
+    }
+
+    pub async fn send(&self) -> Result<Vec<Event>, ResponseError> {
+        let mut response = SearchRequest::for_cases(&self.client, &[self.case_id])
+            .with_cols(&[Column::CaseId, Column::Events])
             .send()
             .await?;
+        let events = case_events(&mut response, self.case_id)?;
+        Ok(events
+            .into_iter()
+            .filter(|event| self.keep(event))
+            .collect())
+    }
+}
 
-        if response.status().is_success() {
-            let mut json: serde_json::Value = response.json().await?;
-            if let serde_json::Value::Array(events) = &mut json["data"]["cases"][0]["events"] {
-                events.retain(|event| matches!(event, serde_json::Value::Object(_)));
-            }
-            let case_details =
-                serde_json::from_value::<CaseDetails>(json["data"]["cases"][0].take())?;
-            Ok(case_details)
-        } else {
-            let json: serde_json::Value = response.json().await?;
-            Err(ResponseError::FogbugzError(json))
+/// Events of the only case in a search response; `CaseNotFound` if there is none
+fn case_events(
+    response: &mut serde_json::Value,
+    case_id: u64,
+) -> Result<Vec<Event>, ResponseError> {
+    let Some(case) = response["data"]["cases"]
+        .as_array_mut()
+        .and_then(|cases| cases.first_mut())
+    else {
+        return Err(FogBugzApiError::CaseNotFound {
+            message: format!("Case {case_id} not found"),
         }
-    }
+        .into());
+    };
+    let mut events = match case["events"].take() {
+        serde_json::Value::Array(events) => events,
+        _ => Vec::new(),
+    };
+    events.retain(|event| matches!(event, serde_json::Value::Object(_)));
+    Ok(serde_json::from_value(serde_json::Value::Array(events))?)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::FogBugzClient;
+    use super::*;
+
+    #[test]
+    fn test_events_request_filter() {
+        let events: Vec<Event> = serde_json::from_value(serde_json::json!([
+            {
+                "evt": 1, "evtDescription": "Opened", "dt": "2024-03-01T10:00:00Z",
+                "ixPerson": 2, "sPerson": "Jane Doe", "ixPersonAssignedTo": 2, "s": ""
+            },
+            {
+                "evt": 2, "evtDescription": "Edited", "dt": "2024-03-05T10:00:00Z",
+                "ixPerson": 2, "sPerson": "Jane Doe", "ixPersonAssignedTo": 2, "s": "More"
+            },
+            {
+                "evt": 6, "evtDescription": "Closed", "dt": "2024-03-09T10:00:00Z",
+                "ixPerson": 2, "sPerson": "Jane Doe", "ixPersonAssignedTo": 2, "s": ""
+            }
+        ]))
+        .unwrap();
+        let api = FogBugzClient::new("https://example.com", "test_key");
+        let request = api
+            .case_events()
+            .case_id(1)
+            .event_types(vec![EventType::Edited, EventType::Closed])
+            .until("2024-03-09T00:00:00Z".parse().unwrap())
+            .build();
+        let kept: Vec<_> = events.iter().filter(|event| request.keep(event)).collect();
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].event_type, EventType::Edited);
+
+        let all = api.case_events().case_id(1).build();
+        assert!(events.iter().all(|event| all.keep(event)));
+    }
+
+    #[test]
+    fn test_case_events_of_missing_case() {
+        let mut response = serde_json::json!({
+            "data": {
+                "cases": [{
+                    "ixBug": 1,
+                    "events": [{
+                        "evt": 1, "evtDescription": "Opened", "dt": "2024-03-01T10:00:00Z",
+                        "ixPerson": 2, "sPerson": "Jane Doe", "ixPersonAssignedTo": 2, "s": ""
+                    }]
+                }]
+            }
+        });
+        assert_eq!(case_events(&mut response, 1).unwrap().len(), 1);
+
+        let mut response = serde_json::json!({ "data": { "cases": [] } });
+        assert!(matches!(
+            case_events(&mut response, 1),
+            Err(ResponseError::FogbugzError(
+                FogBugzApiError::CaseNotFound { .. }
+            ))
+        ));
+    }
 
     #[tokio::test]
     async fn test_case_details_request() {
@@ -0,0 +1,205 @@
+use std::fmt::Write;
+
+use bon::Builder;
+
+use crate::{
+    case_details::{CaseDetails, Event},
+    template::escape_html,
+};
+
+/// Which events of a case go into the email
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum EventSelection {
+    /// Every event
+    All,
+    /// Events with text, skipping pure field changes
+    #[default]
+    Comments,
+    /// The last `n` events with text
+    LastComments(usize),
+    /// Exactly these events (`ixBugEvent`)
+    Ids(Vec<u64>),
+}
+
+impl EventSelection {
+    fn select<'a>(&self, events: &'a [Event]) -> Vec<&'a Event> {
+        let comments = || {
+            events
+                .iter()
+                .filter(|event| !event.content.trim().is_empty())
+        };
+        match self {
+            EventSelection::All => events.iter().collect(),
+            EventSelection::Comments => comments().collect(),
+            EventSelection::LastComments(n) => {
+                let comments: Vec<_> = comments().collect();
+                comments[comments.len().saturating_sub(*n)..].to_vec()
+            }
+            EventSelection::Ids(ids) => events
+                .iter()
+                .filter(|event| ids.contains(&event.event_id))
+                .collect(),
+        }
+    }
+}
+
+/// A case summary ready to send as a multipart email
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseEmail {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+/// Renders a case into an email for people without FogBugz access. Only names and
+/// text are included: no links into FogBugz and no attachment URLs.
+#[derive(Debug, Clone, Builder)]
+pub struct CaseEmailRenderer {
+    /// Events to include (defaults to those with text)
+    #[builder(default)]
+    events: EventSelection,
+    /// Include project, area, status, priority and category
+    #[builder(default = true)]
+    include_fields: bool,
+    /// Paragraph placed before the case, e.g. what the recipient is asked to do
+    #[builder(into)]
+    intro: Option<String>,
+    /// Prefix for the subject (defaults to none)
+    #[builder(into, default)]
+    subject_prefix: String,
+}
+
+impl CaseEmailRenderer {
+    pub fn render(&self, case: &CaseDetails) -> CaseEmail {
+        let subject = format!(
+            "{}Case {}: {}",
+            self.subject_prefix, case.case_id, case.title
+        );
+        let fields = [
+            ("Project", case.project.clone()),
+            ("Area", case.area.clone()),
+            ("Status", case.status.to_string()),
+            ("Priority", case.priority.to_string()),
+            ("Category", case.category.to_string()),
+        ];
+        let events = self.events.select(&case.events);
+
+        let mut text = String::new();
+        let mut html = String::from("<div>");
+        if let Some(intro) = &self.intro {
+            let _ = write!(text, "{intro}\n\n");
+            let _ = write!(html, "<p>{}</p>", paragraph(intro));
+        }
+        let _ = writeln!(text, "Case {}: {}", case.case_id, case.title);
+        let _ = write!(
+            html,
+            "<h2>Case {}: {}</h2>",
+            case.case_id,
+            escape_html(&case.title)
+        );
+        if self.include_fields {
+            html.push_str("<table>");
+            for (name, value) in &fields {
+                let _ = writeln!(text, "{name}: {value}");
+                let _ = write!(
+                    html,
+                    "<tr><th align=\"left\">{name}</th><td>{}</td></tr>",
+                    escape_html(value)
+                );
+            }
+            html.push_str("</table>");
+        }
+        for event in events {
+            let heading = format!(
+                "{} by {} on {}",
+                event.event_type,
+                event.person,
+                event.datetime.format("%Y-%m-%d %H:%M UTC")
+            );
+            let _ = write!(text, "\n--- {heading} ---\n");
+            let _ = write!(html, "<hr><p><b>{}</b></p>", escape_html(&heading));
+            let content = event.content.trim();
+            if !content.is_empty() {
+                let _ = writeln!(text, "{content}");
+                let _ = write!(html, "<p>{}</p>", paragraph(content));
+            }
+            let attachments: Vec<&str> = event
+                .attachments
+                .iter()
+                .flatten()
+                .map(|attachment| attachment.file_name.as_str())
+                .collect();
+            if !attachments.is_empty() {
+                let names = attachments.join(", ");
+                let _ = writeln!(text, "Attachments: {names}");
+                let _ = write!(html, "<p><i>Attachments: {}</i></p>", escape_html(&names));
+            }
+        }
+        html.push_str("</div>");
+
+        CaseEmail {
+            subject,
+            html,
+            text,
+        }
+    }
+}
+
+/// Escaped text with line breaks kept
+fn paragraph(text: &str) -> String {
+    escape_html(text)
+        .replace("\r\n", "\n")
+        .replace('\n', "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_case_email() {
+        let case: CaseDetails = serde_json::from_value(serde_json::json!({
+            "ixBug": 42,
+            "sTitle": "Export <fails>",
+            "sProject": "Widgets",
+            "fOpen": true,
+            "sArea": "Export",
+            "ixStatus": 1,
+            "ixPriority": 2,
+            "ixCategory": 1,
+            "events": [
+                {
+                    "ixBugEvent": 1, "evt": 1, "evtDescription": "Opened",
+                    "dt": "2024-03-04T10:30:00Z", "ixPerson": 2, "sPerson": "Jane Doe",
+                    "ixPersonAssignedTo": 3, "s": "Line one\nLine two",
+                    "attachments": [{ "sFileName": "log.txt", "sURL": "default.asp?x" }]
+                },
+                {
+                    "ixBugEvent": 2, "evt": 3, "evtDescription": "Assigned",
+                    "dt": "2024-03-04T11:00:00Z", "ixPerson": 2, "sPerson": "Jane Doe",
+                    "ixPersonAssignedTo": 4, "s": ""
+                }
+            ]
+        }))
+        .unwrap();
+        let email = CaseEmailRenderer::builder()
+            .intro("Please take a look")
+            .subject_prefix("[Escalation] ")
+            .build()
+            .render(&case);
+        assert_eq!(email.subject, "[Escalation] Case 42: Export <fails>");
+        assert!(email.html.contains("Export &lt;fails&gt;"));
+        assert!(email.html.contains("Line one<br>Line two"));
+        assert!(email.text.contains("Attachments: log.txt"));
+        assert!(!email.html.contains("default.asp"));
+        assert!(!email.text.contains("Assigned by"));
+
+        let all = CaseEmailRenderer::builder()
+            .events(EventSelection::All)
+            .include_fields(false)
+            .build()
+            .render(&case);
+        assert!(all.text.contains("Assigned by Jane Doe"));
+        assert!(!all.text.contains("Project:"));
+    }
+}
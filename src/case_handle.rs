@@ -0,0 +1,198 @@
+use crate::{
+    FogBugzClient, ResponseError,
+    api_client::FogBugzApiError,
+    case_details::CaseDetails,
+    case_management::{
+        AssignCaseResponse, CloseCaseResponse, EditCaseResponse, ResolveCaseResponse,
+    },
+    organization::PersonRef,
+    time_tracking::{StartWorkResponse, TimeInterval},
+};
+
+/// One case and the common operations on it, for scripts that would rather not
+/// assemble a request builder per step. Use the builders for anything these
+/// shortcuts don't cover, e.g. resolving to a specific status.
+#[derive(Debug, Clone)]
+pub struct CaseHandle {
+    case_id: u64,
+    client: FogBugzClient,
+}
+
+impl CaseHandle {
+    pub fn new(client: &FogBugzClient, case_id: u64) -> Self {
+        Self {
+            case_id,
+            client: client.clone(),
+        }
+    }
+
+    pub fn case_id(&self) -> u64 {
+        self.case_id
+    }
+
+    /// The case with its standard columns and events
+    pub async fn details(&self) -> Result<CaseDetails, ResponseError> {
+        self.client
+            .case_details()
+            .case_id(self.case_id)
+            .build()
+            .send()
+            .await
+    }
+
+    /// Assign the case to a person, by `ixPerson`, full name or email
+    pub async fn assign_to(
+        &self,
+        person: impl Into<PersonRef>,
+    ) -> Result<AssignCaseResponse, ResponseError> {
+        let person_id = self.client.resolve_person_ref(&person.into()).await?;
+        self.client
+            .assign_case()
+            .case_id(self.case_id)
+            .assigned_to_id(person_id.into())
+            .build()
+            .send()
+            .await
+    }
+
+    /// Resolve the case with its category's default resolved status
+    pub async fn resolve(&self) -> Result<ResolveCaseResponse, ResponseError> {
+        self.client
+            .resolve_case()
+            .case_id(self.case_id)
+            .build()
+            .send()
+            .await
+    }
+
+    /// Close the case; it must be resolved first
+    pub async fn close(&self) -> Result<CloseCaseResponse, ResponseError> {
+        self.client
+            .close_case()
+            .case_id(self.case_id)
+            .build()
+            .send()
+            .await
+    }
+
+    /// Add a comment without changing anything else
+    pub async fn add_comment(
+        &self,
+        comment: impl Into<String>,
+    ) -> Result<EditCaseResponse, ResponseError> {
+        self.client
+            .edit_case()
+            .case_id(self.case_id)
+            .event(comment)
+            .build()
+            .send()
+            .await
+    }
+
+    /// Start the current user's stopwatch on the case
+    pub async fn start_work(&self) -> Result<StartWorkResponse, ResponseError> {
+        self.client
+            .start_work()
+            .case_id(self.case_id_u32()?)
+            .build()
+            .send()
+            .await
+    }
+
+    /// Time intervals logged on the case
+    pub async fn intervals(&self) -> Result<Vec<TimeInterval>, ResponseError> {
+        let response = self
+            .client
+            .list_intervals()
+            .case_id(self.case_id)
+            .build()
+            .send()
+            .await?;
+        let intervals = serde_json::from_value(response["data"]["intervals"].clone())?;
+        Ok(intervals)
+    }
+
+    /// Commands that take `ixBug` as a 32-bit number
+    fn case_id_u32(&self) -> Result<u32, ResponseError> {
+        u32::try_from(self.case_id).map_err(|_| {
+            FogBugzApiError::CaseNotFound {
+                message: format!("Case {} is out of range", self.case_id),
+            }
+            .into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::Value;
+
+    use super::*;
+    use crate::interceptor::RequestContext;
+
+    /// Client that fails every command, recording what it was asked to send
+    fn recording_client() -> (FogBugzClient, Arc<Mutex<Vec<Value>>>) {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let log = sent.clone();
+        let client = FogBugzClient::new("http://localhost:1", "token").with_interceptor(
+            move |request: &mut RequestContext<'_>| {
+                log.lock().unwrap().push(request.payload.clone());
+            },
+        );
+        (client, sent)
+    }
+
+    #[tokio::test]
+    async fn test_case_handle_requests() {
+        let (client, sent) = recording_client();
+        let case = client.case(42);
+        assert_eq!(case.case_id(), 42);
+
+        assert!(case.details().await.is_err());
+        assert!(case.assign_to(7).await.is_err());
+        assert!(case.resolve().await.is_err());
+        assert!(case.close().await.is_err());
+        assert!(case.add_comment("Looked into it").await.is_err());
+        assert!(case.start_work().await.is_err());
+        assert!(case.intervals().await.is_err());
+
+        let sent = sent.lock().unwrap();
+        let commands: Vec<&str> = sent.iter().map(|p| p["cmd"].as_str().unwrap()).collect();
+        assert_eq!(
+            commands,
+            [
+                "search",
+                "assign",
+                "resolve",
+                "close",
+                "edit",
+                "startWork",
+                "listIntervals"
+            ]
+        );
+        assert_eq!(sent[0]["q"], 42);
+        assert_eq!(sent[1]["ixPersonAssignedTo"], 7);
+        assert_eq!(sent[4]["sEvent"], "Looked into it");
+        assert!(sent[1..].iter().all(|p| p["ixBug"] == 42));
+    }
+
+    #[tokio::test]
+    async fn test_case_id_above_u32_is_rejected() {
+        let (client, sent) = recording_client();
+        let case = client.case(u64::from(u32::MAX) + 1);
+        assert!(matches!(
+            case.start_work().await,
+            Err(ResponseError::FogbugzError(
+                FogBugzApiError::CaseNotFound { .. }
+            ))
+        ));
+        assert!(sent.lock().unwrap().is_empty());
+
+        assert_eq!(
+            client.case(u64::from(u32::MAX)).case_id_u32().unwrap(),
+            u32::MAX
+        );
+    }
+}
@@ -1,13 +1,83 @@
+use std::{collections::HashMap, fmt, path::Path};
+
 use bon::Builder;
+use chrono::{DateTime, Utc};
+use reqwest::multipart::Part;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    FogBugzClient, ResponseError,
+    api_client::FogBugzApiError,
+    enums::{Category, Column},
+    search::{SearchRequest, case_id_query},
+    working_schedule::WorkingSchedule,
+};
+
+/// A file to attach to a new or edited case
+#[derive(Clone)]
+pub struct AttachmentUpload {
+    pub file_name: String,
+    pub content: Vec<u8>,
+    /// MIME type; `application/octet-stream` if not set
+    pub mime_type: Option<String>,
+}
 
-use crate::{FogBugzClient, ResponseError, enums::Category};
+impl AttachmentUpload {
+    pub fn new(file_name: impl Into<String>, content: impl Into<Vec<u8>>) -> Self {
+        Self {
+            file_name: file_name.into(),
+            content: content.into(),
+            mime_type: None,
+        }
+    }
+
+    /// Read a file from disk, naming the attachment after the file
+    pub async fn from_path(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref();
+        let content = tokio::fs::read(path).await?;
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "attachment".to_string());
+        Ok(Self::new(file_name, content))
+    }
+
+    pub fn with_mime_type(mut self, mime_type: impl Into<String>) -> Self {
+        self.mime_type = Some(mime_type.into());
+        self
+    }
+
+    pub(crate) fn to_part(&self) -> Result<Part, ResponseError> {
+        let part = Part::bytes(self.content.clone()).file_name(self.file_name.clone());
+        Ok(part.mime_str(
+            self.mime_type
+                .as_deref()
+                .unwrap_or("application/octet-stream"),
+        )?)
+    }
+}
+
+impl fmt::Debug for AttachmentUpload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AttachmentUpload")
+            .field("file_name", &self.file_name)
+            .field("size", &self.content.len())
+            .field("mime_type", &self.mime_type)
+            .finish()
+    }
+}
 
 /// Request to create a new case
 #[derive(Debug, Serialize, Builder)]
 #[builder(state_mod(vis = "pub(crate)"))]
 pub struct NewCaseRequest {
+    /// Custom field values by column name (see `custom_field`)
+    #[serde(flatten)]
+    #[builder(field)]
+    custom_fields: HashMap<String, Value>,
+
     /// Case title (required)
     #[serde(rename = "sTitle")]
     title: String,
@@ -40,6 +110,11 @@ pub struct NewCaseRequest {
     #[builder(into)]
     assigned_to_id: Option<u64>,
 
+    /// Full name of the person to assign the case to, if the ID isn't known (optional)
+    #[serde(rename = "sPersonAssignedTo", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    assigned_to: Option<String>,
+
     /// Priority level (optional)
     #[serde(rename = "ixPriority", skip_serializing_if = "Option::is_none")]
     #[builder(into)]
@@ -50,39 +125,120 @@ pub struct NewCaseRequest {
     #[builder(into)]
     milestone: Option<u64>,
 
+    /// Parent case, making this a subcase (optional)
+    #[serde(rename = "ixBugParent", skip_serializing_if = "Option::is_none")]
+    parent: Option<u64>,
+
     /// Tags (comma-separated string, optional)
     #[serde(rename = "sTags", skip_serializing_if = "Option::is_none")]
     #[builder(into)]
     tags: Option<String>,
 
+    /// Correspondent's email address, for cases that came in by email (optional)
+    #[serde(rename = "sCustomerEmail", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    customer_email: Option<String>,
+
+    /// Mailbox the case is treated as having arrived through (optional)
+    #[serde(rename = "ixMailbox", skip_serializing_if = "Option::is_none")]
+    mailbox_id: Option<u32>,
+
+    /// Due date (optional); see also `due_in_working_days`
+    #[serde(rename = "dtDue", skip_serializing_if = "Option::is_none")]
+    due: Option<DateTime<Utc>>,
+
+    /// Files to attach (optional)
+    #[serde(skip)]
+    #[builder(default)]
+    attachments: Vec<AttachmentUpload>,
+
     /// API instance
     #[serde(skip)]
     client: FogBugzClient,
 }
 
-/// Response from creating a new case
-#[derive(Debug, Serialize, Deserialize)]
-pub struct NewCaseResponse {
-    /// The newly created case ID
+/// The case returned by a case management command (`new`, `edit`, `assign`, ...)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaseResponse {
+    /// ID of the created or updated case
     #[serde(rename = "ixBug")]
     pub case_id: u64,
+    /// Latest event on the case, i.e. the one this command created
+    #[serde(
+        rename = "ixBugEventLatest",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub latest_event_id: Option<u64>,
+    /// Operations the current user may now perform on the case
+    #[serde(default)]
+    pub operations: Vec<String>,
+    /// Any other case fields FogBugz returned (the updated values of requested columns)
+    #[serde(flatten)]
+    pub fields: serde_json::Map<String, Value>,
+}
+
+impl CaseResponse {
+    /// Updated value of a returned column
+    pub fn field(&self, column: Column) -> Option<&Value> {
+        self.fields.get(column.as_ref())
+    }
+
+    /// Whether FogBugz lists `operation` (e.g. `resolve`) as allowed next
+    pub fn can(&self, operation: &str) -> bool {
+        self.operations.iter().any(|op| op == operation)
+    }
+
+    pub(crate) fn from_response(mut response: Value) -> Result<Self, ResponseError> {
+        ResponseError::deserialize_cases(response["data"]["case"].take())
+    }
+}
+
+/// Response from creating a new case
+pub type NewCaseResponse = CaseResponse;
+/// Response from editing a case
+pub type EditCaseResponse = CaseResponse;
+/// Response from assigning a case
+pub type AssignCaseResponse = CaseResponse;
+/// Response from resolving a case
+pub type ResolveCaseResponse = CaseResponse;
+/// Response from reactivating a case
+pub type ReactivateCaseResponse = CaseResponse;
+/// Response from closing a case
+pub type CloseCaseResponse = CaseResponse;
+/// Response from reopening a closed case
+pub type ReopenCaseResponse = CaseResponse;
+
+impl<S: new_case_request_builder::State> NewCaseRequestBuilder<S> {
+    /// Set a custom field by its column name, e.g.
+    /// `plugin_customfields_at_fogcreek_com_customerxnamez12`
+    pub fn custom_field(mut self, column: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.custom_fields.insert(column.into(), value.into());
+        self
+    }
+
+    /// Set the due date to the end of the working day `days` working days from now
+    /// (see `FogBugzClient::working_schedule`)
+    pub fn due_in_working_days(
+        self,
+        days: u32,
+        schedule: &WorkingSchedule,
+    ) -> NewCaseRequestBuilder<new_case_request_builder::SetDue<S>>
+    where
+        S::Due: new_case_request_builder::IsUnset,
+    {
+        self.due(schedule.add_working_days(Utc::now(), days))
+    }
 }
 
 impl NewCaseRequest {
     /// Create a new case
     pub async fn send(&self) -> Result<NewCaseResponse, ResponseError> {
-        let response = self.client.send_command("new", self).await?;
-
-        // Extract the case ID from the response
-        let case_id = response["data"]["case"]["ixBug"].as_u64().ok_or_else(|| {
-            use std::io;
-            ResponseError::JsonError(serde_json::Error::io(io::Error::new(
-                io::ErrorKind::InvalidData,
-                "Missing or invalid case ID in response",
-            )))
-        })? as u64;
-
-        Ok(NewCaseResponse { case_id })
+        let response = self
+            .client
+            .send_command_multipart("new", self, &self.attachments)
+            .await?;
+        CaseResponse::from_response(response)
     }
 }
 
@@ -90,6 +246,11 @@ impl NewCaseRequest {
 #[derive(Debug, Serialize, Builder)]
 #[builder(state_mod(vis = "pub(crate)"))]
 pub struct EditCaseRequest {
+    /// Custom field values by column name (see `custom_field`)
+    #[serde(flatten)]
+    #[builder(field)]
+    custom_fields: HashMap<String, Value>,
+
     /// Case ID to edit (required)
     #[serde(rename = "ixBug")]
     case_id: u64,
@@ -128,11 +289,30 @@ pub struct EditCaseRequest {
     #[builder(into)]
     milestone: Option<u64>,
 
-    /// Tags (comma-separated string, optional)
+    /// Parent case, making this a subcase (optional)
+    #[serde(rename = "ixBugParent", skip_serializing_if = "Option::is_none")]
+    parent: Option<u64>,
+
+    /// Tags (comma-separated string, optional). Replaces all of the case's tags.
     #[serde(rename = "sTags", skip_serializing_if = "Option::is_none")]
     #[builder(into)]
     tags: Option<String>,
 
+    /// Related cases, shown as "See also" (optional). Replaces the current list.
+    #[serde(rename = "ixRelatedBugs", skip_serializing_if = "Option::is_none")]
+    #[builder(with = |case_ids: &[u64]| case_id_query(case_ids))]
+    see_also: Option<String>,
+
+    /// Tags to add to the case's current ones (optional)
+    #[serde(skip)]
+    #[builder(default, with = |tags: &[&str]| tags.iter().map(|tag| tag.to_string()).collect())]
+    add_tags: Vec<String>,
+
+    /// Tags to remove from the case's current ones (optional)
+    #[serde(skip)]
+    #[builder(default, with = |tags: &[&str]| tags.iter().map(|tag| tag.to_string()).collect())]
+    remove_tags: Vec<String>,
+
     /// Current estimate in hours (optional)
     #[serde(rename = "hrsCurrEst", skip_serializing_if = "Option::is_none")]
     #[builder(into)]
@@ -143,16 +323,86 @@ pub struct EditCaseRequest {
     #[builder(into)]
     elapsed_extra: Option<f64>,
 
+    /// Due date (optional); see also `due_in_working_days`
+    #[serde(rename = "dtDue", skip_serializing_if = "Option::is_none")]
+    due: Option<DateTime<Utc>>,
+
+    /// Files to attach (optional)
+    #[serde(skip)]
+    #[builder(default)]
+    attachments: Vec<AttachmentUpload>,
+
     /// API instance
     #[serde(skip)]
     client: FogBugzClient,
 }
 
+impl<S: edit_case_request_builder::State> EditCaseRequestBuilder<S> {
+    /// Set a custom field by its column name, e.g.
+    /// `plugin_customfields_at_fogcreek_com_customerxnamez12`
+    pub fn custom_field(mut self, column: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.custom_fields.insert(column.into(), value.into());
+        self
+    }
+
+    /// Set the due date to the end of the working day `days` working days from now
+    /// (see `FogBugzClient::working_schedule`)
+    pub fn due_in_working_days(
+        self,
+        days: u32,
+        schedule: &WorkingSchedule,
+    ) -> EditCaseRequestBuilder<edit_case_request_builder::SetDue<S>>
+    where
+        S::Due: edit_case_request_builder::IsUnset,
+    {
+        self.due(schedule.add_working_days(Utc::now(), days))
+    }
+}
+
 impl EditCaseRequest {
-    /// Edit the case
-    pub async fn send(&self) -> Result<Value, ResponseError> {
-        self.client.send_command("edit", self).await
+    /// Edit the case. FogBugz overwrites tags on edit, so with `add_tags` or
+    /// `remove_tags` the case's current tags (or `tags`, if set) are fetched first
+    /// and the merged list is sent.
+    pub async fn send(&self) -> Result<EditCaseResponse, ResponseError> {
+        let mut params = serde_json::to_value(self)?;
+        if !self.add_tags.is_empty() || !self.remove_tags.is_empty() {
+            let current = match &self.tags {
+                Some(tags) => split_tags(tags),
+                None => self.client.case_tags(self.case_id).await?,
+            };
+            params["sTags"] = merge_tags(&current, &self.add_tags, &self.remove_tags).into();
+        }
+        let response = self
+            .client
+            .send_command_multipart("edit", params, &self.attachments)
+            .await?;
+        CaseResponse::from_response(response)
+    }
+}
+
+fn split_tags(tags: &str) -> Vec<String> {
+    tags.split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// `current` without `remove` and with `add` appended, as an `sTags` value.
+/// Tags compare case-insensitively, like in FogBugz.
+fn merge_tags(current: &[String], add: &[String], remove: &[String]) -> String {
+    let same = |a: &str, b: &str| a.trim().eq_ignore_ascii_case(b.trim());
+    let mut tags: Vec<&str> = current
+        .iter()
+        .map(|tag| tag.trim())
+        .filter(|tag| !remove.iter().any(|removed| same(tag, removed)))
+        .collect();
+    for tag in add {
+        if !tag.trim().is_empty() && !tags.iter().any(|existing| same(existing, tag)) {
+            tags.push(tag.trim());
+        }
     }
+    tags.join(",")
 }
 
 /// Request to assign a case to a person
@@ -179,8 +429,9 @@ pub struct AssignCaseRequest {
 
 impl AssignCaseRequest {
     /// Assign the case
-    pub async fn send(&self) -> Result<Value, ResponseError> {
-        self.client.send_command("assign", self).await
+    pub async fn send(&self) -> Result<AssignCaseResponse, ResponseError> {
+        let response = self.client.send_command("assign", self).await?;
+        CaseResponse::from_response(response)
     }
 }
 
@@ -214,8 +465,48 @@ pub struct ResolveCaseRequest {
 
 impl ResolveCaseRequest {
     /// Resolve the case
-    pub async fn send(&self) -> Result<Value, ResponseError> {
-        self.client.send_command("resolve", self).await
+    pub async fn send(&self) -> Result<ResolveCaseResponse, ResponseError> {
+        let response = self.client.send_command("resolve", self).await?;
+        CaseResponse::from_response(response)
+    }
+}
+
+/// Request to resolve a case as a duplicate of another
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct ResolveAsDuplicateRequest {
+    /// Case ID to resolve (required)
+    #[serde(rename = "ixBug")]
+    case_id: u64,
+
+    /// The case this one duplicates (required)
+    #[serde(rename = "ixBugOriginal")]
+    original_id: u64,
+
+    /// Duplicate status to resolve to (optional; looked up from the case's category)
+    #[serde(rename = "ixStatus", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    status_id: Option<u64>,
+
+    /// Resolution comment
+    #[serde(rename = "sEvent", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    event: Option<String>,
+
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl ResolveAsDuplicateRequest {
+    /// Resolve the case
+    pub async fn send(&self) -> Result<ResolveCaseResponse, ResponseError> {
+        let mut params = serde_json::to_value(self)?;
+        if self.status_id.is_none() {
+            params["ixStatus"] = self.client.duplicate_status_id(self.case_id).await?.into();
+        }
+        let response = self.client.send_command("resolve", params).await?;
+        CaseResponse::from_response(response)
     }
 }
 
@@ -244,8 +535,40 @@ pub struct ReactivateCaseRequest {
 
 impl ReactivateCaseRequest {
     /// Reactivate the case
-    pub async fn send(&self) -> Result<Value, ResponseError> {
-        self.client.send_command("reactivate", self).await
+    pub async fn send(&self) -> Result<ReactivateCaseResponse, ResponseError> {
+        let response = self.client.send_command("reactivate", self).await?;
+        CaseResponse::from_response(response)
+    }
+}
+
+/// Request to reopen a closed case
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct ReopenCaseRequest {
+    /// Case ID to reopen (required)
+    #[serde(rename = "ixBug")]
+    case_id: u64,
+
+    /// Person to assign the reopened case to (optional)
+    #[serde(rename = "ixPersonAssignedTo", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    assigned_to_id: Option<u64>,
+
+    /// Reopening comment
+    #[serde(rename = "sEvent", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    event: Option<String>,
+
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl ReopenCaseRequest {
+    /// Reopen the case
+    pub async fn send(&self) -> Result<ReopenCaseResponse, ResponseError> {
+        let response = self.client.send_command("reopen", self).await?;
+        CaseResponse::from_response(response)
     }
 }
 
@@ -269,8 +592,92 @@ pub struct CloseCaseRequest {
 
 impl CloseCaseRequest {
     /// Close the case
-    pub async fn send(&self) -> Result<Value, ResponseError> {
-        self.client.send_command("close", self).await
+    pub async fn send(&self) -> Result<CloseCaseResponse, ResponseError> {
+        let response = self.client.send_command("close", self).await?;
+        CaseResponse::from_response(response)
+    }
+}
+
+/// Error from `resolve_and_close`, naming the step that failed
+#[derive(Debug, Error)]
+pub enum ResolveAndCloseError {
+    #[error("Resolving case {case_id} failed: {source}")]
+    Resolve { case_id: u64, source: ResponseError },
+    #[error("Case {case_id} was resolved but closing it failed: {source}")]
+    Close { case_id: u64, source: ResponseError },
+}
+
+impl FogBugzClient {
+    /// The duplicate status (`fDuplicate`) for a case's category
+    pub async fn duplicate_status_id(&self, case_id: u64) -> Result<u32, ResponseError> {
+        let response = SearchRequest::for_cases(self, &[case_id])
+            .with_cols(&[Column::CaseId, Column::Category])
+            .send()
+            .await?;
+        let category_id = response["data"]["cases"][0][Column::Category.as_ref()]
+            .as_u64()
+            .and_then(|id| u32::try_from(id).ok())
+            .ok_or_else(|| FogBugzApiError::CaseNotFound {
+                message: format!("Case {case_id} not found"),
+            })?;
+        let statuses = self.list_statuses(Some(category_id)).await?;
+        statuses
+            .iter()
+            .find(|status| status.is_duplicate && !status.is_deleted)
+            .map(|status| status.id)
+            .ok_or(ResponseError::NoDuplicateStatus(category_id))
+    }
+
+    /// Current tags of a case
+    pub async fn case_tags(&self, case_id: u64) -> Result<Vec<String>, ResponseError> {
+        let response = SearchRequest::for_cases(self, &[case_id])
+            .with_cols(&[Column::CaseId, Column::Tags])
+            .send()
+            .await?;
+        Ok(match &response["data"]["cases"][0][Column::Tags.as_ref()] {
+            Value::Array(tags) => tags
+                .iter()
+                .filter_map(|tag| tag.as_str())
+                .map(str::to_string)
+                .collect(),
+            Value::String(tags) => split_tags(tags),
+            _ => Vec::new(),
+        })
+    }
+
+    /// Resolve a case and then close it, posting the comment with the resolve.
+    /// Stops at the first failure; a `Close` error means the case was left resolved.
+    pub async fn resolve_and_close(
+        &self,
+        case_id: u64,
+        status_id: Option<u64>,
+        comment: Option<&str>,
+    ) -> Result<CloseCaseResponse, ResolveAndCloseError> {
+        let (resolve, close) = self.resolve_and_close_requests(case_id, status_id, comment);
+        resolve
+            .send()
+            .await
+            .map_err(|source| ResolveAndCloseError::Resolve { case_id, source })?;
+        close
+            .send()
+            .await
+            .map_err(|source| ResolveAndCloseError::Close { case_id, source })
+    }
+
+    fn resolve_and_close_requests(
+        &self,
+        case_id: u64,
+        status_id: Option<u64>,
+        comment: Option<&str>,
+    ) -> (ResolveCaseRequest, CloseCaseRequest) {
+        let resolve = self
+            .resolve_case()
+            .case_id(case_id)
+            .maybe_status_id(status_id)
+            .maybe_event(comment)
+            .build();
+        let close = self.close_case().case_id(case_id).build();
+        (resolve, close)
     }
 }
 
@@ -278,6 +685,19 @@ impl CloseCaseRequest {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_resolve_and_close_comments_once() {
+        let api = FogBugzClient::new("https://example.com", "test_key");
+        let (resolve, close) = api.resolve_and_close_requests(42, Some(2), Some("Fixed in 1.2"));
+        let resolve = serde_json::to_value(&resolve).unwrap();
+        assert_eq!(resolve["ixBug"], 42);
+        assert_eq!(resolve["ixStatus"], 2);
+        assert_eq!(resolve["sEvent"], "Fixed in 1.2");
+        let close = serde_json::to_value(&close).unwrap();
+        assert_eq!(close["ixBug"], 42);
+        assert!(close.get("sEvent").is_none());
+    }
+
     #[test]
     fn test_case_management_builder_api() {
         // Test that the builder API compiles and creates valid request structures
@@ -314,6 +734,22 @@ mod tests {
             .title("Updated title".to_string())
             .build();
 
+        let edit_request = api.edit_case().case_id(123).see_also(&[7, 9]).build();
+        assert_eq!(
+            serde_json::to_value(&edit_request).unwrap()["ixRelatedBugs"],
+            "7,9"
+        );
+
+        let duplicate_request = api
+            .resolve_as_duplicate()
+            .case_id(123)
+            .original_id(100)
+            .status_id(9u64)
+            .build();
+        let params = serde_json::to_value(&duplicate_request).unwrap();
+        assert_eq!(params["ixBugOriginal"], 100);
+        assert_eq!(params["ixStatus"], 9);
+
         // Test assign case builder
         let _assign_request = api.assign_case().case_id(123).assigned_to_id(456).build();
 
@@ -327,4 +763,61 @@ mod tests {
         // All builders should compile without errors
         assert!(true);
     }
+
+    #[tokio::test]
+    async fn test_attachment_from_path() {
+        let path = std::env::temp_dir().join(format!("fogbugz-ox-{}.log", std::process::id()));
+        tokio::fs::write(&path, b"log line").await.unwrap();
+        let attachment = AttachmentUpload::from_path(&path).await.unwrap();
+        tokio::fs::remove_file(&path).await.unwrap();
+
+        assert_eq!(
+            attachment.file_name,
+            path.file_name().unwrap().to_str().unwrap()
+        );
+        assert_eq!(attachment.content, b"log line");
+        assert!(attachment.to_part().is_ok());
+        assert!(
+            attachment
+                .with_mime_type("not a mime type")
+                .to_part()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_merge_tags() {
+        let current = split_tags("backend, Urgent,,customer");
+        let add = vec!["urgent".to_string(), "regression".to_string()];
+        let remove = vec!["CUSTOMER".to_string()];
+        assert_eq!(
+            merge_tags(&current, &add, &remove),
+            "backend,Urgent,regression"
+        );
+        assert_eq!(merge_tags(&[], &[], &[]), "");
+    }
+
+    #[test]
+    fn test_case_response_from_edit() {
+        let response = serde_json::json!({
+            "data": {
+                "case": {
+                    "ixBug": 123,
+                    "ixBugEventLatest": 4567,
+                    "operations": ["edit", "assign", "resolve", "email", "remind"],
+                    "sTitle": "Updated title"
+                }
+            },
+            "errors": []
+        });
+        let case = CaseResponse::from_response(response).unwrap();
+        assert_eq!(case.case_id, 123);
+        assert_eq!(case.latest_event_id, Some(4567));
+        assert!(case.can("resolve"));
+        assert!(!case.can("close"));
+        assert_eq!(
+            case.field(Column::Title),
+            Some(&serde_json::json!("Updated title"))
+        );
+    }
 }
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    FogBugzClient, ResponseError, api_client::FogBugzApiError, enums::Column,
+    estimates::parse_child_ids, search::SearchRequest,
+};
+
+/// A case and its subcases, recursively
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CaseTree {
+    pub case_id: u64,
+    pub title: String,
+    pub is_open: bool,
+    pub children: Vec<CaseTree>,
+}
+
+impl CaseTree {
+    /// IDs of every case below this one, depth first
+    pub fn descendant_ids(&self) -> Vec<u64> {
+        self.children
+            .iter()
+            .flat_map(|child| std::iter::once(child.case_id).chain(child.descendant_ids()))
+            .collect()
+    }
+
+    /// Levels of subcases below this case; 0 for a case without children
+    pub fn depth(&self) -> usize {
+        self.children
+            .iter()
+            .map(|child| child.depth() + 1)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Assemble the tree below `case_id`. Each case is used once, so a cycle in
+    /// the parent links can't recurse forever.
+    fn build(case_id: u64, nodes: &mut HashMap<u64, (CaseTree, Vec<u64>)>) -> Option<Self> {
+        let (mut tree, child_ids) = nodes.remove(&case_id)?;
+        tree.children = child_ids
+            .into_iter()
+            .filter_map(|id| Self::build(id, nodes))
+            .collect();
+        Some(tree)
+    }
+}
+
+impl FogBugzClient {
+    /// A case with all of its subcases, fetched one level at a time
+    pub async fn case_tree(&self, case_id: u64) -> Result<CaseTree, ResponseError> {
+        let mut nodes = HashMap::new();
+        let mut level = vec![case_id];
+        while !level.is_empty() {
+            let response = SearchRequest::for_cases(self, &level)
+                .with_cols(&[
+                    Column::CaseId,
+                    Column::Title,
+                    Column::IsOpen,
+                    Column::Children,
+                ])
+                .send()
+                .await?;
+            let mut next = Vec::new();
+            for case in response["data"]["cases"].as_array().into_iter().flatten() {
+                let Some(id) = case["ixBug"].as_u64() else {
+                    continue;
+                };
+                let child_ids = parse_child_ids(&case[Column::Children.as_ref()]);
+                next.extend(child_ids.iter().copied());
+                nodes.insert(id, (node(id, case), child_ids));
+            }
+            next.retain(|id| !nodes.contains_key(id));
+            next.sort_unstable();
+            next.dedup();
+            level = next;
+        }
+        CaseTree::build(case_id, &mut nodes).ok_or_else(|| {
+            FogBugzApiError::CaseNotFound {
+                message: format!("Case {case_id} not found"),
+            }
+            .into()
+        })
+    }
+}
+
+fn node(case_id: u64, case: &Value) -> CaseTree {
+    CaseTree {
+        case_id,
+        title: case["sTitle"].as_str().unwrap_or_default().to_string(),
+        is_open: case["fOpen"].as_bool().unwrap_or_default(),
+        children: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_case_tree() {
+        let cases = [
+            serde_json::json!({"ixBug": 1, "sTitle": "Epic", "fOpen": true}),
+            serde_json::json!({"ixBug": 2, "sTitle": "Part A", "fOpen": false}),
+            serde_json::json!({"ixBug": 3, "sTitle": "Part B", "fOpen": true}),
+            serde_json::json!({"ixBug": 4, "sTitle": "Detail", "fOpen": true}),
+        ];
+        let child_ids = [vec![2, 3], vec![], vec![4], vec![1]];
+        let mut nodes: HashMap<_, _> = cases
+            .iter()
+            .zip(child_ids)
+            .map(|(case, children)| {
+                let id = case["ixBug"].as_u64().unwrap();
+                (id, (node(id, case), children))
+            })
+            .collect();
+
+        // 4 -> 1 is a cycle and is dropped
+        let tree = CaseTree::build(1, &mut nodes).unwrap();
+        assert_eq!(tree.descendant_ids(), vec![2, 3, 4]);
+        assert_eq!(tree.depth(), 2);
+        assert!(!tree.children[0].is_open);
+        assert!(tree.children[1].children[0].children.is_empty());
+    }
+}
@@ -0,0 +1,414 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt,
+    sync::Arc,
+    time::Duration,
+};
+
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use futures::{Stream, stream};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::{
+    FogBugzClient, ResponseError,
+    enums::Column,
+    filter::FogBugzSearchBuilder,
+    shutdown::{ShutdownSignal, sleep_unless_shutdown},
+    state::StateStore,
+};
+
+/// A case that changed since the previous poll, with the requested columns
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CaseChange {
+    #[serde(rename = "ixBug")]
+    pub case_id: u64,
+    #[serde(rename = "dtLastUpdated")]
+    pub last_updated: DateTime<Utc>,
+    /// The other requested columns, as FogBugz returned them
+    #[serde(flatten)]
+    pub fields: Map<String, Value>,
+}
+
+impl CaseChange {
+    fn from_case(case: Value) -> Option<Self> {
+        let Value::Object(mut fields) = case else {
+            return None;
+        };
+        let case_id = fields.remove(Column::CaseId.as_ref())?.as_u64()?;
+        let last_updated = fields
+            .remove(Column::LastUpdated.as_ref())?
+            .as_str()?
+            .parse()
+            .ok()?;
+        Some(Self {
+            case_id,
+            last_updated,
+            fields,
+        })
+    }
+}
+
+/// Cases updated after `watermark`, plus those updated at exactly `watermark`
+/// that aren't in `seen`, oldest first
+fn changes_after(
+    cases: Vec<Value>,
+    watermark: DateTime<Utc>,
+    seen: &HashSet<u64>,
+) -> Vec<CaseChange> {
+    let mut changes: Vec<CaseChange> = cases
+        .into_iter()
+        .filter_map(CaseChange::from_case)
+        .filter(|change| {
+            change.last_updated > watermark
+                || (change.last_updated == watermark && !seen.contains(&change.case_id))
+        })
+        .collect();
+    changes.sort_by_key(|change| (change.last_updated, change.case_id));
+    changes
+}
+
+/// Search for cases edited on or after the watermark's day. FogBugz reads the
+/// date in the user's time zone rather than UTC, so the search starts a day
+/// earlier; `changes_after` drops the extra cases by `dtLastUpdated`.
+pub(crate) fn edited_since(watermark: DateTime<Utc>) -> String {
+    let from = watermark - chrono::Duration::days(1);
+    FogBugzSearchBuilder::new()
+        .edited_date(&format!("{}..now", from.format("%m/%d/%Y")))
+        .build()
+}
+
+/// Polls for cases updated since the last poll. FogBugz's `edited:` axis only
+/// resolves to the day, so each poll searches from the day before the
+/// watermark's and drops cases that were already seen.
+#[derive(Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct ChangeFeed {
+    /// Extra search query narrowing the feed, e.g. a project (optional)
+    #[builder(into)]
+    query: Option<String>,
+    /// Columns to include besides `ixBug` and `dtLastUpdated`
+    #[builder(default = vec![Column::Title, Column::Project, Column::Status, Column::PersonAssignedTo])]
+    cols: Vec<Column>,
+    /// Only report changes after this time (defaults to the stored watermark,
+    /// or when the feed starts)
+    since: Option<DateTime<Utc>>,
+    /// Pause between polls
+    #[builder(default = Duration::from_secs(60))]
+    interval: Duration,
+    /// Where the watermark is read from at start and flushed to between polls
+    /// and on shutdown (optional)
+    store: Option<Arc<dyn StateStore>>,
+    /// Key of the watermark in `store`
+    #[builder(into, default = "change_feed")]
+    store_key: String,
+    /// Ends the feed once fired (optional)
+    shutdown: Option<ShutdownSignal>,
+    /// API instance
+    client: FogBugzClient,
+}
+
+impl fmt::Debug for ChangeFeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ChangeFeed")
+            .field("query", &self.query)
+            .field("cols", &self.cols)
+            .field("since", &self.since)
+            .field("interval", &self.interval)
+            .field("store", &self.store.as_ref().map(|_| ".."))
+            .field("store_key", &self.store_key)
+            .field("shutdown", &self.shutdown)
+            .field("client", &self.client)
+            .finish()
+    }
+}
+
+/// Progress of a running feed
+struct FeedState {
+    /// `None` until the first poll
+    watermark: Option<DateTime<Utc>>,
+    /// Cases already handed out that were updated at exactly the watermark;
+    /// `dtLastUpdated` has whole seconds, so later edits can share it
+    seen_at_watermark: HashSet<u64>,
+    pending: VecDeque<CaseChange>,
+    /// Watermark last written to the store
+    flushed: Option<DateTime<Utc>>,
+    stopped: bool,
+}
+
+impl FeedState {
+    fn new() -> Self {
+        Self {
+            watermark: None,
+            seen_at_watermark: HashSet::new(),
+            pending: VecDeque::new(),
+            flushed: None,
+            stopped: false,
+        }
+    }
+
+    fn advance(&mut self, changes: Vec<CaseChange>) {
+        if let Some(newest) = changes.last().map(|change| change.last_updated) {
+            if self.watermark != Some(newest) {
+                self.seen_at_watermark.clear();
+            }
+            self.seen_at_watermark.extend(
+                changes
+                    .iter()
+                    .filter(|change| change.last_updated == newest)
+                    .map(|change| change.case_id),
+            );
+            self.watermark = Some(newest);
+        }
+        self.pending.extend(changes);
+    }
+}
+
+impl ChangeFeed {
+    async fn start_watermark(&self) -> Result<DateTime<Utc>, ResponseError> {
+        if let Some(since) = self.since {
+            return Ok(since);
+        }
+        let stored = match &self.store {
+            Some(store) => store.get(&self.store_key).await?,
+            None => None,
+        };
+        Ok(stored
+            .and_then(|watermark| watermark.parse().ok())
+            .unwrap_or_else(Utc::now))
+    }
+
+    async fn flush(&self, watermark: DateTime<Utc>) -> Result<(), ResponseError> {
+        if let Some(store) = &self.store {
+            store.put(&self.store_key, watermark.to_rfc3339()).await?;
+        }
+        Ok(())
+    }
+
+    async fn poll(
+        &self,
+        watermark: DateTime<Utc>,
+        seen: &HashSet<u64>,
+    ) -> Result<Vec<CaseChange>, ResponseError> {
+        let mut query = edited_since(watermark);
+        if let Some(extra) = &self.query {
+            query = format!("{query} ({extra})");
+        }
+        let mut cols = vec![Column::CaseId.to_string(), Column::LastUpdated.to_string()];
+        cols.extend(self.cols.iter().map(Column::to_string));
+        let cases = self
+            .client
+            .search()
+            .query(query)
+            .cols(cols)
+            .build()
+            .send_all()
+            .await?;
+        Ok(changes_after(cases, watermark, seen))
+    }
+
+    /// The next change, or `None` once the feed has stopped
+    async fn next_change(
+        &self,
+        state: &mut FeedState,
+    ) -> Option<Result<CaseChange, ResponseError>> {
+        loop {
+            if let Some(change) = state.pending.pop_front() {
+                return Some(Ok(change));
+            }
+            if state.stopped {
+                return None;
+            }
+            let watermark = match state.watermark {
+                Some(watermark) => {
+                    // Every change up to the watermark has been handed out, so a
+                    // restart after this point picks up where the feed left off
+                    if state.flushed != Some(watermark) {
+                        state.flushed = Some(watermark);
+                        if let Err(e) = self.flush(watermark).await {
+                            return Some(Err(e));
+                        }
+                    }
+                    if sleep_unless_shutdown(self.interval, self.shutdown.as_ref()).await {
+                        state.stopped = true;
+                        return self.flush(watermark).await.err().map(Err);
+                    }
+                    watermark
+                }
+                None => {
+                    if self
+                        .shutdown
+                        .as_ref()
+                        .is_some_and(ShutdownSignal::is_shutdown)
+                    {
+                        return None;
+                    }
+                    match self.start_watermark().await {
+                        Ok(watermark) => watermark,
+                        Err(e) => {
+                            state.stopped = true;
+                            return Some(Err(e));
+                        }
+                    }
+                }
+            };
+            state.watermark = Some(watermark);
+            match self.poll(watermark, &state.seen_at_watermark).await {
+                Ok(changes) => state.advance(changes),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+
+    /// Changes as they are found, until shut down. A failed poll yields its error
+    /// and the feed carries on from the same watermark at the next interval.
+    /// The watermark is flushed to the store once a poll's changes have all been
+    /// handed out. On shutdown, the changes of the poll in flight are still
+    /// handed out, then the watermark is flushed and the stream ends.
+    pub fn stream(&self) -> impl Stream<Item = Result<CaseChange, ResponseError>> + '_ {
+        stream::unfold(FeedState::new(), move |mut state| async move {
+            let change = self.next_change(&mut state).await?;
+            Some((change, state))
+        })
+    }
+
+    /// Same as `stream`, owning the feed so the stream can outlive the builder's scope
+    pub fn into_stream(self) -> impl Stream<Item = Result<CaseChange, ResponseError>> + 'static {
+        stream::unfold((self, FeedState::new()), |(feed, mut state)| async move {
+            let change = feed.next_change(&mut state).await?;
+            Some((change, (feed, state)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changes_after_watermark() {
+        let watermark = "2024-03-04T10:00:00Z".parse().unwrap();
+        let cases = vec![
+            serde_json::json!({"ixBug": 2, "dtLastUpdated": "2024-03-04T11:00:00Z", "sTitle": "B"}),
+            serde_json::json!({"ixBug": 1, "dtLastUpdated": "2024-03-04T09:00:00Z", "sTitle": "A"}),
+            serde_json::json!({"ixBug": 3, "dtLastUpdated": "2024-03-04T10:30:00Z", "sTitle": "C"}),
+            serde_json::json!({"ixBug": 4}),
+            serde_json::json!({"ixBug": 5, "dtLastUpdated": "2024-03-04T10:00:00Z", "sTitle": "E"}),
+            serde_json::json!({"ixBug": 6, "dtLastUpdated": "2024-03-04T10:00:00Z", "sTitle": "F"}),
+        ];
+        let changes = changes_after(cases, watermark, &HashSet::from([5]));
+        let ids: Vec<u64> = changes.iter().map(|change| change.case_id).collect();
+        assert_eq!(ids, vec![6, 3, 2]);
+        assert_eq!(
+            serde_json::to_value(&changes[1]).unwrap(),
+            serde_json::json!({"ixBug": 3, "dtLastUpdated": "2024-03-04T10:30:00Z", "sTitle": "C"})
+        );
+    }
+
+    #[test]
+    fn test_edited_since_covers_previous_day() {
+        let watermark = "2024-03-04T00:30:00Z".parse().unwrap();
+        assert_eq!(edited_since(watermark), "edited:\"03/03/2024..now\"");
+    }
+
+    #[tokio::test]
+    async fn test_poll_groups_extra_query() {
+        use std::sync::Mutex;
+
+        use crate::interceptor::RequestContext;
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let log = sent.clone();
+        let client = FogBugzClient::new("http://localhost:1", "token").with_interceptor(
+            move |request: &mut RequestContext<'_>| {
+                log.lock().unwrap().push(request.payload["q"].clone());
+            },
+        );
+        let feed = ChangeFeed::builder()
+            .query("project:Inbox OR project:Ops")
+            .client(client)
+            .build();
+        let watermark = "2024-03-04T00:30:00Z".parse().unwrap();
+        assert!(feed.poll(watermark, &HashSet::new()).await.is_err());
+        assert!(
+            sent.lock().unwrap()[0]
+                .as_str()
+                .unwrap()
+                .starts_with("edited:\"03/03/2024..now\" (project:Inbox OR project:Ops)")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_flushes_watermark() {
+        use futures::StreamExt;
+
+        use crate::{shutdown::ShutdownHandle, state::MemoryStateStore};
+
+        let store = Arc::new(MemoryStateStore::new());
+        store
+            .put("feed", "2024-03-04T10:00:00+00:00".to_string())
+            .await
+            .unwrap();
+        let handle = ShutdownHandle::new();
+        let feed = ChangeFeed::builder()
+            .client(FogBugzClient::new("http://localhost:1", "token"))
+            .store(store.clone())
+            .store_key("feed")
+            .shutdown(handle.signal())
+            .build();
+        assert_eq!(
+            feed.start_watermark().await.unwrap().to_rfc3339(),
+            "2024-03-04T10:00:00+00:00"
+        );
+
+        handle.shutdown();
+        let items: Vec<_> = feed.stream().collect().await;
+        assert!(items.is_empty());
+        assert_eq!(
+            store.get("feed").await.unwrap().as_deref(),
+            Some("2024-03-04T10:00:00+00:00")
+        );
+
+        feed.flush("2024-03-05T08:00:00Z".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get("feed").await.unwrap().as_deref(),
+            Some("2024-03-05T08:00:00+00:00")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_while_streaming_flushes_watermark() {
+        use futures::StreamExt;
+
+        use crate::{shutdown::ShutdownHandle, state::MemoryStateStore};
+
+        let store = Arc::new(MemoryStateStore::new());
+        let handle = ShutdownHandle::new();
+        let feed = ChangeFeed::builder()
+            .client(FogBugzClient::new("http://localhost:1", "token"))
+            .since("2024-03-04T10:00:00Z".parse().unwrap())
+            .interval(Duration::from_secs(3600))
+            .store(store.clone())
+            .store_key("feed")
+            .shutdown(handle.signal())
+            .build();
+
+        let mut changes = Box::pin(feed.stream());
+        // Nothing listens on the port, so the first poll fails
+        assert!(matches!(changes.next().await, Some(Err(_))));
+
+        let shutdown = handle.clone();
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            shutdown.shutdown();
+        });
+        assert!(changes.next().await.is_none());
+        assert_eq!(
+            store.get("feed").await.unwrap().as_deref(),
+            Some("2024-03-04T10:00:00+00:00")
+        );
+    }
+}
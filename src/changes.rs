@@ -0,0 +1,182 @@
+use std::{fmt, sync::LazyLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// `Priority changed from '3 - Must Fix' to '2 - Should Fix'.`
+static CHANGED_FROM: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(.+?) changed from '(.*)' to '(.*)'\.?$").unwrap());
+
+/// `Estimate set to '4 hours'.`
+static SET_TO: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^(.+?) set to '(.*)'\.?$").unwrap());
+
+/// A single field change described in an event's `sChanges` text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldChange {
+    pub field: String,
+    /// Previous value, if the text mentions one
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {}",
+            self.field,
+            self.old.as_deref().unwrap_or("(none)"),
+            self.new.as_deref().unwrap_or("(none)")
+        )
+    }
+}
+
+/// Parse one line of changes text, e.g. `Title changed from 'a' to 'b'.`
+fn parse_line(line: &str) -> Option<FieldChange> {
+    if let Some(captures) = CHANGED_FROM.captures(line) {
+        return Some(FieldChange {
+            field: captures[1].to_string(),
+            old: Some(captures[2].to_string()),
+            new: Some(captures[3].to_string()),
+        });
+    }
+    SET_TO.captures(line).map(|captures| FieldChange {
+        field: captures[1].to_string(),
+        old: None,
+        new: Some(captures[2].to_string()),
+    })
+}
+
+/// Structured field changes from an event's `sChanges` text, one per line.
+/// Lines that don't describe a field change are skipped.
+pub fn parse_changes(text: &str) -> Vec<FieldChange> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .filter_map(parse_line)
+        .collect()
+}
+
+/// Languages whose changes text we know how to parse.
+/// FogBugz writes `sChanges` in the site's language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangesLanguage {
+    English,
+}
+
+impl ChangesLanguage {
+    /// Parsing rules for a site language as reported by `viewSettings`
+    /// (e.g. `en-us`), or `None` if the language isn't supported
+    pub fn from_site_language(language: &str) -> Option<Self> {
+        let primary = language
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match primary.as_str() {
+            "en" => Some(ChangesLanguage::English),
+            _ => None,
+        }
+    }
+}
+
+/// Field changes of an event, or the raw text if it couldn't be understood
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Changes {
+    Parsed(Vec<FieldChange>),
+    /// The text is in an unsupported language or format; kept verbatim
+    ParseFailed {
+        raw: String,
+    },
+}
+
+impl Changes {
+    /// Parsed field changes; empty if parsing failed
+    pub fn field_changes(&self) -> &[FieldChange] {
+        match self {
+            Changes::Parsed(changes) => changes,
+            Changes::ParseFailed { .. } => &[],
+        }
+    }
+
+    pub fn is_parse_failed(&self) -> bool {
+        matches!(self, Changes::ParseFailed { .. })
+    }
+}
+
+/// Parse changes text using the rules for `language`. Text in an unsupported
+/// language is kept as `ParseFailed`; text in which no line describes a field
+/// change, such as a note that the description was revised, has no changes.
+pub fn parse_changes_in(text: &str, language: Option<ChangesLanguage>) -> Changes {
+    match language {
+        Some(ChangesLanguage::English) => Changes::Parsed(parse_changes(text)),
+        None if text.trim().is_empty() => Changes::Parsed(Vec::new()),
+        None => Changes::ParseFailed {
+            raw: text.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_changes() {
+        let text = "Project changed from 'Inbox' to 'Widget Factory'.\r\n\
+                    Priority changed from '3 - Must Fix' to '2 - Should Fix'.\r\n\
+                    Estimate set to '4 hours'.\r\n\
+                    Revised Description from 1/2/2024 10:00 AM\r\n";
+        assert_eq!(
+            parse_changes(text),
+            vec![
+                FieldChange {
+                    field: "Project".to_string(),
+                    old: Some("Inbox".to_string()),
+                    new: Some("Widget Factory".to_string()),
+                },
+                FieldChange {
+                    field: "Priority".to_string(),
+                    old: Some("3 - Must Fix".to_string()),
+                    new: Some("2 - Should Fix".to_string()),
+                },
+                FieldChange {
+                    field: "Estimate".to_string(),
+                    old: None,
+                    new: Some("4 hours".to_string()),
+                },
+            ]
+        );
+        assert!(parse_changes("").is_empty());
+    }
+
+    #[test]
+    fn test_unparseable_changes_keep_raw_text() {
+        let german = "Priorität geändert von '3' zu '2'.";
+        let language = ChangesLanguage::from_site_language("de-DE");
+        assert_eq!(language, None);
+        assert_eq!(
+            parse_changes_in(german, language),
+            Changes::ParseFailed {
+                raw: german.to_string()
+            }
+        );
+        // English rules find no field changes in it
+        assert_eq!(
+            parse_changes_in(german, Some(ChangesLanguage::English)),
+            Changes::Parsed(Vec::new())
+        );
+        assert_eq!(
+            parse_changes_in(
+                "Revised Description from 1/2/2024 10:00 AM",
+                Some(ChangesLanguage::English)
+            ),
+            Changes::Parsed(Vec::new())
+        );
+
+        let english = "Title changed from 'a' to 'b'.";
+        let language = ChangesLanguage::from_site_language("en-us");
+        assert_eq!(parse_changes_in(english, language).field_changes().len(), 1);
+        assert_eq!(parse_changes_in("", None), Changes::Parsed(Vec::new()));
+    }
+}
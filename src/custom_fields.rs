@@ -0,0 +1,239 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::{
+    FogBugzClient, ResponseError, date::RelativeDate, enums::Column, filter::FogBugzSearchBuilder,
+};
+
+/// Cases sampled when discovering custom fields
+const SAMPLE_SIZE: u32 = 50;
+
+/// Prefix FogBugz gives the columns of custom fields,
+/// e.g. `plugin_customfields_at_fogcreek_com_customerxnamez12`
+pub const CUSTOM_FIELD_PREFIX: &str = "plugin_customfields";
+
+/// Whether a column holds a custom field
+pub fn is_custom_field(column: &str) -> bool {
+    column.starts_with(CUSTOM_FIELD_PREFIX)
+}
+
+/// The custom field columns of a case as returned by `search`
+pub fn custom_fields_of(case: &Value) -> HashMap<String, Value> {
+    case.as_object()
+        .into_iter()
+        .flatten()
+        .filter(|(column, _)| is_custom_field(column))
+        .map(|(column, value)| (column.clone(), value.clone()))
+        .collect()
+}
+
+/// Keep only the custom field columns of a flattened case
+pub(crate) fn deserialize_custom_fields<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<HashMap<String, Value>, D::Error> {
+    let mut fields = HashMap::<String, Value>::deserialize(deserializer)?;
+    fields.retain(|column, _| is_custom_field(column));
+    Ok(fields)
+}
+
+/// Kind of value a custom field holds, as far as the sampled values show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum CustomFieldType {
+    Text,
+    Number,
+    Boolean,
+    Date,
+    /// Multiple values, e.g. a multi-select list
+    List,
+    /// No sampled case had a value
+    Unknown,
+}
+
+impl CustomFieldType {
+    /// Type of one value; FogBugz returns most custom field values as strings
+    fn of(value: &Value) -> Self {
+        match value {
+            Value::Null => Self::Unknown,
+            Value::Bool(_) => Self::Boolean,
+            Value::Number(_) => Self::Number,
+            Value::Array(_) => Self::List,
+            Value::Object(_) => Self::Text,
+            Value::String(text) => {
+                let text = text.trim();
+                if text.is_empty() {
+                    Self::Unknown
+                } else if text.eq_ignore_ascii_case("true") || text.eq_ignore_ascii_case("false") {
+                    Self::Boolean
+                } else if text.parse::<f64>().is_ok() {
+                    Self::Number
+                } else if text.parse::<DateTime<Utc>>().is_ok() {
+                    Self::Date
+                } else {
+                    Self::Text
+                }
+            }
+        }
+    }
+
+    /// Type covering values of both types; mixed values are text
+    fn merge(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Unknown, other) | (other, Self::Unknown) => other,
+            (left, right) if left == right => left,
+            _ => Self::Text,
+        }
+    }
+}
+
+/// A custom field found on sampled cases
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CustomFieldInfo {
+    /// API name of the field, as used by `custom_field` on the request builders
+    pub column: String,
+    pub field_type: CustomFieldType,
+    /// Sampled cases with a value in the field
+    pub filled: usize,
+}
+
+/// Custom fields of `cases` with the type their values suggest, by column
+fn infer_custom_fields(cases: &[Value]) -> Vec<CustomFieldInfo> {
+    let mut fields: BTreeMap<String, CustomFieldInfo> = BTreeMap::new();
+    for case in cases {
+        for (column, value) in custom_fields_of(case) {
+            let value_type = CustomFieldType::of(&value);
+            let field = fields
+                .entry(column.clone())
+                .or_insert_with(|| CustomFieldInfo {
+                    column,
+                    field_type: CustomFieldType::Unknown,
+                    filled: 0,
+                });
+            field.field_type = field.field_type.merge(value_type);
+            if value_type != CustomFieldType::Unknown {
+                field.filled += 1;
+            }
+        }
+    }
+    fields.into_values().collect()
+}
+
+impl FogBugzClient {
+    /// A sample of the cases matching `query`, with their custom fields
+    async fn sample_custom_fields(&self, query: &str) -> Result<Vec<Value>, ResponseError> {
+        let response = self
+            .search()
+            .query(query)
+            .cols(vec![
+                Column::CaseId.to_string(),
+                Column::CustomFields.to_string(),
+            ])
+            .max(SAMPLE_SIZE)
+            .build()
+            .send()
+            .await?;
+        Ok(response["data"]["cases"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    /// Custom field columns present on the cases matching `query`, found by
+    /// requesting the `customFields` column for a sample of them
+    pub async fn discover_custom_fields(
+        &self,
+        query: &str,
+    ) -> Result<BTreeSet<String>, ResponseError> {
+        Ok(self
+            .sample_custom_fields(query)
+            .await?
+            .iter()
+            .flat_map(|case| custom_fields_of(case).into_keys())
+            .collect())
+    }
+
+    /// Custom fields with their inferred types, from cases edited in the last
+    /// 90 days. FogBugz has no schema listing for them, so fields no sampled case
+    /// has are missing; use `list_custom_fields_in` to sample other cases.
+    pub async fn list_custom_fields(&self) -> Result<Vec<CustomFieldInfo>, ResponseError> {
+        let query = FogBugzSearchBuilder::new()
+            .edited_date(&format!("{}..", RelativeDate::days_ago(90)))
+            .build();
+        self.list_custom_fields_in(&query).await
+    }
+
+    /// Custom fields with their inferred types, from the cases matching `query`
+    pub async fn list_custom_fields_in(
+        &self,
+        query: &str,
+    ) -> Result<Vec<CustomFieldInfo>, ResponseError> {
+        let cases = self.sample_custom_fields(query).await?;
+        Ok(infer_custom_fields(&cases))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::case_details::CaseDetails;
+
+    const CUSTOMER: &str = "plugin_customfields_at_fogcreek_com_customerxnamez12";
+
+    #[test]
+    fn test_custom_fields_round_trip() {
+        let case: CaseDetails = serde_json::from_value(serde_json::json!({
+            "ixBug": 1,
+            "sTitle": "Crash",
+            "sProject": "Widget",
+            "fOpen": true,
+            "sArea": "Misc",
+            "ixStatus": 1,
+            "ixPriority": 3,
+            "ixCategory": 1,
+            "events": [],
+            "operations": ["edit", "resolve"],
+            CUSTOMER: "ACME",
+        }))
+        .unwrap();
+        assert_eq!(
+            case.custom_fields,
+            HashMap::from([(CUSTOMER.to_string(), Value::from("ACME"))])
+        );
+
+        let api = FogBugzClient::new("https://example.com", "test_key");
+        let request = api
+            .edit_case()
+            .case_id(1)
+            .custom_field(CUSTOMER, "Initech")
+            .build();
+        let params = serde_json::to_value(&request).unwrap();
+        assert_eq!(params[CUSTOMER], "Initech");
+        assert_eq!(params["ixBug"], 1);
+    }
+
+    #[test]
+    fn test_infer_custom_fields() {
+        const SEATS: &str = "plugin_customfields_at_fogcreek_com_seatsz13";
+        const RENEWAL: &str = "plugin_customfields_at_fogcreek_com_renewalz14";
+        let cases = [
+            serde_json::json!({ "ixBug": 1, CUSTOMER: "ACME", SEATS: "12", RENEWAL: "" }),
+            serde_json::json!({ "ixBug": 2, CUSTOMER: "42", SEATS: "7.5", RENEWAL: "2024-05-01T00:00:00Z" }),
+            serde_json::json!({ "ixBug": 3, SEATS: "" }),
+        ];
+        let fields = infer_custom_fields(&cases);
+        let summary: Vec<(&str, CustomFieldType, usize)> = fields
+            .iter()
+            .map(|field| (field.column.as_str(), field.field_type, field.filled))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (CUSTOMER, CustomFieldType::Text, 2),
+                (RENEWAL, CustomFieldType::Date, 1),
+                (SEATS, CustomFieldType::Number, 2),
+            ]
+        );
+    }
+}
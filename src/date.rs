@@ -2,6 +2,7 @@ use std::fmt;
 use std::num::ParseIntError;
 use std::str::FromStr;
 
+use chrono::{Duration, NaiveDate};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
@@ -15,6 +16,22 @@ impl PointInTime {
     pub fn new(day: u32, month: u32, year: u32) -> Self {
         Self { day, month, year }
     }
+
+    /// Start of the day as an ISO 8601 timestamp, as `listIntervals` expects
+    pub fn start_of_day(&self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}T00:00:00Z",
+            self.year, self.month, self.day
+        )
+    }
+
+    /// Last second of the day as an ISO 8601 timestamp
+    pub fn end_of_day(&self) -> String {
+        format!(
+            "{:04}-{:02}-{:02}T23:59:59Z",
+            self.year, self.month, self.day
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -138,8 +155,93 @@ impl From<DateRange> for Date {
     }
 }
 
+/// A date in search syntax, e.g. `3/26/2007`
+pub(crate) fn search_date(date: NaiveDate) -> String {
+    date.format("%m/%d/%Y").to_string()
+}
+
+/// Unit of a `RelativeDate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RelativeUnit {
+    Days,
+    Weeks,
+    Months,
+    Years,
+}
+
+/// A date relative to today in search syntax, e.g. `-3w` for three weeks ago
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelativeDate {
+    pub amount: u32,
+    pub unit: RelativeUnit,
+}
+
+impl RelativeDate {
+    pub fn days_ago(amount: u32) -> Self {
+        Self {
+            amount,
+            unit: RelativeUnit::Days,
+        }
+    }
+
+    pub fn weeks_ago(amount: u32) -> Self {
+        Self {
+            amount,
+            unit: RelativeUnit::Weeks,
+        }
+    }
+
+    pub fn months_ago(amount: u32) -> Self {
+        Self {
+            amount,
+            unit: RelativeUnit::Months,
+        }
+    }
+
+    pub fn years_ago(amount: u32) -> Self {
+        Self {
+            amount,
+            unit: RelativeUnit::Years,
+        }
+    }
+}
+
+/// The whole days in a duration, rounded up, as weeks when they divide evenly
+impl From<Duration> for RelativeDate {
+    fn from(duration: Duration) -> Self {
+        let days = (duration.num_seconds().max(0) as u64).div_ceil(86_400) as u32;
+        if days > 0 && days.is_multiple_of(7) {
+            Self::weeks_ago(days / 7)
+        } else {
+            Self::days_ago(days)
+        }
+    }
+}
+
+impl fmt::Display for RelativeDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.unit {
+            RelativeUnit::Days => 'd',
+            RelativeUnit::Weeks => 'w',
+            RelativeUnit::Months => 'm',
+            RelativeUnit::Years => 'y',
+        };
+        write!(f, "-{}{}", self.amount, unit)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn test_relative_date_from_duration() {
+        use super::RelativeDate;
+        use chrono::Duration;
+
+        assert_eq!(RelativeDate::from(Duration::days(21)).to_string(), "-3w");
+        assert_eq!(RelativeDate::from(Duration::hours(30)).to_string(), "-2d");
+        assert_eq!(RelativeDate::months_ago(1).to_string(), "-1m");
+    }
+
     #[test]
     fn test_parse_point_in_time() {
         let point_in_time = "31-12-2020".parse::<super::PointInTime>().unwrap();
@@ -0,0 +1,185 @@
+use std::fmt;
+
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{FogBugzClient, ResponseError};
+
+/// A discussion group (forum)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DiscussGroup {
+    #[serde(rename = "ixDiscussGroup")]
+    pub id: u32,
+    #[serde(rename = "sFullName", alias = "sName")]
+    pub name: String,
+    #[serde(rename = "sTagLineHTML", default)]
+    pub tagline: Option<String>,
+}
+
+impl fmt::Display for DiscussGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {}", self.id, self.name)
+    }
+}
+
+/// A topic (thread) in a discussion group
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DiscussTopic {
+    #[serde(rename = "ixDiscussTopic")]
+    pub id: u32,
+    #[serde(rename = "sHeadline", alias = "sTopic", alias = "sSubject")]
+    pub headline: String,
+    /// Name of whoever started the topic
+    #[serde(rename = "sFullName", alias = "sAuthor", default)]
+    pub author: Option<String>,
+    #[serde(rename = "dtPost", alias = "dt", default)]
+    pub posted: Option<DateTime<Utc>>,
+    #[serde(rename = "cPosts", alias = "cReplies", default)]
+    pub post_count: Option<u32>,
+}
+
+impl fmt::Display for DiscussTopic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {}", self.id, self.headline)
+    }
+}
+
+/// A single post in a topic
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DiscussPost {
+    #[serde(rename = "ixDiscussPost", default)]
+    pub id: Option<u32>,
+    #[serde(rename = "sFullName", alias = "sAuthor", default)]
+    pub author: Option<String>,
+    #[serde(rename = "sEmail", default)]
+    pub email: Option<String>,
+    #[serde(rename = "dtPost", alias = "dt", default)]
+    pub posted: Option<DateTime<Utc>>,
+    /// HTML body
+    #[serde(rename = "sPost", alias = "s", default)]
+    pub body: String,
+}
+
+impl fmt::Display for DiscussPost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.author.as_deref().unwrap_or("anonymous"))?;
+        if let Some(posted) = self.posted {
+            write!(f, " ({})", posted.format("%Y-%m-%d %H:%M"))?;
+        }
+        write!(f, ": {}", self.body)
+    }
+}
+
+/// First of `keys` present under `data`; the list names differ between FogBugz versions
+fn take_list(response: &mut Value, keys: &[&str]) -> Value {
+    keys.iter()
+        .map(|key| response["data"][*key].take())
+        .find(|list| !list.is_null())
+        .unwrap_or_else(|| Value::Array(Vec::new()))
+}
+
+/// Request to list all discussion groups
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct ListDiscussGroupsRequest {
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl ListDiscussGroupsRequest {
+    pub async fn send(&self) -> Result<Vec<DiscussGroup>, ResponseError> {
+        let mut response = self.client.send_command("listDiscussGroups", self).await?;
+        let groups = take_list(&mut response, &["discussGroups", "discussions"]);
+        Ok(serde_json::from_value(groups)?)
+    }
+}
+
+/// Request to list the topics of a discussion group
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct ListDiscussionRequest {
+    /// Discussion group to list (required)
+    #[serde(rename = "ixDiscussGroup")]
+    group_id: u32,
+    /// Only topics from this month, 1-12 (optional; FogBugz defaults to recent topics)
+    #[serde(rename = "nMonth", skip_serializing_if = "Option::is_none")]
+    month: Option<u32>,
+    /// Year of `month` (optional)
+    #[serde(rename = "nYear", skip_serializing_if = "Option::is_none")]
+    year: Option<u32>,
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl ListDiscussionRequest {
+    pub async fn send(&self) -> Result<Vec<DiscussTopic>, ResponseError> {
+        let mut response = self.client.send_command("listDiscussion", self).await?;
+        let topics = take_list(&mut response, &["topics", "discussion"]);
+        Ok(serde_json::from_value(topics)?)
+    }
+}
+
+/// Request to list the posts of a topic
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct ListDiscussTopicRequest {
+    /// Topic to list (required)
+    #[serde(rename = "ixDiscussTopic")]
+    topic_id: u32,
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl ListDiscussTopicRequest {
+    pub async fn send(&self) -> Result<Vec<DiscussPost>, ResponseError> {
+        let mut response = self.client.send_command("listDiscussTopic", self).await?;
+        let posts = take_list(&mut response, &["posts", "topic"]);
+        Ok(serde_json::from_value(posts)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discussion_deserialization() {
+        let mut response = serde_json::json!({
+            "data": {
+                "posts": [
+                    {
+                        "ixDiscussPost": 3,
+                        "sFullName": "Jane Doe",
+                        "dtPost": "2024-03-04T10:30:00Z",
+                        "sPost": "Has anyone seen this?"
+                    },
+                    { "sPost": "Yes" }
+                ]
+            }
+        });
+        let posts: Vec<DiscussPost> =
+            serde_json::from_value(take_list(&mut response, &["posts", "topic"])).unwrap();
+        assert_eq!(
+            posts[0].to_string(),
+            "Jane Doe (2024-03-04 10:30): Has anyone seen this?"
+        );
+        assert_eq!(posts[1].to_string(), "anonymous: Yes");
+
+        let topic: DiscussTopic = serde_json::from_value(serde_json::json!({
+            "ixDiscussTopic": 9, "sTopic": "Login loop"
+        }))
+        .unwrap();
+        assert_eq!(topic.to_string(), "#9 Login loop");
+        assert!(
+            take_list(&mut serde_json::json!({}), &["topics"])
+                .as_array()
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
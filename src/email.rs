@@ -0,0 +1,293 @@
+use std::collections::HashMap;
+
+use bon::Builder;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    FogBugzClient, ResponseError,
+    campaign::case_variables,
+    case_management::CaseResponse,
+    template::{Template, TemplateError},
+};
+
+/// A reusable email macro. Placeholders are the case fields available to comment
+/// campaigns (`{{case_id}}`, `{{title}}`, `{{project}}`, `{{area}}`,
+/// `{{assigned_to}}`) plus `{{correspondent}}`, the name of the person being written to.
+#[derive(Debug, Clone)]
+pub struct EmailTemplate {
+    pub subject: Template,
+    pub body: Template,
+}
+
+/// Subject and body rendered for one case
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub body: String,
+}
+
+impl EmailTemplate {
+    pub fn new(subject: impl Into<Template>, body: impl Into<Template>) -> Self {
+        Self {
+            subject: subject.into(),
+            body: body.into(),
+        }
+    }
+
+    /// Render for a case as returned by search. Values substituted into the body
+    /// are HTML-escaped; the subject is plain text.
+    pub fn render(
+        &self,
+        case: &Value,
+        correspondent: &str,
+    ) -> Result<RenderedEmail, TemplateError> {
+        let mut vars = case_variables(case);
+        vars.insert("correspondent".to_string(), correspondent.to_string());
+        self.render_with(&vars)
+    }
+
+    /// Render with explicit variables
+    pub fn render_with(
+        &self,
+        vars: &HashMap<String, String>,
+    ) -> Result<RenderedEmail, TemplateError> {
+        Ok(RenderedEmail {
+            subject: self.subject.render(vars)?,
+            body: self.body.render_html(vars)?,
+        })
+    }
+}
+
+/// Request to reply to the customer who opened a case by email
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct ReplyEmailRequest {
+    /// Case ID to reply on (required)
+    #[serde(rename = "ixBug")]
+    case_id: u64,
+
+    /// Sender address; the project's default if not set
+    #[serde(rename = "sFrom", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    from: Option<String>,
+
+    /// Recipients (required)
+    #[serde(rename = "sTo")]
+    #[builder(into)]
+    to: String,
+
+    #[serde(rename = "sCC", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    cc: Option<String>,
+
+    #[serde(rename = "sBCC", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    bcc: Option<String>,
+
+    #[serde(rename = "sSubject", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    subject: Option<String>,
+
+    /// Message body (required)
+    #[serde(rename = "sEvent")]
+    #[builder(into)]
+    body: String,
+
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl<S: reply_email_request_builder::State> ReplyEmailRequestBuilder<S> {
+    /// Use a rendered `EmailTemplate` as subject and body
+    pub fn email(
+        self,
+        email: RenderedEmail,
+    ) -> ReplyEmailRequestBuilder<
+        reply_email_request_builder::SetBody<reply_email_request_builder::SetSubject<S>>,
+    >
+    where
+        S::Subject: bon::__::IsUnset,
+        S::Body: bon::__::IsUnset,
+    {
+        self.subject(email.subject).body(email.body)
+    }
+}
+
+impl ReplyEmailRequest {
+    /// Send the reply
+    pub async fn send(&self) -> Result<CaseResponse, ResponseError> {
+        let response = self.client.send_command("reply", self).await?;
+        CaseResponse::from_response(response)
+    }
+}
+
+/// Request to forward a case by email
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct ForwardEmailRequest {
+    /// Case ID to forward (required)
+    #[serde(rename = "ixBug")]
+    case_id: u64,
+
+    /// Sender address; the project's default if not set
+    #[serde(rename = "sFrom", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    from: Option<String>,
+
+    /// Recipients (required)
+    #[serde(rename = "sTo")]
+    #[builder(into)]
+    to: String,
+
+    #[serde(rename = "sCC", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    cc: Option<String>,
+
+    #[serde(rename = "sBCC", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    bcc: Option<String>,
+
+    #[serde(rename = "sSubject", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    subject: Option<String>,
+
+    /// Message body (required)
+    #[serde(rename = "sEvent")]
+    #[builder(into)]
+    body: String,
+
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl<S: forward_email_request_builder::State> ForwardEmailRequestBuilder<S> {
+    /// Use a rendered `EmailTemplate` as subject and body
+    pub fn email(
+        self,
+        email: RenderedEmail,
+    ) -> ForwardEmailRequestBuilder<
+        forward_email_request_builder::SetBody<forward_email_request_builder::SetSubject<S>>,
+    >
+    where
+        S::Subject: bon::__::IsUnset,
+        S::Body: bon::__::IsUnset,
+    {
+        self.subject(email.subject).body(email.body)
+    }
+}
+
+impl ForwardEmailRequest {
+    /// Forward the case
+    pub async fn send(&self) -> Result<CaseResponse, ResponseError> {
+        let response = self.client.send_command("forward", self).await?;
+        CaseResponse::from_response(response)
+    }
+}
+
+/// Request to send a new email from a case, or from a new case when no case ID is given
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct NewEmailRequest {
+    /// Case to send the email from; a new case is opened if not set
+    #[serde(rename = "ixBug", skip_serializing_if = "Option::is_none")]
+    case_id: Option<u64>,
+
+    /// Project of the new case when `case_id` isn't set (optional)
+    #[serde(rename = "ixProject", skip_serializing_if = "Option::is_none")]
+    project_id: Option<u32>,
+
+    /// Sender address; the project's default if not set
+    #[serde(rename = "sFrom", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    from: Option<String>,
+
+    /// Recipients (required)
+    #[serde(rename = "sTo")]
+    #[builder(into)]
+    to: String,
+
+    #[serde(rename = "sCC", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    cc: Option<String>,
+
+    #[serde(rename = "sBCC", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    bcc: Option<String>,
+
+    /// Subject (required); also the title of a new case
+    #[serde(rename = "sSubject")]
+    #[builder(into)]
+    subject: String,
+
+    /// Message body (required)
+    #[serde(rename = "sEvent")]
+    #[builder(into)]
+    body: String,
+
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl<S: new_email_request_builder::State> NewEmailRequestBuilder<S> {
+    /// Use a rendered `EmailTemplate` as subject and body
+    pub fn email(
+        self,
+        email: RenderedEmail,
+    ) -> NewEmailRequestBuilder<
+        new_email_request_builder::SetBody<new_email_request_builder::SetSubject<S>>,
+    >
+    where
+        S::Subject: bon::__::IsUnset,
+        S::Body: bon::__::IsUnset,
+    {
+        self.subject(email.subject).body(email.body)
+    }
+}
+
+impl NewEmailRequest {
+    /// Send the email
+    pub async fn send(&self) -> Result<CaseResponse, ResponseError> {
+        let response = self.client.send_command("email", self).await?;
+        CaseResponse::from_response(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email_template_escapes_body_values() {
+        let template = EmailTemplate::new(
+            "Re: {{title}} (case {{case_id}})",
+            "<p>Hi {{correspondent}},</p><p>we are looking into {{title}}.</p>",
+        );
+        let case = serde_json::json!({
+            "ixBug": 62020,
+            "sTitle": "Crash on <script> tag",
+            "sProject": "Widget Factory",
+        });
+        let email = template.render(&case, "Jane & John").unwrap();
+        assert_eq!(email.subject, "Re: Crash on <script> tag (case 62020)");
+        assert_eq!(
+            email.body,
+            "<p>Hi Jane &amp; John,</p><p>we are looking into Crash on &lt;script&gt; tag.</p>"
+        );
+
+        let api = FogBugzClient::new("https://example.com", "test_key");
+        let request = api
+            .reply()
+            .case_id(62020)
+            .to("jane@example.com")
+            .email(email)
+            .build();
+        assert_eq!(
+            request.subject.as_deref(),
+            Some("Re: Crash on <script> tag (case 62020)")
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use std::path::{Path, PathBuf};
+
+use mail_parser::{MessageParser, MimeHeaders};
+use thiserror::Error;
+
+use crate::{
+    FogBugzClient, ResponseError,
+    case_management::{AttachmentUpload, NewCaseResponse},
+};
+
+#[derive(Debug, Error)]
+pub enum EmlError {
+    #[error("Could not read email file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Not an RFC 822 message")]
+    Unparseable,
+}
+
+/// Where to read an RFC 822 message from
+#[derive(Debug, Clone)]
+pub enum EmlSource {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+}
+
+impl From<PathBuf> for EmlSource {
+    fn from(path: PathBuf) -> Self {
+        EmlSource::Path(path)
+    }
+}
+
+impl From<&Path> for EmlSource {
+    fn from(path: &Path) -> Self {
+        EmlSource::Path(path.to_path_buf())
+    }
+}
+
+impl From<Vec<u8>> for EmlSource {
+    fn from(bytes: Vec<u8>) -> Self {
+        EmlSource::Bytes(bytes)
+    }
+}
+
+impl From<&[u8]> for EmlSource {
+    fn from(bytes: &[u8]) -> Self {
+        EmlSource::Bytes(bytes.to_vec())
+    }
+}
+
+impl EmlSource {
+    async fn read(self) -> Result<Vec<u8>, EmlError> {
+        match self {
+            EmlSource::Path(path) => Ok(tokio::fs::read(path).await?),
+            EmlSource::Bytes(bytes) => Ok(bytes),
+        }
+    }
+}
+
+/// The parts of an email that go into a case
+#[derive(Debug)]
+pub struct ParsedEmail {
+    pub subject: String,
+    pub from_name: Option<String>,
+    pub from_address: Option<String>,
+    /// Plain-text body; HTML-only messages are converted to text
+    pub body: String,
+    pub attachments: Vec<AttachmentUpload>,
+}
+
+/// Parse an RFC 822 message
+pub fn parse_eml(raw: &[u8]) -> Result<ParsedEmail, EmlError> {
+    let message = MessageParser::default()
+        .parse(raw)
+        .ok_or(EmlError::Unparseable)?;
+    let from = message.from().and_then(|from| from.first());
+    let attachments = message
+        .attachments()
+        .enumerate()
+        .map(|(index, part)| {
+            let file_name = part
+                .attachment_name()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("attachment-{}", index + 1));
+            let upload = AttachmentUpload::new(file_name, part.contents());
+            match part.content_type() {
+                Some(content_type) => upload.with_mime_type(match content_type.subtype() {
+                    Some(subtype) => format!("{}/{}", content_type.ctype(), subtype),
+                    None => content_type.ctype().to_string(),
+                }),
+                None => upload,
+            }
+        })
+        .collect();
+    Ok(ParsedEmail {
+        subject: message
+            .subject()
+            .map(str::trim)
+            .filter(|subject| !subject.is_empty())
+            .unwrap_or("(no subject)")
+            .to_string(),
+        from_name: from.and_then(|from| from.name()).map(str::to_string),
+        from_address: from.and_then(|from| from.address()).map(str::to_string),
+        body: message
+            .body_text(0)
+            .map(|body| body.into_owned())
+            .unwrap_or_default(),
+        attachments,
+    })
+}
+
+impl ParsedEmail {
+    /// The sender as a case correspondent, `"Name" <address>` when the message
+    /// names them
+    pub fn correspondent(&self) -> Option<String> {
+        let address = self.from_address.as_deref()?;
+        match self.from_name.as_deref().map(str::trim) {
+            Some(name) if !name.is_empty() => {
+                Some(format!("\"{}\" <{}>", name.replace('"', ""), address))
+            }
+            _ => Some(address.to_string()),
+        }
+    }
+}
+
+impl FogBugzClient {
+    /// Create a case from an email message, as if it had arrived through the mailbox
+    /// `mailbox_id`: the subject becomes the title, the body the first event, and
+    /// the sender the correspondent. Attachments are uploaded with the case.
+    pub async fn new_case_from_eml(
+        &self,
+        source: impl Into<EmlSource>,
+        mailbox_id: u32,
+    ) -> Result<NewCaseResponse, ResponseError> {
+        let raw = source.into().read().await?;
+        let email = parse_eml(&raw)?;
+        let correspondent = email.correspondent();
+        self.new_case()
+            .title(email.subject)
+            .description(email.body)
+            .mailbox_id(mailbox_id)
+            .maybe_customer_email(correspondent)
+            .attachments(email.attachments)
+            .build()
+            .send()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_eml() {
+        let raw = concat!(
+            "From: Jane Doe <jane@example.com>\r\n",
+            "To: support@example.com\r\n",
+            "Subject: Printer on fire\r\n",
+            "Date: Mon, 4 Mar 2024 10:30:00 +0000\r\n",
+            "MIME-Version: 1.0\r\n",
+            "Content-Type: multipart/mixed; boundary=\"b1\"\r\n",
+            "\r\n",
+            "--b1\r\n",
+            "Content-Type: text/plain; charset=utf-8\r\n",
+            "\r\n",
+            "It is still burning.\r\n",
+            "--b1\r\n",
+            "Content-Type: text/csv; name=\"log.csv\"\r\n",
+            "Content-Disposition: attachment; filename=\"log.csv\"\r\n",
+            "\r\n",
+            "a,b\r\n",
+            "--b1--\r\n",
+        );
+        let email = parse_eml(raw.as_bytes()).unwrap();
+        assert_eq!(email.subject, "Printer on fire");
+        assert_eq!(email.from_name.as_deref(), Some("Jane Doe"));
+        assert_eq!(email.from_address.as_deref(), Some("jane@example.com"));
+        assert_eq!(
+            email.correspondent().as_deref(),
+            Some("\"Jane Doe\" <jane@example.com>")
+        );
+        assert_eq!(email.body.trim(), "It is still burning.");
+        assert_eq!(email.attachments.len(), 1);
+    }
+
+    #[test]
+    fn test_correspondent_without_name() {
+        let email = parse_eml(b"From: jane@example.com\r\nSubject: Hi\r\n\r\nHello\r\n").unwrap();
+        assert_eq!(email.from_name, None);
+        assert_eq!(email.correspondent().as_deref(), Some("jane@example.com"));
+    }
+}
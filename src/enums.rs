@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, Display, EnumString};
 
-#[derive(Debug, AsRefStr, Display, EnumString)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, AsRefStr, Display, EnumString)]
 #[strum(ascii_case_insensitive)]
 pub enum Column {
     #[strum(serialize = "ixBug", to_string = "ixBug")]
@@ -51,9 +51,138 @@ pub enum Column {
     #[strum(serialize = "sPersonAssignedTo", to_string = "sPersonAssignedTo")]
     #[strum(serialize = "assignedto")]
     PersonAssignedTo,
+    #[strum(serialize = "ixPersonAssignedTo", to_string = "ixPersonAssignedTo")]
+    #[strum(serialize = "assignedtoid")]
+    PersonAssignedToId,
+    #[strum(serialize = "ixBugParent", to_string = "ixBugParent")]
+    #[strum(serialize = "parentid")]
+    ParentId,
+    #[strum(serialize = "ixBugChildren", to_string = "ixBugChildren")]
+    #[strum(serialize = "children")]
+    Children,
     #[strum(serialize = "dtLastUpdated", to_string = "dtLastUpdated")]
     #[strum(serialize = "lastupdated")]
     LastUpdated,
+    #[strum(serialize = "tags", to_string = "tags")]
+    Tags,
+    #[strum(serialize = "dtOpened", to_string = "dtOpened")]
+    #[strum(serialize = "opened")]
+    DateOpened,
+    #[strum(serialize = "dtResolved", to_string = "dtResolved")]
+    #[strum(serialize = "resolved")]
+    DateResolved,
+    #[strum(serialize = "dtClosed", to_string = "dtClosed")]
+    #[strum(serialize = "closed")]
+    DateClosed,
+    #[strum(serialize = "dtDue", to_string = "dtDue")]
+    #[strum(serialize = "due")]
+    DateDue,
+    #[strum(serialize = "sFixFor", to_string = "sFixFor")]
+    #[strum(serialize = "milestone")]
+    Milestone,
+    #[strum(serialize = "ixFixFor", to_string = "ixFixFor")]
+    #[strum(serialize = "milestoneid")]
+    MilestoneId,
+    #[strum(serialize = "sStatus", to_string = "sStatus")]
+    #[strum(serialize = "statusname")]
+    StatusName,
+    #[strum(serialize = "sCategory", to_string = "sCategory")]
+    #[strum(serialize = "categoryname")]
+    CategoryName,
+    #[strum(serialize = "sPriority", to_string = "sPriority")]
+    #[strum(serialize = "priorityname")]
+    PriorityName,
+    #[strum(serialize = "ixArea", to_string = "ixArea")]
+    #[strum(serialize = "areaid")]
+    AreaId,
+    #[strum(serialize = "sLatestTextSummary", to_string = "sLatestTextSummary")]
+    #[strum(serialize = "latestsummary")]
+    LatestTextSummary,
+    #[strum(serialize = "ixPersonOpenedBy", to_string = "ixPersonOpenedBy")]
+    #[strum(serialize = "openedbyid")]
+    PersonOpenedById,
+    #[strum(serialize = "ixPersonResolvedBy", to_string = "ixPersonResolvedBy")]
+    #[strum(serialize = "resolvedbyid")]
+    PersonResolvedById,
+    #[strum(serialize = "ixPersonClosedBy", to_string = "ixPersonClosedBy")]
+    #[strum(serialize = "closedbyid")]
+    PersonClosedById,
+    #[strum(serialize = "sCustomerEmail", to_string = "sCustomerEmail")]
+    #[strum(serialize = "customeremail")]
+    CustomerEmail,
+    #[strum(serialize = "ixBugOriginal", to_string = "ixBugOriginal")]
+    #[strum(serialize = "originalid")]
+    OriginalId,
+    #[strum(serialize = "ixBugDuplicates", to_string = "ixBugDuplicates")]
+    #[strum(serialize = "duplicates")]
+    Duplicates,
+    #[strum(serialize = "ixRelatedBugs", to_string = "ixRelatedBugs")]
+    #[strum(serialize = "related")]
+    RelatedCases,
+    #[strum(serialize = "sVersion", to_string = "sVersion")]
+    #[strum(serialize = "version")]
+    Version,
+    #[strum(serialize = "sComputer", to_string = "sComputer")]
+    #[strum(serialize = "computer")]
+    Computer,
+    #[strum(serialize = "ixBugEventLatest", to_string = "ixBugEventLatest")]
+    #[strum(serialize = "latesteventid")]
+    LatestEventId,
+    #[strum(serialize = "sScoutDescription", to_string = "sScoutDescription")]
+    #[strum(serialize = "scoutdescription")]
+    ScoutDescription,
+    #[strum(serialize = "sScoutMessage", to_string = "sScoutMessage")]
+    #[strum(serialize = "scoutmessage")]
+    ScoutMessage,
+    #[strum(serialize = "fScoutStopReporting", to_string = "fScoutStopReporting")]
+    #[strum(serialize = "scoutstopreporting")]
+    ScoutStopReporting,
+    /// Number of BugzScout occurrences
+    #[strum(serialize = "c", to_string = "c")]
+    #[strum(serialize = "occurrences")]
+    Occurrences,
+}
+
+impl Serialize for Column {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(self.as_ref())
+    }
+}
+
+/// Accepts the FogBugz column name or its lowercase alias, e.g. `ixBug` or `caseid`
+impl<'de> Deserialize<'de> for Column {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let name = String::deserialize(deserializer)?;
+        name.parse()
+            .map_err(|_| serde::de::Error::custom(format!("unknown column: {}", name)))
+    }
+}
+
+/// Typed case structs declare the columns they can't be deserialized without,
+/// so requests can ask for them up front (see `require_columns_for`)
+pub trait RequiredColumns {
+    fn required_columns() -> &'static [Column];
+
+    /// Every column the type has a field for, or `None` if it takes any column
+    fn known_columns() -> Option<&'static [Column]> {
+        None
+    }
+}
+
+/// Append any of `T`'s required columns that are missing from `cols`
+pub(crate) fn add_required_columns<T: RequiredColumns>(cols: &mut Vec<String>) {
+    for column in T::required_columns() {
+        let name = column.to_string();
+        if !cols.contains(&name) {
+            cols.push(name);
+        }
+    }
 }
 
 #[derive(Debug, strum::Display)]
@@ -355,3 +484,21 @@ impl Serialize for Status {
 // //         "fReactivate": false,
 // //         "iOrder": 1
 // //       }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_column_serde_round_trip() {
+        let cols = vec![Column::CaseId, Column::DateOpened, Column::Milestone];
+        let json = serde_json::to_value(&cols).unwrap();
+        assert_eq!(json, serde_json::json!(["ixBug", "dtOpened", "sFixFor"]));
+        assert_eq!(serde_json::from_value::<Vec<Column>>(json).unwrap(), cols);
+        assert_eq!(
+            serde_json::from_value::<Column>(serde_json::json!("latestsummary")).unwrap(),
+            Column::LatestTextSummary
+        );
+        assert!(serde_json::from_value::<Column>(serde_json::json!("sNoSuchColumn")).is_err());
+    }
+}
@@ -0,0 +1,165 @@
+use serde_json::Value;
+
+use crate::{
+    FogBugzClient, ResponseError, case_management::EditCaseResponse, enums::Column, hours::Hours,
+    hours_report::CaseHours, search::SearchRequest,
+};
+
+/// Hours of a parent case's children, summed
+#[derive(Debug)]
+pub struct EstimateRollup {
+    pub parent_id: u64,
+    pub children: Vec<CaseHours>,
+    pub current_estimate: Hours,
+    pub original_estimate: Hours,
+    pub elapsed: Hours,
+    /// Result of writing `current_estimate` back to the parent, if requested and
+    /// at least one child has an estimate
+    pub write_back: Option<EditCaseResponse>,
+}
+
+/// Child case IDs from an `ixBugChildren` value, which FogBugz returns either
+/// as a comma-separated string or as an array
+pub(crate) fn parse_child_ids(value: &Value) -> Vec<u64> {
+    match value {
+        Value::String(ids) => ids
+            .split(',')
+            .filter_map(|id| id.trim().parse().ok())
+            .collect(),
+        Value::Array(ids) => ids
+            .iter()
+            .filter_map(|id| {
+                id.as_u64()
+                    .or_else(|| id.as_str().and_then(|id| id.trim().parse().ok()))
+            })
+            .collect(),
+        Value::Number(id) => id.as_u64().into_iter().collect(),
+        _ => Vec::new(),
+    }
+}
+
+impl FogBugzClient {
+    /// Direct children of a case
+    pub async fn child_case_ids(&self, parent_id: u64) -> Result<Vec<u64>, ResponseError> {
+        let response = SearchRequest::for_cases(self, &[parent_id])
+            .with_cols(&[Column::CaseId, Column::Children])
+            .send()
+            .await?;
+        Ok(parse_child_ids(
+            &response["data"]["cases"][0][Column::Children.as_ref()],
+        ))
+    }
+
+    /// Sum the estimates and elapsed hours of a case's direct children.
+    /// With `write_back`, the summed current estimate is saved as the parent's estimate,
+    /// unless no child has one: a parent is not zeroed for want of child estimates.
+    pub async fn rollup_estimates(
+        &self,
+        parent_id: u64,
+        write_back: bool,
+    ) -> Result<EstimateRollup, ResponseError> {
+        let child_ids = self.child_case_ids(parent_id).await?;
+        let children: Vec<CaseHours> = if child_ids.is_empty() {
+            Vec::new()
+        } else {
+            let mut response = SearchRequest::for_cases(self, &child_ids)
+                .with_cols(&[
+                    Column::HoursElapsed,
+                    Column::HoursCurrentEstimate,
+                    Column::HoursOriginalEstimate,
+                ])
+                .require_columns_for::<CaseHours>()
+                .send()
+                .await?;
+            ResponseError::deserialize_cases(response["data"]["cases"].take())?
+        };
+
+        let current_estimate: Hours = children
+            .iter()
+            .filter_map(|child| child.hours_current_estimate)
+            .sum();
+        let original_estimate = children
+            .iter()
+            .filter_map(|child| child.hours_original_estimate)
+            .sum();
+        let elapsed = children
+            .iter()
+            .filter_map(|child| child.hours_elapsed)
+            .sum();
+
+        let estimated = children
+            .iter()
+            .any(|child| child.hours_current_estimate.is_some());
+        let write_back = if write_back && estimated {
+            let response = self
+                .edit_case()
+                .case_id(parent_id)
+                .current_estimate(current_estimate.as_f64())
+                .build()
+                .send()
+                .await?;
+            Some(response)
+        } else {
+            None
+        };
+
+        Ok(EstimateRollup {
+            parent_id,
+            children,
+            current_estimate,
+            original_estimate,
+            elapsed,
+            write_back,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_server::MockServer;
+
+    #[test]
+    fn test_parse_child_ids() {
+        assert_eq!(
+            parse_child_ids(&serde_json::json!("12, 13,14")),
+            vec![12, 13, 14]
+        );
+        assert_eq!(
+            parse_child_ids(&serde_json::json!([12, "13"])),
+            vec![12, 13]
+        );
+        assert_eq!(parse_child_ids(&serde_json::json!("")), Vec::<u64>::new());
+        assert_eq!(parse_child_ids(&Value::Null), Vec::<u64>::new());
+    }
+
+    #[tokio::test]
+    async fn test_rollup_without_child_estimates_keeps_parent() {
+        let server = MockServer::start(|payload| {
+            let cases = if payload["q"] == "7" {
+                serde_json::json!([{ "ixBug": 7, "ixBugChildren": "8,9" }])
+            } else {
+                serde_json::json!([8, 9].map(|id| serde_json::json!({
+                    "ixBug": id,
+                    "sTitle": "Child",
+                    "sProject": "Inbox",
+                    "sPersonAssignedTo": "Anna",
+                    "hrsCurrEst": null,
+                })))
+            };
+            serde_json::json!({ "data": { "cases": cases }, "errors": [] })
+        })
+        .await;
+        let api = FogBugzClient::new(server.url(), "token");
+
+        let rollup = api.rollup_estimates(7, true).await.unwrap();
+        assert_eq!(rollup.children.len(), 2);
+        assert!(rollup.write_back.is_none());
+        assert!(
+            server
+                .requests()
+                .iter()
+                .all(|payload| payload["cmd"] == "search")
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt, stream};
+use regex::Regex;
+
+use crate::{FogBugzClient, ResponseError, case_details::CaseDetails};
+
+/// Characters of context kept on each side of a match in `EventMatch::snippet`
+const SNIPPET_CONTEXT: usize = 40;
+
+/// What to look for in event bodies
+#[derive(Debug, Clone)]
+pub enum EventPattern {
+    Substring {
+        needle: String,
+        case_insensitive: bool,
+    },
+    Regex(Regex),
+}
+
+impl EventPattern {
+    pub fn substring(needle: impl Into<String>) -> Self {
+        EventPattern::Substring {
+            needle: needle.into(),
+            case_insensitive: false,
+        }
+    }
+
+    pub fn substring_case_insensitive(needle: impl Into<String>) -> Self {
+        EventPattern::Substring {
+            needle: needle.into(),
+            case_insensitive: true,
+        }
+    }
+
+    pub fn regex(pattern: &str) -> Result<Self, regex::Error> {
+        Ok(EventPattern::Regex(Regex::new(pattern)?))
+    }
+
+    /// Byte range of the first match in `text`
+    fn find(&self, text: &str) -> Option<(usize, usize)> {
+        match self {
+            EventPattern::Substring {
+                needle,
+                case_insensitive: false,
+            } => text
+                .find(needle.as_str())
+                .map(|start| (start, start + needle.len())),
+            EventPattern::Substring {
+                needle,
+                case_insensitive: true,
+            } => {
+                // Lowercasing can change byte lengths for non-ASCII text, so walk char boundaries
+                let needle = needle.to_lowercase();
+                text.char_indices().find_map(|(start, _)| {
+                    let rest = &text[start..];
+                    let mut end = start;
+                    let mut candidate = String::new();
+                    for c in rest.chars() {
+                        if candidate.len() >= needle.len() {
+                            break;
+                        }
+                        candidate.extend(c.to_lowercase());
+                        end += c.len_utf8();
+                    }
+                    (candidate == needle).then_some((start, end))
+                })
+            }
+            EventPattern::Regex(regex) => regex.find(text).map(|m| (m.start(), m.end())),
+        }
+    }
+}
+
+/// An event whose body matched the pattern
+#[derive(Debug, Clone)]
+pub struct EventMatch {
+    pub case_id: u64,
+    pub event_id: u64,
+    pub person: String,
+    pub datetime: DateTime<Utc>,
+    /// The matched text
+    pub matched: String,
+    /// The match with some surrounding context
+    pub snippet: String,
+}
+
+fn floor_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    while !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+/// Find matching events in a case
+fn match_events(case: &CaseDetails, pattern: &EventPattern) -> Vec<EventMatch> {
+    case.events
+        .iter()
+        .filter_map(|event| {
+            let text = event.content.as_str();
+            let (start, end) = pattern.find(text)?;
+            let snippet_start = floor_char_boundary(text, start.saturating_sub(SNIPPET_CONTEXT));
+            let snippet_end = ceil_char_boundary(text, (end + SNIPPET_CONTEXT).min(text.len()));
+            Some(EventMatch {
+                case_id: case.case_id,
+                event_id: event.event_id,
+                person: event.person.clone(),
+                datetime: event.datetime,
+                matched: text[start..end].to_string(),
+                snippet: text[snippet_start..snippet_end].to_string(),
+            })
+        })
+        .collect()
+}
+
+impl FogBugzClient {
+    /// Search event bodies of the given cases client-side, for content FogBugz's
+    /// full-text search doesn't index. Cases are fetched one at a time and matches
+    /// are yielded as soon as each case has been scanned.
+    pub fn search_events<'a>(
+        &'a self,
+        case_ids: &'a [u64],
+        pattern: &'a EventPattern,
+    ) -> impl Stream<Item = Result<EventMatch, ResponseError>> + 'a {
+        stream::iter(case_ids)
+            .then(move |&case_id| async move {
+                self.case_details()
+                    .case_id(case_id)
+                    .default_cols()
+                    .build()
+                    .send()
+                    .await
+            })
+            .flat_map(move |result| {
+                let matches = match result {
+                    Ok(case) => match_events(&case, pattern).into_iter().map(Ok).collect(),
+                    Err(e) => vec![Err(e)],
+                };
+                stream::iter(matches)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case_with_events(bodies: &[&str]) -> CaseDetails {
+        let events: Vec<serde_json::Value> = bodies
+            .iter()
+            .enumerate()
+            .map(|(i, body)| {
+                serde_json::json!({
+                    "ixBugEvent": i + 1,
+                    "evt": 2,
+                    "evtDescription": "Edited by Jane Doe",
+                    "dt": "2024-03-01T10:00:00Z",
+                    "ixPerson": 75,
+                    "sPerson": "Jane Doe",
+                    "ixPersonAssignedTo": null,
+                    "attachments": null,
+                    "s": body
+                })
+            })
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "ixBug": 62020,
+            "sTitle": "Fix login page",
+            "sProject": "Widget Factory",
+            "fOpen": true,
+            "sArea": "Misc",
+            "ixStatus": 1,
+            "ixPriority": 3,
+            "ixCategory": 1,
+            "events": events
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_match_events_substring_and_regex() {
+        let case = case_with_events(&[
+            "Nothing to see here",
+            "Customer reports ERROR 500 on checkout",
+            "Fixed the error handling",
+        ]);
+
+        let matches = match_events(&case, &EventPattern::substring("ERROR"));
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].event_id, 2);
+
+        let matches = match_events(&case, &EventPattern::substring_case_insensitive("error"));
+        let ids: Vec<u64> = matches.iter().map(|m| m.event_id).collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert_eq!(matches[0].matched, "ERROR");
+
+        let matches = match_events(&case, &EventPattern::regex(r"ERROR \d+").unwrap());
+        assert_eq!(matches[0].matched, "ERROR 500");
+        assert_eq!(matches[0].snippet, "Customer reports ERROR 500 on checkout");
+    }
+}
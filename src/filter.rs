@@ -1,8 +1,78 @@
-use std::fmt;
+use std::{cmp::Ordering, fmt, ops::RangeInclusive};
+
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use strum::{AsRefStr, Display};
+
+use crate::{
+    date::{RelativeDate, search_date},
+    search::case_id_query,
+};
+
+/// Case number that never exists, for queries that must match no cases
+const NO_CASE: &str = "0";
+
+/// Values of the `type` axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, AsRefStr, Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum DocType {
+    Case,
+    Wiki,
+    #[strum(serialize = "discuss")]
+    Discussion,
+}
+
+/// Values of the `status` axis: a status group or the name of a specific status
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum StatusFilter {
+    /// Any status that is not closed
+    Open,
+    Closed,
+    Active,
+    /// Any of the resolved statuses
+    Resolved,
+    /// A status by name, e.g. `Resolved (Fixed)`
+    Named(String),
+}
+
+impl StatusFilter {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Open => "open",
+            Self::Closed => "closed",
+            Self::Active => "active",
+            Self::Resolved => "resolved",
+            Self::Named(name) => name,
+        }
+    }
+}
+
+/// A numeric comparison for hours axes such as `elapsedtime`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Comparison {
+    /// More than, e.g. `">5"`
+    Gt(f64),
+    /// Less than, e.g. `"<5"`
+    Lt(f64),
+    /// Between, inclusive, e.g. `"2..5"`
+    Range(f64, f64),
+}
+
+impl fmt::Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Comparison::Gt(value) => write!(f, ">{value}"),
+            Comparison::Lt(value) => write!(f, "<{value}"),
+            Comparison::Range(from, to) => write!(f, "{from}..{to}"),
+        }
+    }
+}
 
 /// Represents a component of a FogBugz search query.
-#[derive(Clone, Debug)]
-enum SearchComponent {
+/// Serializes as e.g. `{"axis": {"axis": "project", "query": "Widget"}}`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchComponent {
     /// A single search term, e.g., `apple`.
     Term(String),
     /// A search phrase, e.g., `"apple peach"`.
@@ -17,6 +87,13 @@ enum SearchComponent {
     ExactAxis { axis: String, query: String },
     /// A group of components joined by OR, e.g., `(assignedto:"A" OR assignedto:"B")`.
     Or(Vec<SearchComponent>),
+    /// A negated search phrase, e.g., `-"apple peach"`.
+    NegatedPhrase(String),
+    /// A negated OR group, e.g., `-(tag:a OR tag:b)`.
+    NegatedGroup(Vec<SearchComponent>),
+    /// A group of components joined by AND, for nesting inside other groups,
+    /// e.g., `((project:A OR project:B) AND status:active)`.
+    And(Vec<SearchComponent>),
 }
 
 impl SearchComponent {
@@ -34,7 +111,7 @@ impl SearchComponent {
                     || query.contains(':')
                     || query.contains('"')
                     || query.contains("..")
-                    || query.starts_with('-'); // Quote date ranges and descending order
+                    || query.starts_with(['-', '>', '<']); // Quote ranges, comparisons and descending order
 
                 let formatted_query =
                     if needs_quoting && !(query.starts_with('"') && query.ends_with('"')) {
@@ -56,32 +133,51 @@ impl SearchComponent {
                 let formatted_query = query.replace("\"", "\\\"");
                 format!("{}:={}", axis, formatted_query)
             }
-            SearchComponent::Or(components) => {
-                // Filter out potential empty components before joining
-                let parts: Vec<String> = components
-                    .iter()
-                    .map(|c| c.stringify())
-                    .filter(|s| !s.is_empty())
-                    .collect();
-
-                if parts.is_empty() {
-                    String::new() // Return empty string if OR group becomes empty
+            SearchComponent::Or(components) => stringify_group(components, " OR "),
+            SearchComponent::And(components) => stringify_group(components, " AND "),
+            SearchComponent::NegatedPhrase(phrase) => {
+                format!("-{}", SearchComponent::Phrase(phrase.clone()).stringify())
+            }
+            SearchComponent::NegatedGroup(components) => {
+                let group = SearchComponent::Or(components.clone()).stringify();
+                if group.is_empty() {
+                    group
                 } else {
-                    format!("({})", parts.join(" OR ")) // Wrap OR group in parentheses
+                    format!("-{}", group)
                 }
             }
         }
     }
 }
 
-/// Helper struct for building the components within an OR group.
+/// Joins a group's components with `operator` and wraps them in parentheses.
+fn stringify_group(components: &[SearchComponent], operator: &str) -> String {
+    // Filter out potential empty components before joining
+    let parts: Vec<String> = components
+        .iter()
+        .map(|c| c.stringify())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    if parts.is_empty() {
+        String::new() // Return empty string if the group becomes empty
+    } else {
+        format!("({})", parts.join(operator))
+    }
+}
+
+/// Helper struct for building the components within a group.
+/// Whether they are joined by OR or AND depends on the method that opened the group.
 #[derive(Debug, Default)]
-pub struct OrBuilder {
+pub struct GroupBuilder {
     components: Vec<SearchComponent>,
 }
 
-impl OrBuilder {
-    /// Creates a new, empty OR builder. Typically used internally.
+/// Builder passed to `or()`; groups are nestable, so it is a `GroupBuilder`.
+pub type OrBuilder = GroupBuilder;
+
+impl GroupBuilder {
+    /// Creates a new, empty group builder. Typically used internally.
     fn new() -> Self {
         Default::default()
     }
@@ -104,6 +200,15 @@ impl OrBuilder {
         self
     }
 
+    /// Adds a negated term to the group.
+    pub fn negated_term(mut self, term: &str) -> Self {
+        if !term.trim().is_empty() {
+            self.components
+                .push(SearchComponent::NegatedTerm(term.to_string()));
+        }
+        self
+    }
+
     /// Adds an axis search to the OR group.
     pub fn axis(mut self, axis: &str, query: &str) -> Self {
         if !axis.trim().is_empty() && !query.trim().is_empty() {
@@ -115,6 +220,35 @@ impl OrBuilder {
         self
     }
 
+    /// Adds a negated axis search to the group.
+    pub fn negated_axis(mut self, axis: &str, query: &str) -> Self {
+        if !axis.trim().is_empty() && !query.trim().is_empty() {
+            self.components.push(SearchComponent::NegatedAxis {
+                axis: axis.to_string(),
+                query: query.to_string(),
+            });
+        }
+        self
+    }
+
+    /// Adds a nested group joined by OR, e.g., `(project:A OR project:B)`.
+    pub fn or(mut self, build_group: impl FnOnce(GroupBuilder) -> GroupBuilder) -> Self {
+        let group = build_group(GroupBuilder::new());
+        if !group.components.is_empty() {
+            self.components.push(SearchComponent::Or(group.components));
+        }
+        self
+    }
+
+    /// Adds a nested group joined by AND, e.g., `(status:active AND -tag:wip)`.
+    pub fn and(mut self, build_group: impl FnOnce(GroupBuilder) -> GroupBuilder) -> Self {
+        let group = build_group(GroupBuilder::new());
+        if !group.components.is_empty() {
+            self.components.push(SearchComponent::And(group.components));
+        }
+        self
+    }
+
     // --- Add common axis helpers specific to OR groups if desired ---
 
     /// Adds an `assignedto` axis search to the OR group.
@@ -131,11 +265,29 @@ impl OrBuilder {
     pub fn edited_by(self, user_name: &str) -> Self {
         self.axis("editedby", user_name)
     }
+
+    /// Adds a `project` axis search to the group.
+    pub fn project(self, project_name: &str) -> Self {
+        self.axis("project", project_name)
+    }
+
+    /// Adds a `status` axis search to the group.
+    pub fn status(self, status: StatusFilter) -> Self {
+        self.axis("status", status.as_str())
+    }
+
+    /// Adds a `tag` axis search to the group.
+    pub fn tag(self, tag_name: &str) -> Self {
+        self.axis("tag", tag_name)
+    }
 }
 
 /// Builds a FogBugz search query string by combining various filters.
-/// Filters added are implicitly joined by AND, unless grouped using `or()`.
-#[derive(Debug, Default)]
+/// Filters added are implicitly joined by AND, unless grouped using `or()` or `group()`.
+/// Serializes as its list of components, so structured queries can be stored
+/// and edited later instead of only the built string.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct FogBugzSearchBuilder {
     components: Vec<SearchComponent>,
 }
@@ -146,6 +298,21 @@ impl FogBugzSearchBuilder {
         Default::default()
     }
 
+    /// Creates a builder from previously stored components.
+    pub fn from_components(components: Vec<SearchComponent>) -> Self {
+        Self { components }
+    }
+
+    /// The components added so far, in order.
+    pub fn components(&self) -> &[SearchComponent] {
+        &self.components
+    }
+
+    /// The components added so far, for editing in place.
+    pub fn components_mut(&mut self) -> &mut Vec<SearchComponent> {
+        &mut self.components
+    }
+
     /// Adds a simple search term (implicitly ANDed with previous components).
     /// Example: `term("apple")` adds `apple`.
     pub fn term(mut self, term: &str) -> Self {
@@ -181,6 +348,16 @@ impl FogBugzSearchBuilder {
         self
     }
 
+    /// Adds a negated search phrase (implicitly ANDed with previous components).
+    /// Example: `negated_phrase("apple peach")` adds `-"apple peach"`.
+    pub fn negated_phrase(mut self, phrase: &str) -> Self {
+        if !phrase.trim().is_empty() {
+            self.components
+                .push(SearchComponent::NegatedPhrase(phrase.to_string()));
+        }
+        self
+    }
+
     /// Adds an axis search (implicitly ANDed with previous components).
     /// Handles necessary quoting and escaping for the query value.
     /// Example: `axis("project", "Widget Factory")` adds `project:"Widget Factory"`.
@@ -241,6 +418,28 @@ impl FogBugzSearchBuilder {
         self
     }
 
+    /// Adds a parenthesized group joined by AND whose members can themselves be
+    /// nested groups, e.g., `((project:A OR project:B) AND (status:active OR -tag:wip))`.
+    pub fn group(mut self, build_group: impl FnOnce(GroupBuilder) -> GroupBuilder) -> Self {
+        let group = build_group(GroupBuilder::new());
+        if !group.components.is_empty() {
+            self.components.push(SearchComponent::And(group.components));
+        }
+        self
+    }
+
+    /// Excludes cases matching any filter of the group (implicitly ANDed with previous components).
+    /// Example: `negated_or(|group| group.axis("tag", "a").axis("tag", "b"))`
+    /// adds `-(tag:a OR tag:b)`.
+    pub fn negated_or(mut self, build_or_group: impl FnOnce(OrBuilder) -> OrBuilder) -> Self {
+        let finished_builder = build_or_group(OrBuilder::new());
+        if !finished_builder.components.is_empty() {
+            self.components
+                .push(SearchComponent::NegatedGroup(finished_builder.components));
+        }
+        self
+    }
+
     // --- Common Axis Shortcuts ---
 
     /// Adds `project:<project_name>` axis search.
@@ -280,9 +479,9 @@ impl FogBugzSearchBuilder {
         self.axis("resolvedby", user_name)
     }
 
-    /// Adds `status:<status_name>` axis search.
-    pub fn status(self, status_name: &str) -> Self {
-        self.axis("status", status_name)
+    /// Adds `status:<status>` axis search.
+    pub fn status(self, status: StatusFilter) -> Self {
+        self.axis("status", status.as_str())
     }
 
     /// Adds `tag:<tag_name>` axis search (exact match by default in FogBugz).
@@ -302,9 +501,9 @@ impl FogBugzSearchBuilder {
         self.axis("tag", &query)
     }
 
-    /// Adds `type:<doc_type>` axis search ("case", "wiki", "discuss").
-    pub fn type_is(self, doc_type: &str) -> Self {
-        self.axis("type", doc_type)
+    /// Adds `type:<doc_type>` axis search.
+    pub fn type_is(self, doc_type: DocType) -> Self {
+        self.axis("type", doc_type.as_ref())
     }
 
     /// Adds `ixBug:<case_number>` axis search.
@@ -312,6 +511,26 @@ impl FogBugzSearchBuilder {
         self.axis("ixBug", &case_number.to_string()) // ixBug uses standard axis syntax
     }
 
+    /// Adds `ixBug:"<first>..<last>"` axis search for a range of case numbers.
+    /// An empty range matches no cases.
+    pub fn case_number_range(self, case_numbers: RangeInclusive<u64>) -> Self {
+        let (first, last) = case_numbers.into_inner();
+        match first.cmp(&last) {
+            Ordering::Less => self.axis("ixBug", &format!("{first}..{last}")),
+            Ordering::Equal => self.axis("ixBug", &first.to_string()),
+            Ordering::Greater => self.axis("ixBug", NO_CASE),
+        }
+    }
+
+    /// Adds a `1,2,3` list matching any of the given case numbers.
+    /// An empty list matches no cases.
+    pub fn case_numbers(self, case_numbers: &[u64]) -> Self {
+        if case_numbers.is_empty() {
+            return self.axis("ixBug", NO_CASE);
+        }
+        self.term(&case_id_query(case_numbers))
+    }
+
     // --- Date Axis Shortcuts ---
     // These accept string representations as FogBugz date handling is flexible.
     // Examples: "today", "yesterday", "March 2007", "3/26/2007..6/8/2007", "-3w..-1w"
@@ -341,6 +560,70 @@ impl FogBugzSearchBuilder {
         self.axis("due", date_query)
     }
 
+    /// Adds `due:"..yesterday"`: cases whose due date has passed.
+    pub fn overdue(self) -> Self {
+        self.due_date("..yesterday")
+    }
+
+    /// Adds `due:today` axis search.
+    pub fn due_today(self) -> Self {
+        self.due_date("today")
+    }
+
+    /// Adds `due:"this week"` axis search.
+    pub fn due_this_week(self) -> Self {
+        self.due_date("this week")
+    }
+
+    /// Adds `due:"next week"` axis search.
+    pub fn due_next_week(self) -> Self {
+        self.due_date("next week")
+    }
+
+    // --- Hours Axis Shortcuts ---
+
+    /// Adds `elapsedtime:<comparison>` axis search, in hours.
+    pub fn elapsed_time(self, comparison: Comparison) -> Self {
+        self.axis("elapsedtime", &comparison.to_string())
+    }
+
+    /// Adds `currentestimate:<comparison>` axis search, in hours.
+    pub fn current_estimate(self, comparison: Comparison) -> Self {
+        self.axis("currentestimate", &comparison.to_string())
+    }
+
+    /// Adds `originalestimate:<comparison>` axis search, in hours.
+    pub fn original_estimate(self, comparison: Comparison) -> Self {
+        self.axis("originalestimate", &comparison.to_string())
+    }
+
+    /// Adds `remainingtime:<comparison>` axis search, in hours.
+    pub fn remaining_time(self, comparison: Comparison) -> Self {
+        self.axis("remainingtime", &comparison.to_string())
+    }
+
+    /// Adds `edited:"<from>..<to>"` axis search. FogBugz matches whole days,
+    /// so only the (UTC) dates of `from` and `to` are used.
+    pub fn edited_between(self, from: DateTime<Utc>, to: DateTime<Utc>) -> Self {
+        let range = format!(
+            "{}..{}",
+            search_date(from.date_naive()),
+            search_date(to.date_naive())
+        );
+        self.edited_date(&range)
+    }
+
+    /// Adds `opened:"<since>.."` axis search, e.g. `opened_since(Duration::weeks(3))`
+    /// adds `opened:"-3w.."`.
+    pub fn opened_since(self, since: impl Into<RelativeDate>) -> Self {
+        self.opened_date(&format!("{}..", since.into()))
+    }
+
+    /// Adds `resolved:<date>` axis search.
+    pub fn resolved_on(self, date: NaiveDate) -> Self {
+        self.resolved_date(&search_date(date))
+    }
+
     // --- Wildcard / Existence Axis Shortcuts ---
 
     /// Adds search for items *having* a value for the specified axis.
@@ -446,6 +729,14 @@ mod tests {
             .negated_term("peach")
             .build();
         assert_eq!(query, "apple -peach");
+
+        // Negated phrase and negated OR group
+        let query = FogBugzSearchBuilder::new()
+            .negated_phrase("won't \"fix\"")
+            .negated_or(|group| group.axis("tag", "a").axis("tag", "b c"))
+            .negated_or(|group| group)
+            .build();
+        assert_eq!(query, r#"-"won't \"fix\"" -(tag:a OR tag:"b c")"#);
     }
 
     #[test]
@@ -519,6 +810,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_nested_groups() {
+        let query = FogBugzSearchBuilder::new()
+            .group(|g| {
+                g.or(|or| or.project("A").project("B"))
+                    .or(|or| or.status(StatusFilter::Active).negated_axis("tag", "wip"))
+            })
+            .build();
+        assert_eq!(
+            query,
+            "((project:A OR project:B) AND (status:active OR -tag:wip))"
+        );
+
+        // Groups nest to any depth and empty ones are dropped
+        let query = FogBugzSearchBuilder::new()
+            .term("crash")
+            .or(|or| {
+                or.assigned_to("Ann")
+                    .and(|and| and.tag("urgent").or(|or| or.project("A").project("B")))
+                    .and(|and| and)
+            })
+            .group(|g| g)
+            .build();
+        assert_eq!(
+            query,
+            "crash (assignedto:Ann OR (tag:urgent AND (project:A OR project:B)))"
+        );
+    }
+
     #[test]
     fn test_date_searches() {
         // Simple date search
@@ -573,11 +893,105 @@ mod tests {
         assert_eq!(query, "OrderBy:Milestone OrderBy:Priority");
     }
 
+    #[test]
+    fn test_builder_serde_round_trip() {
+        let builder = FogBugzSearchBuilder::new()
+            .project("Widget")
+            .negated_term("peach")
+            .or(|group| group.assigned_to("Alice").term("urgent"));
+        let json = serde_json::to_value(&builder).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([
+                {"axis": {"axis": "project", "query": "Widget"}},
+                {"negated_term": "peach"},
+                {"or": [{"axis": {"axis": "assignedto", "query": "Alice"}}, {"term": "urgent"}]},
+            ])
+        );
+
+        let mut restored: FogBugzSearchBuilder = serde_json::from_value(json).unwrap();
+        assert_eq!(restored, builder);
+        restored.components_mut().remove(1);
+        assert_eq!(
+            restored.build(),
+            "project:Widget (assignedto:Alice OR urgent)"
+        );
+    }
+
+    #[test]
+    fn test_axis_value_enums() {
+        let query = FogBugzSearchBuilder::new()
+            .type_is(DocType::Discussion)
+            .status(StatusFilter::Open)
+            .status(StatusFilter::Named("Resolved (Fixed)".to_string()))
+            .build();
+        assert_eq!(
+            query,
+            "type:discuss status:open status:\"Resolved (Fixed)\""
+        );
+    }
+
+    #[test]
+    fn test_case_number_sets() {
+        let query = FogBugzSearchBuilder::new()
+            .case_number_range(100..=250)
+            .case_number_range(7..=7)
+            .case_numbers(&[3, 5, 8])
+            .build();
+        assert_eq!(query, "ixBug:\"100..250\" ixBug:7 3,5,8");
+    }
+
+    #[test]
+    fn test_empty_case_number_sets_match_nothing() {
+        let query = FogBugzSearchBuilder::new()
+            .project("Inbox")
+            .case_number_range(RangeInclusive::new(250, 100))
+            .build();
+        assert_eq!(query, "project:Inbox ixBug:0");
+
+        let query = FogBugzSearchBuilder::new()
+            .project("Inbox")
+            .case_numbers(&[])
+            .build();
+        assert_eq!(query, "project:Inbox ixBug:0");
+    }
+
+    #[test]
+    fn test_hours_and_due_axes() {
+        let query = FogBugzSearchBuilder::new()
+            .elapsed_time(Comparison::Gt(5.0))
+            .current_estimate(Comparison::Lt(2.5))
+            .remaining_time(Comparison::Range(1.0, 8.0))
+            .due_next_week()
+            .overdue()
+            .build();
+        assert_eq!(
+            query,
+            "elapsedtime:\">5\" currentestimate:\"<2.5\" remainingtime:\"1..8\" due:\"next week\" due:\"..yesterday\""
+        );
+    }
+
+    #[test]
+    fn test_typed_date_axes() {
+        let query = FogBugzSearchBuilder::new()
+            .edited_between(
+                "2024-03-04T10:00:00Z".parse().unwrap(),
+                "2024-03-08T18:00:00Z".parse().unwrap(),
+            )
+            .opened_since(chrono::Duration::weeks(3))
+            .resolved_on(NaiveDate::from_ymd_opt(2024, 3, 5).unwrap())
+            .build();
+        assert_eq!(
+            query,
+            "edited:\"03/04/2024..03/08/2024\" opened:\"-3w..\" resolved:03/05/2024"
+        );
+    }
+
     #[test]
     fn test_complex_query() {
         let query = FogBugzSearchBuilder::new()
             .project("Sample Project")
-            .status("Active")
+            .status(StatusFilter::Active)
             .or(|or| or.assigned_to("Alice").assigned_to("Bob"))
             .edited_date("-1w..today")
             .negated_axis("tag", "obsolete")
@@ -587,7 +1001,7 @@ mod tests {
 
         assert_eq!(
             query,
-            "project:\"Sample Project\" status:Active (assignedto:Alice OR assignedto:Bob) edited:\"-1w..today\" -tag:obsolete OrderBy:Priority OrderBy:\"-Due\""
+            "project:\"Sample Project\" status:active (assignedto:Alice OR assignedto:Bob) edited:\"-1w..today\" -tag:obsolete OrderBy:Priority OrderBy:\"-Due\""
         );
     }
 }
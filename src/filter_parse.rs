@@ -0,0 +1,352 @@
+use std::{iter::Peekable, str::CharIndices, str::FromStr};
+
+use thiserror::Error;
+
+use crate::filter::{FogBugzSearchBuilder, SearchComponent};
+
+/// Why a search string couldn't be read back into components. Positions are
+/// byte offsets into the string.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum QueryParseError {
+    #[error("Quote at {0} is never closed")]
+    UnclosedQuote(usize),
+    #[error("Parenthesis at {0} has no match")]
+    UnmatchedParenthesis(usize),
+    #[error("{operator} at {position} has nothing to join")]
+    DanglingOperator {
+        operator: &'static str,
+        position: usize,
+    },
+}
+
+#[derive(Debug)]
+enum Token {
+    Open,
+    Close,
+    Or,
+    And,
+    /// `-` directly in front of a term, phrase, axis or group
+    Negate,
+    Item(SearchComponent),
+}
+
+/// Read a quoted string starting at the opening quote, unescaping `\"`
+fn read_quoted(
+    chars: &mut Peekable<CharIndices<'_>>,
+    start: usize,
+) -> Result<String, QueryParseError> {
+    chars.next();
+    let mut text = String::new();
+    while let Some((_, c)) = chars.next() {
+        match c {
+            '"' => return Ok(text),
+            '\\' if chars.peek().is_some_and(|&(_, next)| next == '"') => {
+                chars.next();
+                text.push('"');
+            }
+            _ => text.push(c),
+        }
+    }
+    Err(QueryParseError::UnclosedQuote(start))
+}
+
+/// Read characters up to whitespace or a parenthesis
+fn read_bare(chars: &mut Peekable<CharIndices<'_>>, stop_at: &[char]) -> String {
+    let mut text = String::new();
+    while let Some(&(_, c)) = chars.peek() {
+        if c.is_whitespace() || c == '(' || c == ')' || stop_at.contains(&c) {
+            break;
+        }
+        text.push(c);
+        chars.next();
+    }
+    text
+}
+
+fn tokenize(query: &str) -> Result<Vec<(usize, Token)>, QueryParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = query.char_indices().peekable();
+    while let Some(&(position, c)) = chars.peek() {
+        let token = match c {
+            _ if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' => {
+                chars.next();
+                Token::Open
+            }
+            ')' => {
+                chars.next();
+                Token::Close
+            }
+            '"' => Token::Item(SearchComponent::Phrase(read_quoted(&mut chars, position)?)),
+            '-' => {
+                chars.next();
+                match chars.peek() {
+                    Some(&(_, next)) if !next.is_whitespace() && next != ')' => Token::Negate,
+                    _ => Token::Item(SearchComponent::Term("-".to_string())),
+                }
+            }
+            _ => {
+                // A leading colon is part of the term, not an axis separator
+                let mut word = String::new();
+                if c == ':' {
+                    chars.next();
+                    word.push(c);
+                }
+                word.push_str(&read_bare(&mut chars, &[':', '"']));
+                match chars.peek() {
+                    Some(&(_, ':')) => {
+                        chars.next();
+                        let exact = chars.next_if(|&(_, next)| next == '=').is_some();
+                        let query = match chars.peek() {
+                            Some(&(start, '"')) => read_quoted(&mut chars, start)?,
+                            _ => read_bare(&mut chars, &[]),
+                        };
+                        let axis = word;
+                        Token::Item(if exact {
+                            SearchComponent::ExactAxis { axis, query }
+                        } else {
+                            SearchComponent::Axis { axis, query }
+                        })
+                    }
+                    _ => match word.as_str() {
+                        "OR" => Token::Or,
+                        "AND" => Token::And,
+                        _ => Token::Item(SearchComponent::Term(word)),
+                    },
+                }
+            }
+        };
+        tokens.push((position, token));
+    }
+    Ok(tokens)
+}
+
+fn negate(component: SearchComponent) -> SearchComponent {
+    match component {
+        SearchComponent::Term(term) => SearchComponent::NegatedTerm(term),
+        SearchComponent::Phrase(phrase) => SearchComponent::NegatedPhrase(phrase),
+        SearchComponent::Axis { axis, query } => SearchComponent::NegatedAxis { axis, query },
+        SearchComponent::Or(components) => SearchComponent::NegatedGroup(components),
+        other => SearchComponent::NegatedGroup(vec![other]),
+    }
+}
+
+/// The components of a parenthesized group: AND sequences separated by OR
+fn group(mut alternatives: Vec<Vec<SearchComponent>>) -> Option<SearchComponent> {
+    if alternatives.len() == 1 {
+        let sequence = alternatives.remove(0);
+        return (!sequence.is_empty()).then_some(SearchComponent::And(sequence));
+    }
+    let members = alternatives
+        .into_iter()
+        .map(|mut sequence| {
+            if sequence.len() == 1 {
+                sequence.remove(0)
+            } else {
+                SearchComponent::And(sequence)
+            }
+        })
+        .collect();
+    Some(SearchComponent::Or(members))
+}
+
+/// Recursive descent over the tokens; OR binds looser than AND (or juxtaposition)
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    next: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&(usize, Token)> {
+        self.tokens.get(self.next)
+    }
+
+    fn is_sequence_end(&self) -> bool {
+        matches!(
+            self.peek(),
+            None | Some((_, Token::Close | Token::Or | Token::And))
+        )
+    }
+
+    fn parse_or(&mut self) -> Result<Vec<Vec<SearchComponent>>, QueryParseError> {
+        let mut alternatives = vec![self.parse_and()?];
+        while let Some(&(position, Token::Or)) = self.peek() {
+            self.next += 1;
+            let sequence = self.parse_and()?;
+            if sequence.is_empty() || alternatives.iter().any(Vec::is_empty) {
+                return Err(QueryParseError::DanglingOperator {
+                    operator: "OR",
+                    position,
+                });
+            }
+            alternatives.push(sequence);
+        }
+        Ok(alternatives)
+    }
+
+    fn parse_and(&mut self) -> Result<Vec<SearchComponent>, QueryParseError> {
+        let mut sequence = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some((_, Token::Close | Token::Or)) => return Ok(sequence),
+                Some(&(position, Token::And)) => {
+                    self.next += 1;
+                    if sequence.is_empty() || self.is_sequence_end() {
+                        return Err(QueryParseError::DanglingOperator {
+                            operator: "AND",
+                            position,
+                        });
+                    }
+                }
+                Some(_) => sequence.extend(self.parse_unary()?),
+            }
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Option<SearchComponent>, QueryParseError> {
+        if let Some((_, Token::Negate)) = self.peek() {
+            self.next += 1;
+            return Ok(self.parse_primary()?.map(negate));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Option<SearchComponent>, QueryParseError> {
+        let Some((position, token)) = self.tokens.get_mut(self.next) else {
+            return Ok(None);
+        };
+        let position = *position;
+        self.next += 1;
+        match token {
+            Token::Open => {
+                let alternatives = self.parse_or()?;
+                match self.peek() {
+                    Some((_, Token::Close)) => self.next += 1,
+                    _ => return Err(QueryParseError::UnmatchedParenthesis(position)),
+                }
+                Ok(group(alternatives))
+            }
+            Token::Item(component) => Ok(Some(std::mem::replace(
+                component,
+                SearchComponent::Term(String::new()),
+            ))),
+            // `-` in front of another `-`, or an operator after `-`
+            Token::Negate => self.parse_primary().map(|component| component.map(negate)),
+            Token::Close => Err(QueryParseError::UnmatchedParenthesis(position)),
+            Token::Or => Err(QueryParseError::DanglingOperator {
+                operator: "OR",
+                position,
+            }),
+            Token::And => Err(QueryParseError::DanglingOperator {
+                operator: "AND",
+                position,
+            }),
+        }
+    }
+}
+
+impl FogBugzSearchBuilder {
+    /// Reads a FogBugz search string back into components, so a user-supplied
+    /// query can be extended and rebuilt, e.g.
+    /// `FogBugzSearchBuilder::parse(query)?.opened_since(RelativeDate::weeks_ago(1)).build()`.
+    /// Understands terms, phrases, axes (`axis:value`, `axis:"value"`, `axis:=value`),
+    /// `-` negation, parentheses and `OR`/`AND`.
+    pub fn parse(query: &str) -> Result<Self, QueryParseError> {
+        let mut parser = Parser {
+            tokens: tokenize(query)?,
+            next: 0,
+        };
+        let mut alternatives = parser.parse_or()?;
+        if let Some((position, _)) = parser.peek() {
+            return Err(QueryParseError::UnmatchedParenthesis(*position));
+        }
+        let components = if alternatives.len() == 1 {
+            alternatives.remove(0)
+        } else {
+            group(alternatives).into_iter().collect()
+        };
+        Ok(Self::from_components(components))
+    }
+}
+
+impl FromStr for FogBugzSearchBuilder {
+    type Err = QueryParseError;
+
+    fn from_str(query: &str) -> Result<Self, Self::Err> {
+        Self::parse(query)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_round_trips() {
+        for query in [
+            "project:Widget \"apple peach\" -tag:wip ixBug:=12 -\"foo bar\"",
+            "newfeature (assignedto:\"Tester 1\" OR assignedto:\"Tester 2\") -(tag:a OR tag:b)",
+            "((project:A OR project:B) AND (status:active OR -tag:wip))",
+            "title:\"say \\\"hi\\\"\" orderby:\"-pri\" edited:\"01/01/2024..today\"",
+        ] {
+            let builder = FogBugzSearchBuilder::parse(query).unwrap();
+            assert_eq!(builder.build(), query);
+        }
+    }
+
+    #[test]
+    fn test_parse_then_extend() {
+        let builder: FogBugzSearchBuilder = "assignedto:Ann OR assignedto:Bob AND tag:urgent"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            builder.components()[0],
+            SearchComponent::Or(vec![
+                SearchComponent::Axis {
+                    axis: "assignedto".to_string(),
+                    query: "Ann".to_string()
+                },
+                SearchComponent::And(vec![
+                    SearchComponent::Axis {
+                        axis: "assignedto".to_string(),
+                        query: "Bob".to_string()
+                    },
+                    SearchComponent::Axis {
+                        axis: "tag".to_string(),
+                        query: "urgent".to_string()
+                    },
+                ]),
+            ])
+        );
+        assert_eq!(
+            builder.edited_date("today").build(),
+            "(assignedto:Ann OR (assignedto:Bob AND tag:urgent)) edited:today"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            FogBugzSearchBuilder::parse("title:\"open"),
+            Err(QueryParseError::UnclosedQuote(6))
+        );
+        assert_eq!(
+            FogBugzSearchBuilder::parse("(a OR b"),
+            Err(QueryParseError::UnmatchedParenthesis(0))
+        );
+        assert_eq!(
+            FogBugzSearchBuilder::parse("a b)"),
+            Err(QueryParseError::UnmatchedParenthesis(3))
+        );
+        assert_eq!(
+            FogBugzSearchBuilder::parse("a OR"),
+            Err(QueryParseError::DanglingOperator {
+                operator: "OR",
+                position: 2
+            })
+        );
+    }
+}
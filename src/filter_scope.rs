@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use crate::{
+    FogBugzClient, ResponseError,
+    date::DateRange,
+    enums::{Column, RequiredColumns},
+    hours::Hours,
+    list_cases::Case,
+    organization::Filter,
+};
+
+/// A saved filter (`sFilter`) used as a report source, so filters maintained in the
+/// FogBugz UI don't have to be re-encoded as search queries
+#[derive(Debug, Clone)]
+pub struct FilterScope {
+    filter: String,
+    client: FogBugzClient,
+}
+
+impl FilterScope {
+    pub fn new(client: &FogBugzClient, filter: impl Into<String>) -> Self {
+        Self {
+            filter: filter.into(),
+            client: client.clone(),
+        }
+    }
+
+    /// The saved filter ID
+    pub fn filter(&self) -> &str {
+        &self.filter
+    }
+
+    async fn fetch(&self, cols: &[Column]) -> Result<Vec<Value>, ResponseError> {
+        let cols: Vec<String> = cols.iter().map(|col| col.to_string()).collect();
+        let params = serde_json::json!({
+            "sFilter": self.filter,
+            "cols": cols,
+        });
+        let mut response = self.client.send_list_cases(params).await?;
+        match response["data"]["cases"].take() {
+            Value::Array(cases) => Ok(cases),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Cases matching the filter
+    pub async fn cases(&self) -> Result<Vec<Case>, ResponseError> {
+        self.cases_as().await
+    }
+
+    /// Cases matching the filter, with the columns `T` needs
+    pub async fn cases_as<T>(&self) -> Result<Vec<T>, ResponseError>
+    where
+        T: RequiredColumns + DeserializeOwned,
+    {
+        let cases = self.fetch(T::required_columns()).await?;
+        ResponseError::deserialize_cases(Value::Array(cases))
+    }
+
+    /// IDs of the cases matching the filter
+    pub async fn case_ids(&self) -> Result<Vec<u64>, ResponseError> {
+        let cases = self.fetch(&[Column::CaseId]).await?;
+        Ok(cases
+            .iter()
+            .filter_map(|case| case["ixBug"].as_u64())
+            .collect())
+    }
+
+    /// Number of cases matching the filter
+    pub async fn count(&self) -> Result<usize, ResponseError> {
+        Ok(self.case_ids().await?.len())
+    }
+
+    /// Hours logged on the filter's cases during `range`
+    pub async fn hours(&self, range: &DateRange) -> Result<Hours, ResponseError> {
+        let case_ids: HashSet<u64> = self.case_ids().await?.into_iter().collect();
+        let response = self
+            .client
+            .aggregate_hours()
+            .start_date(range.start.start_of_day())
+            .end_date(range.end.end_of_day())
+            .build()
+            .send()
+            .await?;
+        Ok(hours_of_cases(&response, &case_ids))
+    }
+}
+
+/// Elapsed hours of the `case_ids` cases in an aggregated hours response
+fn hours_of_cases(response: &Value, case_ids: &HashSet<u64>) -> Hours {
+    response["data"]["cases"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|case| {
+            case["ixBug"]
+                .as_u64()
+                .is_some_and(|id| case_ids.contains(&id))
+        })
+        .map(|case| Hours::new(case["hrsElapsed"].as_f64().unwrap_or_default()))
+        .sum()
+}
+
+impl Filter {
+    /// Use this saved filter as a report source
+    pub fn scope(&self, client: &FogBugzClient) -> FilterScope {
+        FilterScope::new(client, self.id.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+    use crate::interceptor::RequestContext;
+
+    #[tokio::test]
+    async fn test_filter_scope_lists_saved_filter() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let log = sent.clone();
+        let client = FogBugzClient::new("http://localhost:1", "token").with_interceptor(
+            move |request: &mut RequestContext<'_>| {
+                log.lock().unwrap().push(request.payload.clone());
+            },
+        );
+        let scope = client.filter_scope("12");
+        assert_eq!(scope.filter(), "12");
+
+        assert!(scope.cases().await.is_err());
+        assert!(scope.count().await.is_err());
+
+        let sent = sent.lock().unwrap();
+        assert_eq!(sent.len(), 2);
+        assert!(
+            sent.iter()
+                .all(|payload| payload["cmd"] == "listCases" && payload["sFilter"] == "12")
+        );
+        assert_eq!(
+            sent[0]["cols"],
+            serde_json::json!(["ixBug", "ixProject", "sProject", "sTitle"])
+        );
+        assert_eq!(sent[1]["cols"], serde_json::json!(["ixBug"]));
+    }
+
+    #[test]
+    fn test_hours_of_filter_cases() {
+        let response = serde_json::json!({
+            "data": {
+                "cases": [
+                    { "ixBug": 1, "hrsElapsed": 2.5 },
+                    { "ixBug": 2, "hrsElapsed": 4.0 },
+                    { "ixBug": 3, "hrsElapsed": 1.25 },
+                    { "ixBug": 4 }
+                ]
+            }
+        });
+        assert_eq!(
+            hours_of_cases(&response, &HashSet::from([1, 3, 4])),
+            Hours::new(3.75)
+        );
+        assert_eq!(
+            hours_of_cases(&serde_json::json!({}), &HashSet::from([1])),
+            Hours::default()
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    fmt,
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, Sub, SubAssign},
+};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A number of hours, as used by FogBugz elapsed and estimate columns.
+/// Non-finite values (NaN, infinity) are clamped to zero so sums never get poisoned.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Hours(f64);
+
+impl Hours {
+    pub const ZERO: Hours = Hours(0.0);
+
+    pub fn new(hours: f64) -> Self {
+        if hours.is_finite() {
+            Hours(hours)
+        } else {
+            Hours::ZERO
+        }
+    }
+
+    /// Hours elapsed between two points in time
+    pub fn between(start: DateTime<Utc>, end: DateTime<Utc>) -> Self {
+        Self::from(end - start)
+    }
+
+    pub fn as_f64(self) -> f64 {
+        self.0
+    }
+
+    pub fn to_duration(self) -> Duration {
+        Duration::seconds((self.0 * 3600.0).round() as i64)
+    }
+}
+
+impl From<f64> for Hours {
+    fn from(hours: f64) -> Self {
+        Hours::new(hours)
+    }
+}
+
+impl From<Hours> for f64 {
+    fn from(hours: Hours) -> Self {
+        hours.0
+    }
+}
+
+impl From<Duration> for Hours {
+    fn from(duration: Duration) -> Self {
+        Hours::new(duration.num_seconds() as f64 / 3600.0)
+    }
+}
+
+impl From<Hours> for Duration {
+    fn from(hours: Hours) -> Self {
+        hours.to_duration()
+    }
+}
+
+impl Add for Hours {
+    type Output = Hours;
+
+    fn add(self, rhs: Hours) -> Hours {
+        Hours::new(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Hours {
+    fn add_assign(&mut self, rhs: Hours) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Hours {
+    type Output = Hours;
+
+    fn sub(self, rhs: Hours) -> Hours {
+        Hours::new(self.0 - rhs.0)
+    }
+}
+
+impl SubAssign for Hours {
+    fn sub_assign(&mut self, rhs: Hours) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<f64> for Hours {
+    type Output = Hours;
+
+    fn mul(self, rhs: f64) -> Hours {
+        Hours::new(self.0 * rhs)
+    }
+}
+
+impl Sum for Hours {
+    fn sum<I: Iterator<Item = Hours>>(iter: I) -> Hours {
+        iter.fold(Hours::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Hours> for Hours {
+    fn sum<I: Iterator<Item = &'a Hours>>(iter: I) -> Hours {
+        iter.copied().sum()
+    }
+}
+
+impl fmt::Display for Hours {
+    /// Formats as whole hours and minutes, e.g. `7h 30m`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total_minutes = (self.0 * 60.0).round() as i64;
+        let sign = if total_minutes < 0 { "-" } else { "" };
+        let total_minutes = total_minutes.abs();
+        write!(f, "{}{}h {}m", sign, total_minutes / 60, total_minutes % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hours_display() {
+        assert_eq!(Hours::new(7.5).to_string(), "7h 30m");
+        assert_eq!(Hours::new(0.0).to_string(), "0h 0m");
+        assert_eq!(Hours::new(1.0 / 60.0).to_string(), "0h 1m");
+        assert_eq!(Hours::new(-1.25).to_string(), "-1h 15m");
+    }
+
+    #[test]
+    fn test_hours_duration_conversions() {
+        let hours = Hours::from(Duration::minutes(90));
+        assert_eq!(hours, Hours::new(1.5));
+        assert_eq!(hours.to_duration(), Duration::minutes(90));
+
+        let start = Utc::now();
+        let end = start + Duration::hours(2);
+        assert_eq!(Hours::between(start, end), Hours::new(2.0));
+    }
+
+    #[test]
+    fn test_hours_arithmetic() {
+        let total: Hours = [Hours::new(1.5), Hours::new(2.25), Hours::new(f64::NAN)]
+            .iter()
+            .sum();
+        assert_eq!(total, Hours::new(3.75));
+        assert_eq!(total - Hours::new(0.75), Hours::new(3.0));
+        assert_eq!(Hours::new(2.0) * 1.5, Hours::new(3.0));
+        assert_eq!(Hours::new(f64::INFINITY), Hours::ZERO);
+    }
+}
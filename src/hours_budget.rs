@@ -0,0 +1,250 @@
+use std::{collections::BTreeMap, fmt};
+
+use chrono::{Duration, NaiveDate, Utc};
+use serde::Serialize;
+
+use crate::{
+    FogBugzClient, ResponseError, enums::Column, filter::FogBugzSearchBuilder, hours::Hours,
+    hours_report::CaseHours,
+};
+
+/// What a budget of hours is allocated to
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum BudgetTarget {
+    Project(String),
+    Milestone(String),
+}
+
+impl BudgetTarget {
+    /// Hours logged on the target's cases, per an hours report's `cases`
+    fn logged_in(&self, cases: &[CaseHours]) -> Hours {
+        cases
+            .iter()
+            .filter(|case| match self {
+                BudgetTarget::Project(name) => &case.project == name,
+                BudgetTarget::Milestone(name) => case.milestone.as_ref() == Some(name),
+            })
+            .map(|case| case.hours_elapsed.unwrap_or_default())
+            .sum()
+    }
+
+    fn query(&self) -> String {
+        match self {
+            BudgetTarget::Project(name) => FogBugzSearchBuilder::new().project(name),
+            BudgetTarget::Milestone(name) => FogBugzSearchBuilder::new().axis("milestone", name),
+        }
+        .build()
+    }
+}
+
+impl fmt::Display for BudgetTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetTarget::Project(name) => write!(f, "project {name}"),
+            BudgetTarget::Milestone(name) => write!(f, "milestone {name}"),
+        }
+    }
+}
+
+/// Budgeted hours per project or milestone
+#[derive(Debug, Clone, Default)]
+pub struct HoursBudgets(BTreeMap<BudgetTarget, Hours>);
+
+impl HoursBudgets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn project(mut self, name: impl Into<String>, hours: impl Into<Hours>) -> Self {
+        self.0
+            .insert(BudgetTarget::Project(name.into()), hours.into());
+        self
+    }
+
+    pub fn milestone(mut self, name: impl Into<String>, hours: impl Into<Hours>) -> Self {
+        self.0
+            .insert(BudgetTarget::Milestone(name.into()), hours.into());
+        self
+    }
+
+    pub fn get(&self, target: &BudgetTarget) -> Option<Hours> {
+        self.0.get(target).copied()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&BudgetTarget, &Hours)> {
+        self.0.iter()
+    }
+}
+
+/// Budget, consumption and projection for one project or milestone, as part of
+/// an hours report with `budgets`
+#[derive(Debug, Serialize)]
+pub struct BudgetStatus {
+    pub target: BudgetTarget,
+    pub budget: Hours,
+    /// Elapsed hours of all cases in the project or milestone
+    pub consumed: Hours,
+    /// Negative once the budget is overspent
+    pub remaining: Hours,
+    /// Average hours logged per day over the report's period
+    pub burn_rate: Hours,
+    /// Day the budget runs out at the current burn rate; `None` if nothing is being
+    /// logged, today if it is already used up
+    pub projected_exhaustion: Option<NaiveDate>,
+}
+
+impl BudgetStatus {
+    fn new(
+        target: BudgetTarget,
+        budget: Hours,
+        consumed: Hours,
+        recent: Hours,
+        window_days: u32,
+        today: NaiveDate,
+    ) -> Self {
+        let remaining = budget - consumed;
+        let burn_rate = Hours::new(recent.as_f64() / f64::from(window_days.max(1)));
+        let projected_exhaustion = if remaining.as_f64() <= 0.0 {
+            Some(today)
+        } else if burn_rate.as_f64() > 0.0 {
+            let days = (remaining.as_f64() / burn_rate.as_f64()).ceil() as i64;
+            today.checked_add_signed(Duration::days(days))
+        } else {
+            None
+        };
+        Self {
+            target,
+            budget,
+            consumed,
+            remaining,
+            burn_rate,
+            projected_exhaustion,
+        }
+    }
+}
+
+impl fmt::Display for BudgetStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} of {} used, {} remaining",
+            self.target, self.consumed, self.budget, self.remaining
+        )?;
+        match self.projected_exhaustion {
+            Some(date) => write!(f, ", runs out {}", date.format("%Y-%m-%d")),
+            None => Ok(()),
+        }
+    }
+}
+
+impl HoursBudgets {
+    /// Status of each budget. `cases` are the cases of an hours report covering
+    /// the last `period_days` days, and give the burn rate; consumption is the
+    /// elapsed hours of every case in the project or milestone.
+    pub(crate) async fn statuses(
+        &self,
+        client: &FogBugzClient,
+        cases: &[CaseHours],
+        period_days: u32,
+    ) -> Result<Vec<BudgetStatus>, ResponseError> {
+        let today = Utc::now().date_naive();
+        let mut statuses = Vec::new();
+        for (target, budget) in self.iter() {
+            let consumed: Hours = client
+                .search()
+                .query(target.query())
+                .cols(vec![
+                    Column::CaseId.to_string(),
+                    Column::HoursElapsed.to_string(),
+                ])
+                .build()
+                .send_all()
+                .await?
+                .iter()
+                .map(|case| Hours::new(case["hrsElapsed"].as_f64().unwrap_or_default()))
+                .sum();
+            statuses.push(BudgetStatus::new(
+                target.clone(),
+                *budget,
+                consumed,
+                target.logged_in(cases),
+                period_days,
+                today,
+            ));
+        }
+        Ok(statuses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_status_projection() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 1).unwrap();
+        let target = BudgetTarget::Project("Widgets".to_string());
+
+        // 40h left at 2h/day runs out in 20 days
+        let status = BudgetStatus::new(
+            target.clone(),
+            Hours::new(100.0),
+            Hours::new(60.0),
+            Hours::new(56.0),
+            28,
+            today,
+        );
+        assert_eq!(status.remaining, Hours::new(40.0));
+        assert_eq!(status.burn_rate, Hours::new(2.0));
+        assert_eq!(
+            status.projected_exhaustion,
+            NaiveDate::from_ymd_opt(2024, 3, 21)
+        );
+
+        let idle = BudgetStatus::new(
+            target.clone(),
+            Hours::new(100.0),
+            Hours::new(60.0),
+            Hours::default(),
+            28,
+            today,
+        );
+        assert_eq!(idle.projected_exhaustion, None);
+
+        let overspent = BudgetStatus::new(
+            target,
+            Hours::new(100.0),
+            Hours::new(120.0),
+            Hours::default(),
+            28,
+            today,
+        );
+        assert_eq!(overspent.remaining, Hours::new(-20.0));
+        assert_eq!(overspent.projected_exhaustion, Some(today));
+    }
+
+    #[test]
+    fn test_budget_target_logged_hours() {
+        let cases: Vec<CaseHours> = serde_json::from_value(serde_json::json!([
+            { "ixBug": 1, "sTitle": "Fix login page", "sProject": "Widgets",
+              "hrsElapsed": 3.0, "sPersonAssignedTo": "Jane Doe", "sFixFor": "1.0" },
+            { "ixBug": 2, "sTitle": "Invoice export", "sProject": "Widgets",
+              "hrsElapsed": 1.5, "sPersonAssignedTo": "John Roe", "sFixFor": "2.0" },
+            { "ixBug": 3, "sTitle": "Gadget polish", "sProject": "Gadgets",
+              "hrsElapsed": 2.0, "sPersonAssignedTo": "Jane Doe", "sFixFor": "1.0" }
+        ]))
+        .unwrap();
+        assert_eq!(
+            BudgetTarget::Project("Widgets".to_string()).logged_in(&cases),
+            Hours::new(4.5)
+        );
+        assert_eq!(
+            BudgetTarget::Milestone("1.0".to_string()).logged_in(&cases),
+            Hours::new(5.0)
+        );
+        assert_eq!(
+            BudgetTarget::Milestone("3.0".to_string()).logged_in(&cases),
+            Hours::ZERO
+        );
+    }
+}
@@ -1,7 +1,24 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+};
+
 use bon::Builder;
+use chrono::{DateTime, Datelike, NaiveDate, NaiveTime, Utc};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
-use crate::{FogBugzClient, ResponseError};
+use crate::{
+    FogBugzClient, ResponseError,
+    date::DateRange,
+    enums::{Column, RequiredColumns},
+    hours::Hours,
+    hours_budget::{BudgetStatus, HoursBudgets},
+    hours_tree::HoursTree,
+    organization::{Person, PersonRef, find_person},
+    search::SearchRequest,
+    time_tracking::TimeInterval,
+};
 
 /// Request to view hours remaining report for a milestone
 #[derive(Debug, Serialize, Builder)]
@@ -28,36 +45,163 @@ impl HoursRemainingReportRequest {
 #[derive(Debug, Serialize, Builder)]
 #[builder(state_mod(vis = "pub(crate)"))]
 pub struct AggregateHoursRequest {
-    /// Project ID to aggregate hours for (optional)
-    #[serde(rename = "ixProject", skip_serializing_if = "Option::is_none")]
+    /// Only count hours on cases in this project (optional). `listIntervals`
+    /// can't filter by project, so intervals are matched against the case details.
+    #[serde(skip)]
     project_id: Option<u32>,
-    /// Person ID to filter by (optional)
-    #[serde(rename = "ixPerson", skip_serializing_if = "Option::is_none")]
-    person_id: Option<u32>,
+    /// Person to filter by, either `ixPerson` or a name/email resolved before sending (optional)
+    #[serde(skip)]
+    #[builder(into)]
+    person: Option<PersonRef>,
     /// Start date for aggregation (optional)
     #[serde(rename = "dtStart", skip_serializing_if = "Option::is_none")]
     start_date: Option<String>,
     /// End date for aggregation (optional)
     #[serde(rename = "dtEnd", skip_serializing_if = "Option::is_none")]
     end_date: Option<String>,
+    /// Also total the intervals per group, e.g. per week or per milestone (optional)
+    #[serde(skip)]
+    group_by: Option<GroupBy>,
+    /// Budgeted hours per project or milestone, to report each one's status with
+    /// the burn rate over the report's period (optional)
+    #[serde(skip)]
+    budgets: Option<HoursBudgets>,
     /// API instance
     #[serde(skip)]
     client: FogBugzClient,
 }
 
+impl<S: aggregate_hours_request_builder::State> AggregateHoursRequestBuilder<S> {
+    /// Filter by person ID (`ixPerson`)
+    pub fn person_id(
+        self,
+        person_id: u32,
+    ) -> AggregateHoursRequestBuilder<aggregate_hours_request_builder::SetPerson<S>>
+    where
+        S::Person: bon::__::IsUnset,
+    {
+        self.person(PersonRef::Id(person_id))
+    }
+
+    /// Filter by person full name or email, resolved to `ixPerson` when sending
+    pub fn person_name(
+        self,
+        name_or_email: impl Into<String>,
+    ) -> AggregateHoursRequestBuilder<aggregate_hours_request_builder::SetPerson<S>>
+    where
+        S::Person: bon::__::IsUnset,
+    {
+        self.person(PersonRef::Name(name_or_email.into()))
+    }
+}
+
 impl AggregateHoursRequest {
-    /// Get aggregated hours data using listIntervals for accurate time tracking
+    /// Get aggregated hours data using listIntervals for accurate time tracking,
+    /// shaped like a search response with one entry per case
     pub async fn send(&self) -> Result<serde_json::Value, ResponseError> {
+        let hours = self.send_typed().await?;
+        let cases = serde_json::to_value(&hours.cases)?;
+        Ok(serde_json::json!({
+            "data": {
+                "cases": cases,
+                "count": hours.cases.len(),
+                "totalHits": hours.cases.len()
+            },
+            "errorCode": null,
+            "errors": [],
+            "maxCacheAge": null,
+            "meta": {
+                "clientVersionAllowed": {
+                    "max": 822909000,
+                    "min": 822909000
+                }
+            },
+            "warnings": []
+        }))
+    }
+
+    /// Aggregated hours per case, project and person, and per `group_by` group.
+    /// People are listed at most once per send, for a person given by name and
+    /// for `GroupBy::Person`; set the client's `metadata_cache_ttl` to reuse the
+    /// list across sends.
+    pub async fn send_typed(&self) -> Result<AggregatedHours, ResponseError> {
+        let people = if matches!(self.person, Some(PersonRef::Name(_)))
+            || self.group_by == Some(GroupBy::Person)
+        {
+            self.client.list_people().await?
+        } else {
+            Vec::new()
+        };
+
         // The search API approach doesn't work well for time interval filtering
         // Use listIntervals API instead and aggregate client-side
-        
+        let params = self.interval_params(&people)?;
+        let response = self.client.send_command("listIntervals", params).await?;
+        let mut intervals = finished_intervals(&response["data"]["intervals"])?;
+
+        // Fetch case details for project, estimate and assignee information
+        let case_ids: Vec<u64> = intervals
+            .iter()
+            .map(|interval| u64::from(interval.case_id))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        let details: Vec<CaseHours> = if case_ids.is_empty() {
+            Vec::new()
+        } else {
+            let mut search_response = SearchRequest::for_cases(&self.client, &case_ids)
+                .with_cols(&[
+                    Column::CaseId,
+                    Column::Title,
+                    Column::Project,
+                    Column::ProjectId,
+                    Column::HoursCurrentEstimate,
+                    Column::HoursOriginalEstimate,
+                    Column::PersonAssignedTo,
+                    Column::PersonAssignedToId,
+                    Column::Milestone,
+                    Column::Tags,
+                ])
+                .send()
+                .await?;
+            serde_json::from_value(search_response["data"]["cases"].take())?
+        };
+        if let Some(project_id) = self.project_id {
+            intervals = intervals_in_project(intervals, &details, project_id);
+        }
+
+        let mut hours = aggregate_intervals(&intervals, &details);
+        if let Some(group_by) = self.group_by {
+            let people: HashMap<u32, String> = people
+                .into_iter()
+                .map(|person| (person.id, person.full_name))
+                .collect();
+            hours.groups = group_intervals(&intervals, &hours.cases, group_by, &people);
+        }
+        if let Some(budgets) = &self.budgets {
+            let period_days = self.period_days(&intervals, Utc::now());
+            hours.budgets = budgets
+                .statuses(&self.client, &hours.cases, period_days)
+                .await?;
+        }
+        Ok(hours)
+    }
+
+    /// `listIntervals` parameters, with a person given by name resolved in `people`
+    fn interval_params(&self, people: &[Person]) -> Result<serde_json::Value, ResponseError> {
         let mut params = serde_json::json!({});
-        
+
         // Add person filter (listIntervals supports ixPerson)
-        if let Some(person_id) = self.person_id {
-            params["ixPerson"] = person_id.into();
+        match &self.person {
+            Some(PersonRef::Id(id)) => params["ixPerson"] = (*id).into(),
+            Some(PersonRef::Name(name)) => {
+                let person = find_person(people, name)
+                    .ok_or_else(|| ResponseError::PersonNotFound(name.clone()))?;
+                params["ixPerson"] = person.id.into();
+            }
+            None => {}
         }
-        
+
         // Add date filters (listIntervals supports dtStart/dtEnd)
         if let Some(start_date) = &self.start_date {
             params["dtStart"] = start_date.clone().into();
@@ -65,152 +209,162 @@ impl AggregateHoursRequest {
         if let Some(end_date) = &self.end_date {
             params["dtEnd"] = end_date.clone().into();
         }
+        Ok(params)
+    }
 
-        // Get time intervals using listIntervals command (which properly supports date/person filtering)
-        let intervals_response = self.client.send_command("listIntervals", params).await?;
-        
-        // Process intervals and aggregate by cases/projects
-        if let Some(intervals) = intervals_response["data"]["intervals"].as_array() {
-            let mut cases_map = std::collections::HashMap::new();
-            let mut case_ids = std::collections::HashSet::new();
-            
-            // First pass: collect case IDs and calculate durations
-            for interval in intervals {
-                if let (Some(case_id), Some(title), Some(start_str), Some(end_str)) = (
-                    interval["ixBug"].as_u64(),
-                    interval["sTitle"].as_str(),
-                    interval["dtStart"].as_str(),
-                    interval["dtEnd"].as_str(),
-                ) {
-                    case_ids.insert(case_id);
-                    
-                    // Calculate duration for this interval
-                    if let (Ok(start_time), Ok(end_time)) = (
-                        chrono::DateTime::parse_from_rfc3339(start_str),
-                        chrono::DateTime::parse_from_rfc3339(end_str),
-                    ) {
-                        let duration_hours = (end_time - start_time).num_seconds() as f64 / 3600.0;
-                        
-                        let case_entry = cases_map.entry(case_id).or_insert_with(|| {
-                            serde_json::json!({
-                                "ixBug": case_id,
-                                "sTitle": title,
-                                "hrsElapsed": 0.0,
-                                "hrsCurrEst": 0.0,
-                                "hrsOrigEst": 0.0,
-                                "sProject": "Unknown",
-                                "ixProject": null,
-                                "sPersonAssignedTo": "Unknown",
-                                "ixPersonAssignedTo": null
-                            })
-                        });
-                        
-                        // Add to elapsed hours
-                        if let Some(current_elapsed) = case_entry["hrsElapsed"].as_f64() {
-                            if let Some(number) = serde_json::Number::from_f64(current_elapsed + duration_hours) {
-                                case_entry["hrsElapsed"] = serde_json::Value::Number(number);
-                            }
-                        } else {
-                            if let Some(number) = serde_json::Number::from_f64(duration_hours) {
-                                case_entry["hrsElapsed"] = serde_json::Value::Number(number);
-                            }
-                        }
-                    }
-                }
-            }
-            
-            // Second pass: fetch case details for project information
-            if !case_ids.is_empty() {
-                // Build search query for the specific cases
-                let case_numbers: Vec<String> = case_ids.iter().map(|id| id.to_string()).collect();
-                let case_query = case_numbers.join(",");
-                
-                let search_params = serde_json::json!({
-                    "q": case_query,
-                    "cols": "ixBug,sTitle,sProject,ixProject,hrsElapsed,hrsCurrEst,hrsOrigEst,sPersonAssignedTo,ixPersonAssignedTo"
-                });
-                
-                if let Ok(search_response) = self.client.send_search(search_params).await {
-                    if let Some(cases) = search_response["data"]["cases"].as_array() {
-                        for case in cases {
-                            if let Some(case_id) = case["ixBug"].as_u64() {
-                                if let Some(case_entry) = cases_map.get_mut(&case_id) {
-                                    // Update with project and estimate information
-                                    if let Some(project) = case["sProject"].as_str() {
-                                        case_entry["sProject"] = serde_json::Value::String(project.to_string());
-                                    }
-                                    if let Some(project_id) = case["ixProject"].as_u64() {
-                                        case_entry["ixProject"] = serde_json::Value::Number(serde_json::Number::from(project_id));
-                                    }
-                                    if let Some(curr_est) = case["hrsCurrEst"].as_f64() {
-                                        if let Some(number) = serde_json::Number::from_f64(curr_est) {
-                                            case_entry["hrsCurrEst"] = serde_json::Value::Number(number);
-                                        }
-                                    }
-                                    if let Some(orig_est) = case["hrsOrigEst"].as_f64() {
-                                        if let Some(number) = serde_json::Number::from_f64(orig_est) {
-                                            case_entry["hrsOrigEst"] = serde_json::Value::Number(number);
-                                        }
-                                    }
-                                    if let Some(assigned_to) = case["sPersonAssignedTo"].as_str() {
-                                        case_entry["sPersonAssignedTo"] = serde_json::Value::String(assigned_to.to_string());
-                                    }
-                                    if let Some(assigned_to_id) = case["ixPersonAssignedTo"].as_u64() {
-                                        case_entry["ixPersonAssignedTo"] = serde_json::Value::Number(serde_json::Number::from(assigned_to_id));
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            
-            // Convert to FogBugz search API response format
-            let cases: Vec<serde_json::Value> = cases_map.into_values().collect();
-            let response = serde_json::json!({
-                "data": {
-                    "cases": cases,
-                    "count": cases.len(),
-                    "totalHits": cases.len()
-                },
-                "errorCode": null,
-                "errors": [],
-                "maxCacheAge": null,
-                "meta": {
-                    "clientVersionAllowed": {
-                        "max": 822909000,
-                        "min": 822909000
-                    }
+    /// Whole days from `start_date` to `end_date`. A missing start falls back to
+    /// the earliest interval, a missing end to `now`.
+    fn period_days(&self, intervals: &[TimeInterval], now: DateTime<Utc>) -> u32 {
+        let start = self
+            .start_date
+            .as_deref()
+            .and_then(parse_report_date)
+            .or_else(|| intervals.iter().map(|interval| interval.start_time).min())
+            .unwrap_or(now);
+        let end = self
+            .end_date
+            .as_deref()
+            .and_then(parse_report_date)
+            .unwrap_or(now);
+        let seconds = u64::try_from((end - start).num_seconds()).unwrap_or_default();
+        u32::try_from(seconds.div_ceil(86_400))
+            .unwrap_or(u32::MAX)
+            .max(1)
+    }
+
+    /// Aggregated hours as a project → milestone → case tree
+    pub async fn send_tree(&self) -> Result<HoursTree, ResponseError> {
+        let hours = self.send_typed().await?;
+        Ok(HoursTree::from_cases(&hours.cases))
+    }
+}
+
+/// Hours logged in intervals, totalled per case, project and person
+#[derive(Debug, Default, Serialize)]
+pub struct AggregatedHours {
+    /// Cases with time logged, by case ID; `hours_elapsed` is the time logged
+    /// in the intervals, not the case's lifetime total
+    pub cases: Vec<CaseHours>,
+    /// Totals per project, by project name
+    pub projects: Vec<ProjectHours>,
+    /// Totals per person who logged the time, by person ID
+    pub people: Vec<PersonHours>,
+    /// Totals per group when the request has `group_by`, empty otherwise
+    pub groups: BTreeMap<String, Hours>,
+    /// Status of each budget when the request has `budgets`, empty otherwise
+    pub budgets: Vec<BudgetStatus>,
+}
+
+/// A `dtStart`/`dtEnd` value, as a timestamp or a plain date
+fn parse_report_date(date: &str) -> Option<DateTime<Utc>> {
+    date.parse().ok().or_else(|| {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .ok()
+            .map(|day| day.and_time(NaiveTime::MIN).and_utc())
+    })
+}
+
+/// Intervals of a `listIntervals` response, skipping running timers: they have
+/// no end yet and aren't counted. Any other malformed interval is an error.
+fn finished_intervals(
+    intervals: &serde_json::Value,
+) -> Result<Vec<TimeInterval>, serde_json::Error> {
+    intervals
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|interval| !interval["dtEnd"].is_null())
+        .map(|interval| serde_json::from_value(interval.clone()))
+        .collect()
+}
+
+/// Intervals on cases that `details` places in `project_id`
+fn intervals_in_project(
+    intervals: Vec<TimeInterval>,
+    details: &[CaseHours],
+    project_id: u32,
+) -> Vec<TimeInterval> {
+    let in_project: BTreeSet<u32> = details
+        .iter()
+        .filter(|case| case.project_id == Some(project_id))
+        .map(|case| case.case_id)
+        .collect();
+    intervals
+        .into_iter()
+        .filter(|interval| in_project.contains(&interval.case_id))
+        .collect()
+}
+
+/// Sum interval durations per case, project and person. `details` supplies
+/// project, estimates and assignee for each case; cases missing from it (e.g.
+/// deleted ones) are kept under the "Unknown" project.
+pub fn aggregate_intervals(intervals: &[TimeInterval], details: &[CaseHours]) -> AggregatedHours {
+    let details: BTreeMap<u32, &CaseHours> =
+        details.iter().map(|case| (case.case_id, case)).collect();
+    let mut cases: BTreeMap<u32, CaseHours> = BTreeMap::new();
+    let mut people: BTreeMap<u32, (Hours, BTreeSet<u32>)> = BTreeMap::new();
+    for interval in intervals.iter().filter(|interval| !interval.is_deleted) {
+        let duration = interval.duration();
+        let case = cases.entry(interval.case_id).or_insert_with(|| {
+            let mut case = match details.get(&interval.case_id) {
+                Some(&case) => case.clone(),
+                None => CaseHours {
+                    case_id: interval.case_id,
+                    title: interval.title.clone(),
+                    project: "Unknown".to_string(),
+                    project_id: None,
+                    hours_elapsed: None,
+                    hours_current_estimate: None,
+                    hours_original_estimate: None,
+                    assigned_to: "Unknown".to_string(),
+                    assigned_to_id: None,
+                    milestone: None,
+                    tags: None,
                 },
-                "warnings": []
+            };
+            case.hours_elapsed = Some(Hours::ZERO);
+            case
+        });
+        *case.hours_elapsed.get_or_insert_default() += duration;
+
+        let (elapsed, person_cases) = people.entry(interval.person_id).or_default();
+        *elapsed += duration;
+        person_cases.insert(interval.case_id);
+    }
+
+    let mut projects: BTreeMap<&str, ProjectHours> = BTreeMap::new();
+    for case in cases.values() {
+        let project = projects
+            .entry(&case.project)
+            .or_insert_with(|| ProjectHours {
+                project: case.project.clone(),
+                total_elapsed: Hours::ZERO,
+                total_estimate: Hours::ZERO,
+                case_count: 0,
             });
-            
-            Ok(response)
-        } else {
-            // No intervals found, return empty response
-            Ok(serde_json::json!({
-                "data": {
-                    "cases": [],
-                    "count": 0,
-                    "totalHits": 0
-                },
-                "errorCode": null,
-                "errors": [],
-                "maxCacheAge": null,
-                "meta": {
-                    "clientVersionAllowed": {
-                        "max": 822909000,
-                        "min": 822909000
-                    }
-                },
-                "warnings": []
-            }))
-        }
+        project.total_elapsed += case.hours_elapsed.unwrap_or_default();
+        project.total_estimate += case.hours_current_estimate.unwrap_or_default();
+        project.case_count += 1;
+    }
+
+    AggregatedHours {
+        projects: projects.into_values().collect(),
+        people: people
+            .into_iter()
+            .map(|(person_id, (total_elapsed, cases))| PersonHours {
+                person_id,
+                total_elapsed,
+                case_count: cases.len() as u32,
+            })
+            .collect(),
+        cases: cases.into_values().collect(),
+        groups: BTreeMap::new(),
+        budgets: Vec::new(),
     }
 }
 
 /// Hours data for a case
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CaseHours {
     #[serde(rename = "ixBug")]
     pub case_id: u32,
@@ -221,30 +375,524 @@ pub struct CaseHours {
     #[serde(rename = "ixProject")]
     pub project_id: Option<u32>,
     #[serde(rename = "hrsElapsed")]
-    pub hours_elapsed: Option<f64>,
+    pub hours_elapsed: Option<Hours>,
     #[serde(rename = "hrsCurrEst")]
-    pub hours_current_estimate: Option<f64>,
+    pub hours_current_estimate: Option<Hours>,
     #[serde(rename = "hrsOrigEst")]
-    pub hours_original_estimate: Option<f64>,
+    pub hours_original_estimate: Option<Hours>,
     #[serde(rename = "sPersonAssignedTo")]
     pub assigned_to: String,
     #[serde(rename = "ixPersonAssignedTo")]
     pub assigned_to_id: Option<u32>,
+    #[serde(rename = "sFixFor", default)]
+    pub milestone: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+}
+
+impl RequiredColumns for CaseHours {
+    fn required_columns() -> &'static [Column] {
+        &[
+            Column::CaseId,
+            Column::Title,
+            Column::Project,
+            Column::PersonAssignedTo,
+        ]
+    }
+}
+
+impl fmt::Display for CaseHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} [{}] {}: {} elapsed / {} estimate, assigned to {}",
+            self.case_id,
+            self.project,
+            self.title,
+            self.hours_elapsed.unwrap_or_default(),
+            self.hours_current_estimate.unwrap_or_default(),
+            self.assigned_to
+        )
+    }
 }
 
 /// Aggregated hours by project
 #[derive(Debug, Serialize)]
 pub struct ProjectHours {
     pub project: String,
-    pub total_elapsed: f64,
-    pub total_estimate: f64,
+    pub total_elapsed: Hours,
+    pub total_estimate: Hours,
+    pub case_count: u32,
+}
+
+impl fmt::Display for ProjectHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} elapsed / {} estimate across {} cases",
+            self.project, self.total_elapsed, self.total_estimate, self.case_count
+        )
+    }
+}
+
+/// Aggregated hours by the person who logged them
+#[derive(Debug, Serialize)]
+pub struct PersonHours {
+    pub person_id: u32,
+    pub total_elapsed: Hours,
     pub case_count: u32,
 }
 
+impl fmt::Display for PersonHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "person {}: {} elapsed across {} cases",
+            self.person_id, self.total_elapsed, self.case_count
+        )
+    }
+}
+
+/// How aggregated hours are grouped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    Project,
+    Case,
+    /// Person the case is assigned to
+    AssignedTo,
+    /// Person who logged the time
+    Person,
+    /// Cases without a milestone go under "Undecided"
+    Milestone,
+    /// Time on a case counts towards each of its tags, so groups can overlap;
+    /// untagged cases go under "Untagged"
+    Tag,
+    /// UTC day the interval started, e.g. `2025-01-06`
+    Day,
+    /// ISO week the interval started, e.g. `2025-W02`
+    Week,
+    /// Month the interval started, e.g. `2025-01`
+    Month,
+}
+
+impl GroupBy {
+    /// Whether groups depend on who logged the time or when, not just the case
+    pub fn needs_intervals(self) -> bool {
+        matches!(
+            self,
+            GroupBy::Person | GroupBy::Day | GroupBy::Week | GroupBy::Month
+        )
+    }
+
+    /// Groups of a case, or `None` when the grouping depends on the intervals
+    fn case_keys(self, case: &CaseHours) -> Option<Vec<String>> {
+        let key = match self {
+            GroupBy::Project => case.project.clone(),
+            GroupBy::Case => format!("#{} {}", case.case_id, case.title),
+            GroupBy::AssignedTo => case.assigned_to.clone(),
+            GroupBy::Milestone => case
+                .milestone
+                .clone()
+                .filter(|milestone| !milestone.is_empty())
+                .unwrap_or_else(|| "Undecided".to_string()),
+            GroupBy::Tag => {
+                return Some(match &case.tags {
+                    Some(tags) if !tags.is_empty() => tags.clone(),
+                    _ => vec!["Untagged".to_string()],
+                });
+            }
+            GroupBy::Person | GroupBy::Day | GroupBy::Week | GroupBy::Month => return None,
+        };
+        Some(vec![key])
+    }
+
+    /// Groups time logged in `interval` on `case` counts towards
+    fn interval_keys(
+        self,
+        interval: &TimeInterval,
+        case: &CaseHours,
+        people: &HashMap<u32, String>,
+    ) -> Vec<String> {
+        let start = interval.start_time;
+        let key = match self {
+            GroupBy::Person => people
+                .get(&interval.person_id)
+                .cloned()
+                .unwrap_or_else(|| format!("Person {}", interval.person_id)),
+            GroupBy::Day => start.format("%Y-%m-%d").to_string(),
+            GroupBy::Week => {
+                let week = start.iso_week();
+                format!("{}-W{:02}", week.year(), week.week())
+            }
+            GroupBy::Month => start.format("%Y-%m").to_string(),
+            _ => return self.case_keys(case).unwrap_or_default(),
+        };
+        vec![key]
+    }
+}
+
+/// A grouping by who logged the time or when, which case totals can't give
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+#[error("Grouping by {0:?} needs the time intervals; use group_intervals")]
+pub struct NeedsIntervals(pub GroupBy);
+
+/// Sum elapsed hours per group. Groupings by who logged the time or when
+/// (`Person`, `Day`, `Week`, `Month`) need the intervals, see `group_intervals`.
+pub fn group_hours(
+    cases: &[CaseHours],
+    group_by: GroupBy,
+) -> Result<BTreeMap<String, Hours>, NeedsIntervals> {
+    if group_by.needs_intervals() {
+        return Err(NeedsIntervals(group_by));
+    }
+    let mut groups = BTreeMap::new();
+    for case in cases {
+        for key in group_by.case_keys(case).unwrap_or_default() {
+            *groups.entry(key).or_default() += case.hours_elapsed.unwrap_or_default();
+        }
+    }
+    Ok(groups)
+}
+
+/// Sum interval durations per group. `cases` supplies project, milestone, tags
+/// and so on for each interval's case (see `aggregate_intervals`); `people`
+/// names the people for `GroupBy::Person`, others are shown by ID.
+pub fn group_intervals(
+    intervals: &[TimeInterval],
+    cases: &[CaseHours],
+    group_by: GroupBy,
+    people: &HashMap<u32, String>,
+) -> BTreeMap<String, Hours> {
+    let cases: HashMap<u32, &CaseHours> = cases.iter().map(|case| (case.case_id, case)).collect();
+    let mut groups = BTreeMap::new();
+    for interval in intervals.iter().filter(|interval| !interval.is_deleted) {
+        let Some(case) = cases.get(&interval.case_id) else {
+            continue;
+        };
+        for key in group_by.interval_keys(interval, case, people) {
+            *groups.entry(key).or_default() += interval.duration();
+        }
+    }
+    groups
+}
+
+/// Change in elapsed hours of one group between two periods
+#[derive(Debug, Serialize)]
+pub struct HoursDelta {
+    pub group: String,
+    pub before: Hours,
+    pub after: Hours,
+    pub delta: Hours,
+    /// `None` when the group had no hours in the first period
+    pub percent_change: Option<f64>,
+}
+
+impl fmt::Display for HoursDelta {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} -> {}", self.group, self.before, self.after)?;
+        match self.percent_change {
+            Some(percent) => write!(f, " ({percent:+.1}%)"),
+            None => write!(f, " (new)"),
+        }
+    }
+}
+
+/// Per-group deltas from `before` to `after`, covering groups present in either
+pub fn compare_hours(
+    before: &BTreeMap<String, Hours>,
+    after: &BTreeMap<String, Hours>,
+) -> Vec<HoursDelta> {
+    let groups: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    groups
+        .into_iter()
+        .map(|group| {
+            let before = before.get(group).copied().unwrap_or_default();
+            let after = after.get(group).copied().unwrap_or_default();
+            let percent_change = (before.as_f64() != 0.0)
+                .then(|| (after.as_f64() - before.as_f64()) / before.as_f64() * 100.0);
+            HoursDelta {
+                group: group.clone(),
+                before,
+                after,
+                delta: after - before,
+                percent_change,
+            }
+        })
+        .collect()
+}
+
+impl FogBugzClient {
+    /// Aggregate hours for two periods and compare them group by group,
+    /// e.g. for month-over-month effort reports
+    pub async fn compare_periods(
+        &self,
+        period_a: &DateRange,
+        period_b: &DateRange,
+        group_by: GroupBy,
+    ) -> Result<Vec<HoursDelta>, ResponseError> {
+        let before = self.period_hours(period_a, group_by).await?;
+        let after = self.period_hours(period_b, group_by).await?;
+        Ok(compare_hours(&before, &after))
+    }
+
+    async fn period_hours(
+        &self,
+        period: &DateRange,
+        group_by: GroupBy,
+    ) -> Result<BTreeMap<String, Hours>, ResponseError> {
+        let hours = self
+            .aggregate_hours()
+            .start_date(period.start.start_of_day())
+            .end_date(period.end.end_of_day())
+            .group_by(group_by)
+            .build()
+            .send_typed()
+            .await?;
+        Ok(hours.groups)
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::{
+        CaseHours, GroupBy, NeedsIntervals, ProjectHours, aggregate_intervals, compare_hours,
+        finished_intervals, group_hours, group_intervals, intervals_in_project,
+    };
+    use crate::hours::Hours;
+    use crate::hours_budget::HoursBudgets;
+    use crate::organization::Person;
+    use crate::time_tracking::TimeInterval;
     use crate::FogBugzClient;
 
+    #[test]
+    fn test_hours_rows_display() {
+        let case_hours: CaseHours = serde_json::from_value(serde_json::json!({
+            "ixBug": 62020,
+            "sTitle": "Fix login page",
+            "sProject": "Widget Factory",
+            "ixProject": 3,
+            "hrsElapsed": 7.5,
+            "hrsCurrEst": 10.0,
+            "hrsOrigEst": 8.0,
+            "sPersonAssignedTo": "Jane Doe",
+            "ixPersonAssignedTo": 75
+        }))
+        .unwrap();
+        assert_eq!(
+            case_hours.to_string(),
+            "#62020 [Widget Factory] Fix login page: 7h 30m elapsed / 10h 0m estimate, assigned to Jane Doe"
+        );
+
+        let project_hours = ProjectHours {
+            project: "Widget Factory".to_string(),
+            total_elapsed: Hours::new(12.5),
+            total_estimate: Hours::new(20.0),
+            case_count: 4,
+        };
+        assert_eq!(
+            project_hours.to_string(),
+            "Widget Factory: 12h 30m elapsed / 20h 0m estimate across 4 cases"
+        );
+    }
+
+    #[test]
+    fn test_compare_hours() {
+        let before = BTreeMap::from([
+            ("Widgets".to_string(), Hours::new(10.0)),
+            ("Gadgets".to_string(), Hours::new(4.0)),
+        ]);
+        let after = BTreeMap::from([
+            ("Widgets".to_string(), Hours::new(15.0)),
+            ("Gizmos".to_string(), Hours::new(2.0)),
+        ]);
+        let deltas = compare_hours(&before, &after);
+        let groups: Vec<&str> = deltas.iter().map(|d| d.group.as_str()).collect();
+        assert_eq!(groups, vec!["Gadgets", "Gizmos", "Widgets"]);
+        assert_eq!(deltas[0].percent_change, Some(-100.0));
+        assert_eq!(deltas[1].percent_change, None);
+        assert_eq!(deltas[2].delta, Hours::new(5.0));
+        assert_eq!(deltas[2].percent_change, Some(50.0));
+    }
+
+    fn canned_intervals() -> Vec<TimeInterval> {
+        serde_json::from_value(serde_json::json!([
+            { "ixInterval": 1, "ixPerson": 75, "ixBug": 100, "sTitle": "Fix login page",
+              "dtStart": "2025-01-06T09:00:00Z", "dtEnd": "2025-01-06T11:30:00Z", "fDeleted": false },
+            { "ixInterval": 2, "ixPerson": 76, "ixBug": 100, "sTitle": "Fix login page",
+              "dtStart": "2025-01-06T13:00:00Z", "dtEnd": "2025-01-06T14:00:00Z", "fDeleted": false },
+            { "ixInterval": 3, "ixPerson": 75, "ixBug": 200, "sTitle": "Invoice export",
+              "dtStart": "2025-01-07T09:00:00Z", "dtEnd": "2025-01-07T10:00:00Z", "fDeleted": false },
+            { "ixInterval": 4, "ixPerson": 75, "ixBug": 200, "sTitle": "Invoice export",
+              "dtStart": "2025-01-07T10:00:00Z", "dtEnd": "2025-01-07T18:00:00Z", "fDeleted": true },
+            { "ixInterval": 5, "ixPerson": 76, "ixBug": 300, "sTitle": "Deleted case",
+              "dtStart": "2025-01-13T09:00:00Z", "dtEnd": "2025-01-13T09:45:00Z", "fDeleted": false }
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_finished_intervals() {
+        let intervals = finished_intervals(&serde_json::json!([
+            { "ixInterval": 1, "ixPerson": 75, "ixBug": 100, "sTitle": "Fix login page",
+              "dtStart": "2025-01-06T09:00:00Z", "dtEnd": "2025-01-06T11:30:00Z", "fDeleted": false },
+            { "ixInterval": 2, "ixPerson": 75, "ixBug": 100, "sTitle": "Fix login page",
+              "dtStart": "2025-01-06T13:00:00Z", "dtEnd": null, "fDeleted": false }
+        ]))
+        .unwrap();
+        let ids: Vec<u32> = intervals.iter().map(|interval| interval.id).collect();
+        assert_eq!(ids, vec![1]);
+
+        assert!(
+            finished_intervals(&serde_json::json!([
+                { "ixInterval": 3, "ixPerson": 75, "ixBug": 100, "sTitle": "Fix login page",
+                  "dtStart": "yesterday", "dtEnd": "2025-01-06T11:30:00Z", "fDeleted": false }
+            ]))
+            .is_err()
+        );
+        assert!(
+            finished_intervals(&serde_json::Value::Null)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    fn canned_details() -> Vec<CaseHours> {
+        serde_json::from_value(serde_json::json!([
+            { "ixBug": 100, "sTitle": "Fix login page", "sProject": "Widget Factory",
+              "ixProject": 3, "hrsCurrEst": 6.0, "sPersonAssignedTo": "Jane Doe",
+              "sFixFor": "1.0", "tags": ["auth", "ui"] },
+            { "ixBug": 200, "sTitle": "Invoice export", "sProject": "Widget Factory",
+              "ixProject": 3, "hrsCurrEst": 4.0, "sPersonAssignedTo": "John Roe",
+              "sFixFor": "", "tags": [] }
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_aggregate_intervals() {
+        let intervals = canned_intervals();
+        let details = canned_details();
+
+        let hours = aggregate_intervals(&intervals, &details);
+        let cases: Vec<(u32, &str, Option<Hours>)> = hours
+            .cases
+            .iter()
+            .map(|case| (case.case_id, case.project.as_str(), case.hours_elapsed))
+            .collect();
+        assert_eq!(
+            cases,
+            vec![
+                (100, "Widget Factory", Some(Hours::new(3.5))),
+                (200, "Widget Factory", Some(Hours::new(1.0))),
+                (300, "Unknown", Some(Hours::new(0.75))),
+            ]
+        );
+        assert_eq!(hours.cases[2].title, "Deleted case");
+
+        let projects: Vec<String> = hours.projects.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            projects,
+            vec![
+                "Unknown: 0h 45m elapsed / 0h 0m estimate across 1 cases",
+                "Widget Factory: 4h 30m elapsed / 10h 0m estimate across 2 cases",
+            ]
+        );
+        let people: Vec<String> = hours.people.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            people,
+            vec![
+                "person 75: 3h 30m elapsed across 2 cases",
+                "person 76: 1h 45m elapsed across 2 cases",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_intervals_in_project() {
+        let mut details = canned_details();
+        details[1].project_id = Some(4);
+
+        let intervals = intervals_in_project(canned_intervals(), &details, 3);
+        let ids: Vec<u32> = intervals.iter().map(|interval| interval.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+        assert!(intervals_in_project(canned_intervals(), &details, 9).is_empty());
+    }
+
+    #[test]
+    fn test_group_intervals() {
+        let intervals = canned_intervals();
+        let hours = aggregate_intervals(&intervals, &canned_details());
+        let groups = |group_by| {
+            let people = HashMap::from([(75, "Jane Doe".to_string())]);
+            group_intervals(&intervals, &hours.cases, group_by, &people)
+                .into_iter()
+                .map(|(key, hours)| (key, hours.to_string()))
+                .collect::<Vec<_>>()
+        };
+        let expected = |groups: &[(&str, &str)]| {
+            groups
+                .iter()
+                .map(|(key, hours)| (key.to_string(), hours.to_string()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            groups(GroupBy::Person),
+            expected(&[("Jane Doe", "3h 30m"), ("Person 76", "1h 45m")])
+        );
+        assert_eq!(
+            groups(GroupBy::Milestone),
+            expected(&[("1.0", "3h 30m"), ("Undecided", "1h 45m")])
+        );
+        assert_eq!(
+            groups(GroupBy::Tag),
+            expected(&[("Untagged", "1h 45m"), ("auth", "3h 30m"), ("ui", "3h 30m")])
+        );
+        assert_eq!(
+            groups(GroupBy::Day),
+            expected(&[
+                ("2025-01-06", "3h 30m"),
+                ("2025-01-07", "1h 0m"),
+                ("2025-01-13", "0h 45m")
+            ])
+        );
+        assert_eq!(
+            groups(GroupBy::Week),
+            expected(&[("2025-W02", "4h 30m"), ("2025-W03", "0h 45m")])
+        );
+        assert_eq!(groups(GroupBy::Month), expected(&[("2025-01", "5h 15m")]));
+        assert_eq!(
+            group_hours(&hours.cases, GroupBy::Project),
+            Ok(BTreeMap::from([
+                ("Unknown".to_string(), Hours::new(0.75)),
+                ("Widget Factory".to_string(), Hours::new(4.5)),
+            ]))
+        );
+        assert_eq!(
+            group_hours(&hours.cases, GroupBy::Week),
+            Err(NeedsIntervals(GroupBy::Week))
+        );
+    }
+
+    #[test]
+    fn test_budget_period_days() {
+        let api = FogBugzClient::new("https://example.com", "test_key");
+        let now = "2025-01-20T12:00:00Z".parse().unwrap();
+        let request = api
+            .aggregate_hours()
+            .start_date("2025-01-06".to_string())
+            .end_date("2025-01-19T23:59:59Z".to_string())
+            .budgets(HoursBudgets::new().project("Widget Factory", 40.0))
+            .build();
+        assert_eq!(request.period_days(&[], now), 14);
+
+        // Without a start, the period starts at the earliest interval
+        let request = api.aggregate_hours().build();
+        assert_eq!(request.period_days(&canned_intervals(), now), 15);
+        assert_eq!(request.period_days(&[], now), 1);
+    }
+
     #[test]
     fn test_hours_report_builder_api() {
         #[cfg(feature = "leaky-bucket")]
@@ -276,6 +924,29 @@ mod tests {
             .end_date("2024-12-31".to_string())
             .build();
 
+        // Person can also be given by name or email
+        let people: Vec<Person> = serde_json::from_value(serde_json::json!([{
+            "ixPerson": 75, "sFullName": "Jane Doe", "sEmail": "jane@example.com",
+            "sPhone": "", "fAdministrator": false, "fCommunity": false, "fVirtual": false,
+            "fDeleted": false, "fNotify": true, "sHomepage": "", "sLocale": "*",
+            "sLanguage": "*", "sTimeZoneKey": "*"
+        }]))
+        .unwrap();
+        let aggregate_by_name_request = api
+            .aggregate_hours()
+            .person_name("jane@example.com")
+            .start_date("2024-01-01".to_string())
+            .build();
+        let params = aggregate_by_name_request.interval_params(&people).unwrap();
+        assert_eq!(params["ixPerson"], 75);
+        assert_eq!(params["dtStart"], "2024-01-01");
+
+        let unknown_request = api.aggregate_hours().person_name("Nobody").build();
+        assert!(matches!(
+            unknown_request.interval_params(&people),
+            Err(crate::ResponseError::PersonNotFound(name)) if name == "Nobody"
+        ));
+
         assert!(true);
     }
 
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{hours::Hours, hours_report::CaseHours};
+
+/// Milestone name FogBugz shows for cases without one
+const NO_MILESTONE: &str = "Undecided";
+
+/// Hours rolled up over every case below a node
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct HoursTotals {
+    pub elapsed: Hours,
+    pub current_estimate: Hours,
+    pub original_estimate: Hours,
+    pub case_count: u32,
+}
+
+impl HoursTotals {
+    fn add(&mut self, case: &CaseHours) {
+        self.elapsed += case.hours_elapsed.unwrap_or_default();
+        self.current_estimate += case.hours_current_estimate.unwrap_or_default();
+        self.original_estimate += case.hours_original_estimate.unwrap_or_default();
+        self.case_count += 1;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MilestoneNode {
+    pub milestone: String,
+    pub totals: HoursTotals,
+    /// By case number
+    pub cases: Vec<CaseHours>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectNode {
+    pub project: String,
+    pub totals: HoursTotals,
+    /// By milestone name
+    pub milestones: Vec<MilestoneNode>,
+}
+
+/// Hours report as project → milestone → case, with totals at each level
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct HoursTree {
+    pub totals: HoursTotals,
+    /// By project name
+    pub projects: Vec<ProjectNode>,
+}
+
+impl HoursTree {
+    pub fn from_cases(cases: &[CaseHours]) -> Self {
+        let mut grouped: BTreeMap<&str, BTreeMap<&str, Vec<&CaseHours>>> = BTreeMap::new();
+        for case in cases {
+            let milestone = case.milestone.as_deref().unwrap_or(NO_MILESTONE);
+            grouped
+                .entry(&case.project)
+                .or_default()
+                .entry(milestone)
+                .or_default()
+                .push(case);
+        }
+
+        let mut tree = Self::default();
+        for (project, milestones) in grouped {
+            let mut project_node = ProjectNode {
+                project: project.to_string(),
+                totals: HoursTotals::default(),
+                milestones: Vec::new(),
+            };
+            for (milestone, mut cases) in milestones {
+                cases.sort_by_key(|case| case.case_id);
+                let mut milestone_node = MilestoneNode {
+                    milestone: milestone.to_string(),
+                    totals: HoursTotals::default(),
+                    cases: Vec::with_capacity(cases.len()),
+                };
+                for case in cases {
+                    milestone_node.totals.add(case);
+                    project_node.totals.add(case);
+                    tree.totals.add(case);
+                    milestone_node.cases.push(case.clone());
+                }
+                project_node.milestones.push(milestone_node);
+            }
+            tree.projects.push(project_node);
+        }
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hours_tree_rolls_up_totals() {
+        let cases: Vec<CaseHours> = serde_json::from_value(serde_json::json!([
+            { "ixBug": 3, "sTitle": "c", "sProject": "Web", "ixProject": 1, "hrsElapsed": 2.0,
+              "hrsCurrEst": 4.0, "hrsOrigEst": 3.0, "sPersonAssignedTo": "A", "ixPersonAssignedTo": 1,
+              "sFixFor": "1.0" },
+            { "ixBug": 1, "sTitle": "a", "sProject": "Web", "ixProject": 1, "hrsElapsed": 1.5,
+              "hrsCurrEst": 2.0, "hrsOrigEst": 2.0, "sPersonAssignedTo": "A", "ixPersonAssignedTo": 1,
+              "sFixFor": "1.0" },
+            { "ixBug": 2, "sTitle": "b", "sProject": "Web", "ixProject": 1, "hrsElapsed": 1.0,
+              "hrsCurrEst": null, "hrsOrigEst": null, "sPersonAssignedTo": "B", "ixPersonAssignedTo": 2 },
+            { "ixBug": 4, "sTitle": "d", "sProject": "Api", "ixProject": 2, "hrsElapsed": 0.5,
+              "hrsCurrEst": 1.0, "hrsOrigEst": 1.0, "sPersonAssignedTo": "B", "ixPersonAssignedTo": 2,
+              "sFixFor": "2.0" },
+        ]))
+        .unwrap();
+        let tree = HoursTree::from_cases(&cases);
+
+        assert_eq!(tree.totals.elapsed, Hours::new(5.0));
+        assert_eq!(tree.totals.case_count, 4);
+        let projects: Vec<&str> = tree.projects.iter().map(|p| p.project.as_str()).collect();
+        assert_eq!(projects, vec!["Api", "Web"]);
+
+        let web = &tree.projects[1];
+        assert_eq!(web.totals.elapsed, Hours::new(4.5));
+        assert_eq!(web.totals.current_estimate, Hours::new(6.0));
+        let milestones: Vec<&str> = web
+            .milestones
+            .iter()
+            .map(|m| m.milestone.as_str())
+            .collect();
+        assert_eq!(milestones, vec!["1.0", "Undecided"]);
+        let release = &web.milestones[0];
+        assert_eq!(release.totals.elapsed, Hours::new(3.5));
+        let ids: Vec<u32> = release.cases.iter().map(|case| case.case_id).collect();
+        assert_eq!(ids, vec![1, 3]);
+    }
+}
@@ -0,0 +1,149 @@
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use reqwest::header::HeaderMap;
+use serde_json::Value;
+
+use crate::{FogBugzClient, ResponseError};
+
+/// A command about to be sent
+#[derive(Debug)]
+pub struct RequestContext<'a> {
+    pub cmd: &'a str,
+    /// JSON payload, with `cmd` and `token` set
+    pub payload: &'a mut Value,
+    /// Extra headers to send
+    pub headers: &'a mut HeaderMap,
+}
+
+/// A command that got a response or failed
+#[derive(Debug)]
+pub struct ResponseContext<'a> {
+    pub cmd: &'a str,
+    /// Time from sending the request to having the decoded response
+    pub elapsed: Duration,
+    pub result: Result<&'a Value, &'a ResponseError>,
+}
+
+/// Middleware run around every command a client sends, e.g. to add headers,
+/// audit-log commands or record latencies. Each attempt is seen separately,
+/// so retries and session renewals show up as several requests.
+pub trait Interceptor: Send + Sync {
+    fn on_request(&self, _request: &mut RequestContext<'_>) {}
+
+    fn on_response(&self, _response: &ResponseContext<'_>) {}
+}
+
+impl<F: Fn(&mut RequestContext<'_>) + Send + Sync> Interceptor for F {
+    fn on_request(&self, request: &mut RequestContext<'_>) {
+        self(request)
+    }
+}
+
+/// Interceptors of a client, run in the order they were added
+#[derive(Clone, Default)]
+pub(crate) struct Interceptors(Vec<Arc<dyn Interceptor>>);
+
+impl fmt::Debug for Interceptors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} interceptors", self.0.len())
+    }
+}
+
+impl FogBugzClient {
+    /// Copy of this client that also runs `interceptor` around every command
+    pub fn with_interceptor(&self, interceptor: impl Interceptor + 'static) -> Self {
+        let mut client = self.clone();
+        client.interceptors.0.push(Arc::new(interceptor));
+        client
+    }
+
+    /// Run the interceptors and then the request hook on a command's final
+    /// payload; returns the headers to send and when the request started
+    pub(crate) fn prepare_request(&self, cmd: &str, payload: &mut Value) -> (HeaderMap, Instant) {
+        let mut headers = HeaderMap::new();
+        for interceptor in &self.interceptors.0 {
+            interceptor.on_request(&mut RequestContext {
+                cmd,
+                payload,
+                headers: &mut headers,
+            });
+        }
+        headers.extend(self.sign_request(payload));
+        (headers, Instant::now())
+    }
+
+    /// Report the outcome of a command prepared with `prepare_request`
+    pub(crate) fn finish_request(
+        &self,
+        cmd: &str,
+        started: Instant,
+        result: &Result<Value, ResponseError>,
+    ) {
+        if self.interceptors.0.is_empty() {
+            return;
+        }
+        let response = ResponseContext {
+            cmd,
+            elapsed: started.elapsed(),
+            result: result.as_ref(),
+        };
+        for interceptor in &self.interceptors.0 {
+            interceptor.on_response(&response);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[derive(Default)]
+    struct AuditLog(Mutex<Vec<String>>);
+
+    impl Interceptor for Arc<AuditLog> {
+        fn on_request(&self, request: &mut RequestContext<'_>) {
+            self.0.lock().unwrap().push(format!("send {}", request.cmd));
+        }
+
+        fn on_response(&self, response: &ResponseContext<'_>) {
+            let outcome = if response.result.is_ok() {
+                "ok"
+            } else {
+                "failed"
+            };
+            self.0
+                .lock()
+                .unwrap()
+                .push(format!("{} {outcome}", response.cmd));
+        }
+    }
+
+    #[test]
+    fn test_interceptors_run_in_order() {
+        let log = Arc::new(AuditLog::default());
+        let api = FogBugzClient::new("https://example.com", "key")
+            .with_interceptor(|request: &mut RequestContext<'_>| {
+                request
+                    .headers
+                    .insert("X-Team", HeaderValue::from_static("support"));
+                request.payload["sTitle"] = "intercepted".into();
+            })
+            .with_interceptor(log.clone());
+
+        let mut payload = serde_json::json!({ "cmd": "new" });
+        let (headers, started) = api.prepare_request("new", &mut payload);
+        assert_eq!(headers["X-Team"], "support");
+        assert_eq!(payload["sTitle"], "intercepted");
+
+        api.finish_request("new", started, &Err(ResponseError::ReadOnly("new".into())));
+        assert_eq!(*log.0.lock().unwrap(), vec!["send new", "new failed"]);
+    }
+}
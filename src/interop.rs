@@ -0,0 +1,222 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    FogBugzClient,
+    case_details::{CaseDetails, Event, EventType},
+    case_management::NewCaseRequest,
+    enums::Category,
+};
+
+/// Label prefixes for the FogBugz fields that have no place of their own in the model
+const AREA_LABEL: &str = "area:";
+const PRIORITY_LABEL: &str = "priority:";
+
+/// Open or closed, the one status every tracker agrees on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IssueStatus {
+    Open,
+    Closed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssueComment {
+    pub author: String,
+    pub created: DateTime<Utc>,
+    /// Plain text
+    pub body: String,
+}
+
+/// A URL attached to an issue, e.g. an attachment or a related issue
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssueLink {
+    pub title: String,
+    pub url: String,
+}
+
+/// A tracker-agnostic issue, the intermediate form for migrations between FogBugz
+/// and other trackers. FogBugz fields without a direct counterpart (category, area,
+/// priority) are kept as labels.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IssueModel {
+    /// ID in the source tracker, for cross-references after the migration
+    pub source_id: Option<String>,
+    /// Project (FogBugz), repository (GitHub) or project key (Jira)
+    pub project: Option<String>,
+    pub title: String,
+    /// Plain text
+    pub body: String,
+    pub comments: Vec<IssueComment>,
+    pub labels: Vec<String>,
+    /// Full name of whoever opened the issue
+    #[serde(default)]
+    pub reporter: Option<String>,
+    #[serde(default)]
+    pub created: Option<DateTime<Utc>>,
+    /// Full name of the assignee
+    pub assignee: Option<String>,
+    pub status: IssueStatus,
+    pub links: Vec<IssueLink>,
+}
+
+/// Assignee named by an English `Assigned to <name> by <person>` event description
+fn assignee_from(event: &Event) -> Option<String> {
+    let rest = event.description.strip_prefix("Assigned to ")?;
+    let name = rest.rsplit_once(" by ").map_or(rest, |(name, _)| name);
+    Some(name.trim().to_string()).filter(|name| !name.is_empty())
+}
+
+fn category_from_label(label: &str) -> Option<Category> {
+    [
+        Category::Bug,
+        Category::Feature,
+        Category::Inquiry,
+        Category::Schedule,
+        Category::Report,
+        Category::Emergency,
+        Category::Review,
+    ]
+    .into_iter()
+    .find(|category| category.to_string().eq_ignore_ascii_case(label))
+}
+
+impl From<CaseDetails> for IssueModel {
+    /// The opening event becomes the body and later events with text the comments.
+    /// The assignee is read from the latest assignment event, which assumes an
+    /// English site.
+    fn from(case: CaseDetails) -> Self {
+        let assignee = case
+            .events
+            .iter()
+            .rev()
+            .filter(|event| matches!(event.event_type, EventType::Assigned))
+            .find_map(assignee_from);
+        let links = case
+            .events
+            .iter()
+            .flat_map(|event| event.attachments.iter().flatten())
+            .map(|attachment| IssueLink {
+                title: attachment.file_name.clone(),
+                url: attachment.url.clone(),
+            })
+            .collect();
+        let mut events = case.events.into_iter();
+        let (body, reporter, created) = match events.next() {
+            Some(opened) => (opened.content, Some(opened.person), Some(opened.datetime)),
+            None => (String::new(), None, None),
+        };
+        let comments = events
+            .filter(|event| !event.content.trim().is_empty())
+            .map(|event| IssueComment {
+                author: event.person,
+                created: event.datetime,
+                body: event.content,
+            })
+            .collect();
+        IssueModel {
+            source_id: Some(case.case_id.to_string()),
+            project: Some(case.project),
+            title: case.title,
+            body,
+            comments,
+            labels: vec![
+                case.category.to_string().to_lowercase(),
+                format!("{AREA_LABEL}{}", case.area),
+                format!("{PRIORITY_LABEL}{}", case.priority),
+            ],
+            reporter,
+            created,
+            assignee,
+            status: if case.is_open {
+                IssueStatus::Open
+            } else {
+                IssueStatus::Closed
+            },
+            links,
+        }
+    }
+}
+
+impl IssueModel {
+    /// Map to a FogBugz case. A label naming a category sets the category and an
+    /// `area:` label the area; the other labels become tags, except `priority:`,
+    /// whose values differ per site. Comments and links aren't part of a new case:
+    /// post them with `edit_case` once the case exists.
+    pub fn to_new_case_request(&self, client: &FogBugzClient) -> NewCaseRequest {
+        let mut category = None;
+        let mut area = None;
+        let mut tags = Vec::new();
+        for label in &self.labels {
+            if let Some(name) = label.strip_prefix(AREA_LABEL) {
+                area = Some(name.to_string());
+            } else if label.starts_with(PRIORITY_LABEL) {
+                continue;
+            } else if let Some(found) = category_from_label(label).filter(|_| category.is_none()) {
+                category = Some(found);
+            } else {
+                tags.push(label.as_str());
+            }
+        }
+        NewCaseRequest::builder()
+            .client(client.clone())
+            .title(self.title.clone())
+            .description(self.body.clone())
+            .maybe_project(self.project.clone())
+            .maybe_area(area)
+            .maybe_category(category)
+            .maybe_assigned_to(self.assignee.clone())
+            .maybe_tags((!tags.is_empty()).then(|| tags.join(",")))
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_issue_model_round_trip() {
+        let case: CaseDetails = serde_json::from_value(serde_json::json!({
+            "ixBug": 42,
+            "sTitle": "Export fails",
+            "sProject": "Widgets",
+            "fOpen": false,
+            "sArea": "Export",
+            "ixStatus": 2,
+            "ixPriority": 3,
+            "ixCategory": 1,
+            "events": [
+                {
+                    "evt": 1, "evtDescription": "Opened by Jane Doe",
+                    "dt": "2024-03-04T10:30:00Z", "ixPerson": 2, "sPerson": "Jane Doe",
+                    "ixPersonAssignedTo": 3, "s": "Steps to reproduce",
+                    "attachments": [{ "sFileName": "log.txt", "sURL": "default.asp?x" }]
+                },
+                {
+                    "evt": 3, "evtDescription": "Assigned to John Smith by Jane Doe",
+                    "dt": "2024-03-04T11:00:00Z", "ixPerson": 2, "sPerson": "Jane Doe",
+                    "ixPersonAssignedTo": 4, "s": "Can you look?"
+                }
+            ]
+        }))
+        .unwrap();
+        let issue = IssueModel::from(case);
+        assert_eq!(issue.body, "Steps to reproduce");
+        assert_eq!(issue.reporter.as_deref(), Some("Jane Doe"));
+        assert_eq!(issue.comments.len(), 1);
+        assert_eq!(issue.assignee.as_deref(), Some("John Smith"));
+        assert_eq!(issue.status, IssueStatus::Closed);
+        assert_eq!(issue.links[0].title, "log.txt");
+        assert_eq!(issue.labels[0], "bug");
+
+        let mut issue = issue;
+        issue.labels.push("customer".to_string());
+        let api = FogBugzClient::new("https://example.com", "test_key");
+        let params = serde_json::to_value(issue.to_new_case_request(&api)).unwrap();
+        assert_eq!(params["sProject"], "Widgets");
+        assert_eq!(params["sArea"], "Export");
+        assert!(!params["ixCategory"].is_null());
+        assert_eq!(params["sPersonAssignedTo"], "John Smith");
+        assert_eq!(params["sTags"], "customer");
+    }
+}
@@ -0,0 +1,353 @@
+use std::collections::{HashMap, HashSet};
+
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use thiserror::Error;
+
+use crate::{
+    FogBugzClient, ResponseError,
+    enums::Column,
+    hours::Hours,
+    organization::{PersonRef, find_person},
+    progress::{ProgressCallback, ProgressTracker},
+    search::SearchRequest,
+};
+
+/// One time entry from an external tracker, e.g. a row of a Toggl or Clockify CSV export
+#[derive(Debug, Clone, PartialEq)]
+pub struct IntervalRecord {
+    /// Who did the work, by `ixPerson`, full name or email
+    pub person: PersonRef,
+    /// Case number, as `123`, `#123` or a description containing `#123`
+    pub case: String,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    /// Becomes the interval's title
+    pub note: Option<String>,
+}
+
+/// Why a record was not imported
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("Interval ends at {end}, not after its start at {start}")]
+    InvalidRange {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+    #[error("Interval is {0}, longer than allowed")]
+    TooLong(Hours),
+    #[error("No case number in {0:?}")]
+    UnknownCase(String),
+    #[error("Case {0} does not exist")]
+    CaseNotFound(u64),
+    #[error("Person {0} not found")]
+    PersonNotFound(String),
+    #[error("Overlaps record {0} of the same import")]
+    OverlapsRecord(usize),
+    #[error("Overlaps existing interval {0}")]
+    OverlapsInterval(u32),
+    #[error(transparent)]
+    Api(#[from] ResponseError),
+}
+
+/// What happened to one record
+#[derive(Debug)]
+pub struct ImportResult {
+    /// Position of the record in the input
+    pub index: usize,
+    /// ID of the created interval; `None` on a dry run
+    pub result: Result<Option<u32>, ImportError>,
+}
+
+#[derive(Debug)]
+pub struct ImportReport {
+    /// One result per record, in input order
+    pub results: Vec<ImportResult>,
+}
+
+impl ImportReport {
+    pub fn succeeded(&self) -> usize {
+        self.results
+            .iter()
+            .filter(|result| result.result.is_ok())
+            .count()
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &ImportResult> {
+        self.results.iter().filter(|result| result.result.is_err())
+    }
+}
+
+/// The case number in `text`: the first `#123`, or the whole text if it is a number
+pub fn case_number(text: &str) -> Option<u64> {
+    let text = text.trim();
+    if let Ok(number) = text.parse() {
+        return Some(number);
+    }
+    text.match_indices('#').find_map(|(at, _)| {
+        let digits: String = text[at + 1..]
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect();
+        digits.parse().ok()
+    })
+}
+
+/// A record that passed validation and resolution
+#[derive(Debug, Clone, Copy)]
+struct Resolved {
+    index: usize,
+    person_id: u32,
+    case_id: u64,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+}
+
+impl Resolved {
+    fn overlaps(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> bool {
+        self.start < end && start < self.end
+    }
+}
+
+/// Records that overlap an earlier record of the same person, with the record they overlap
+fn overlapping_records(records: &[Resolved]) -> HashMap<usize, usize> {
+    let mut by_person: HashMap<u32, Vec<&Resolved>> = HashMap::new();
+    for record in records {
+        by_person.entry(record.person_id).or_default().push(record);
+    }
+    let mut overlaps = HashMap::new();
+    for mut records in by_person.into_values() {
+        records.sort_by_key(|record| (record.start, record.index));
+        let mut kept: Option<&Resolved> = None;
+        for record in records {
+            match kept {
+                Some(previous) if record.overlaps(previous.start, previous.end) => {
+                    overlaps.insert(record.index, previous.index);
+                }
+                _ => kept = Some(record),
+            }
+        }
+    }
+    overlaps
+}
+
+/// Create time intervals from another tracker's entries. Each record is validated,
+/// its person and case resolved, and checked against the other records and the
+/// person's existing intervals before anything is created. Intervals are created
+/// one at a time through the client's rate limiter; a bad record doesn't stop the rest.
+#[derive(Debug, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct IntervalImportRequest {
+    /// Entries to import (required)
+    #[builder(into)]
+    records: Vec<IntervalRecord>,
+    /// Longest interval accepted
+    #[builder(default = Hours::new(12.0))]
+    max_length: Hours,
+    /// Validate and resolve everything, but don't create intervals
+    #[builder(default)]
+    dry_run: bool,
+    /// Called after each interval is created
+    #[builder(into)]
+    on_progress: Option<ProgressCallback>,
+    /// API instance
+    client: FogBugzClient,
+}
+
+impl IntervalImportRequest {
+    /// Validate the records, then create an interval for every one that passed
+    pub async fn send(&self) -> Result<ImportReport, ResponseError> {
+        let mut results: Vec<Option<Result<Option<u32>, ImportError>>> =
+            (0..self.records.len()).map(|_| None).collect();
+        let mut reject = |index: usize, error: ImportError| results[index] = Some(Err(error));
+
+        let people = if self
+            .records
+            .iter()
+            .any(|record| matches!(record.person, PersonRef::Name(_)))
+        {
+            self.client.list_people().await?
+        } else {
+            Vec::new()
+        };
+        let case_ids: Vec<u64> = self
+            .records
+            .iter()
+            .filter_map(|record| case_number(&record.case))
+            .collect();
+        let existing_cases: HashSet<u64> = if case_ids.is_empty() {
+            HashSet::new()
+        } else {
+            let response = SearchRequest::for_cases(&self.client, &case_ids)
+                .with_cols(&[Column::CaseId])
+                .send()
+                .await?;
+            response["data"]["cases"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|case| case["ixBug"].as_u64())
+                .collect()
+        };
+
+        let mut resolved = Vec::new();
+        for (index, record) in self.records.iter().enumerate() {
+            if record.end <= record.start {
+                reject(
+                    index,
+                    ImportError::InvalidRange {
+                        start: record.start,
+                        end: record.end,
+                    },
+                );
+                continue;
+            }
+            let length = Hours::between(record.start, record.end);
+            if length > self.max_length {
+                reject(index, ImportError::TooLong(length));
+                continue;
+            }
+            let person_id = match &record.person {
+                PersonRef::Id(id) => *id,
+                PersonRef::Name(name) => match find_person(&people, name) {
+                    Some(person) => person.id,
+                    None => {
+                        reject(index, ImportError::PersonNotFound(name.clone()));
+                        continue;
+                    }
+                },
+            };
+            let Some(case_id) = case_number(&record.case) else {
+                reject(index, ImportError::UnknownCase(record.case.clone()));
+                continue;
+            };
+            if !existing_cases.contains(&case_id) {
+                reject(index, ImportError::CaseNotFound(case_id));
+                continue;
+            }
+            resolved.push(Resolved {
+                index,
+                person_id,
+                case_id,
+                start: record.start,
+                end: record.end,
+            });
+        }
+
+        let overlaps = overlapping_records(&resolved);
+        resolved.retain(|record| match overlaps.get(&record.index) {
+            Some(&other) => {
+                reject(record.index, ImportError::OverlapsRecord(other));
+                false
+            }
+            None => true,
+        });
+
+        let person_ids: HashSet<u32> = resolved.iter().map(|record| record.person_id).collect();
+        for person_id in person_ids {
+            let records = resolved
+                .iter()
+                .filter(|record| record.person_id == person_id);
+            let from = records.clone().map(|record| record.start).min();
+            let to = records.map(|record| record.end).max();
+            let existing = self
+                .client
+                .list_time_intervals(Some(person_id), from, to)
+                .await?;
+            resolved.retain(|record| {
+                let overlapping = existing.iter().find(|interval| {
+                    record.person_id == interval.person_id
+                        && !interval.is_deleted
+                        && record.overlaps(interval.start_time, interval.end_time)
+                });
+                match overlapping {
+                    Some(interval) => {
+                        reject(record.index, ImportError::OverlapsInterval(interval.id));
+                        false
+                    }
+                    None => true,
+                }
+            });
+        }
+
+        let mut tracker = ProgressTracker::new(
+            self.on_progress.as_ref(),
+            resolved.len(),
+            self.client.min_request_interval(),
+        );
+        for record in resolved {
+            let result = if self.dry_run {
+                Ok(None)
+            } else {
+                self.create(&record).await
+            };
+            results[record.index] = Some(result);
+            tracker.advance(format_args!("case {}", record.case_id));
+        }
+
+        Ok(ImportReport {
+            results: results
+                .into_iter()
+                .enumerate()
+                .map(|(index, result)| ImportResult {
+                    index,
+                    result: result.unwrap_or(Ok(None)),
+                })
+                .collect(),
+        })
+    }
+
+    async fn create(&self, record: &Resolved) -> Result<Option<u32>, ImportError> {
+        let case_id =
+            u32::try_from(record.case_id).map_err(|_| ImportError::CaseNotFound(record.case_id))?;
+        let response = self
+            .client
+            .new_interval()
+            .case_id(case_id)
+            .start_time(record.start)
+            .end_time(record.end)
+            .maybe_title(self.records[record.index].note.clone())
+            .person_id(record.person_id)
+            .build()
+            .send()
+            .await?;
+        Ok(response["data"]["interval"]["ixInterval"]
+            .as_u64()
+            .and_then(|id| u32::try_from(id).ok()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_case_number() {
+        assert_eq!(case_number(" 123 "), Some(123));
+        assert_eq!(case_number("Fix login (#4567) and tests"), Some(4567));
+        assert_eq!(case_number("# notes, see #89"), Some(89));
+        assert_eq!(case_number("Weekly meeting"), None);
+    }
+
+    #[test]
+    fn test_overlapping_records() {
+        let at = |hour: u32| -> DateTime<Utc> {
+            format!("2024-03-04T{hour:02}:00:00Z").parse().unwrap()
+        };
+        let record = |index, person_id, start, end| Resolved {
+            index,
+            person_id,
+            case_id: 1,
+            start: at(start),
+            end: at(end),
+        };
+        let records = [
+            record(0, 1, 9, 11),
+            record(1, 1, 10, 12),
+            record(2, 1, 11, 12),
+            record(3, 2, 10, 12),
+        ];
+        let overlaps = overlapping_records(&records);
+        assert_eq!(overlaps, HashMap::from([(1, 0)]));
+    }
+}
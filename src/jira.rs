@@ -0,0 +1,275 @@
+use std::collections::BTreeMap;
+
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    FogBugzClient, ResponseError,
+    case_details::{Attachment, CaseDetails},
+    interop::{IssueModel, IssueStatus},
+};
+
+/// A file in the JSON importer's format
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JiraImport {
+    pub users: Vec<JiraUser>,
+    pub projects: Vec<JiraProject>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JiraUser {
+    pub name: String,
+    #[serde(rename = "fullname")]
+    pub full_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JiraProject {
+    pub name: String,
+    pub key: String,
+    pub issues: Vec<JiraIssue>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JiraIssue {
+    /// FogBugz case ID
+    pub external_id: Option<String>,
+    pub summary: String,
+    pub description: String,
+    pub issue_type: String,
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reporter: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<DateTime<Utc>>,
+    pub labels: Vec<String>,
+    pub comments: Vec<JiraComment>,
+    pub attachments: Vec<JiraAttachment>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JiraComment {
+    pub body: String,
+    pub author: String,
+    pub created: DateTime<Utc>,
+}
+
+/// An attachment the importer downloads from `uri`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JiraAttachment {
+    pub name: String,
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attacher: Option<String>,
+}
+
+/// Jira user names can't contain spaces, so `Jane Doe` becomes `jane.doe`
+pub fn jira_user_name(full_name: &str) -> String {
+    full_name
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(".")
+        .to_lowercase()
+}
+
+/// Jira labels can't contain spaces either
+fn jira_label(label: &str) -> String {
+    label.split_whitespace().collect::<Vec<_>>().join("_")
+}
+
+fn issue_type(labels: &[String]) -> &'static str {
+    if labels.iter().any(|label| label == "bug") {
+        "Bug"
+    } else if labels.iter().any(|label| label == "feature") {
+        "New Feature"
+    } else {
+        "Task"
+    }
+}
+
+impl From<&IssueModel> for JiraIssue {
+    /// Link URLs become attachment URIs as they are, so make them absolute first
+    fn from(issue: &IssueModel) -> Self {
+        JiraIssue {
+            external_id: issue.source_id.clone(),
+            summary: issue.title.clone(),
+            description: issue.body.clone(),
+            issue_type: issue_type(&issue.labels).to_string(),
+            status: match issue.status {
+                IssueStatus::Open => "Open",
+                IssueStatus::Closed => "Closed",
+            }
+            .to_string(),
+            reporter: issue.reporter.as_deref().map(jira_user_name),
+            assignee: issue.assignee.as_deref().map(jira_user_name),
+            created: issue.created,
+            labels: issue.labels.iter().map(|label| jira_label(label)).collect(),
+            comments: issue
+                .comments
+                .iter()
+                .map(|comment| JiraComment {
+                    body: comment.body.clone(),
+                    author: jira_user_name(&comment.author),
+                    created: comment.created,
+                })
+                .collect(),
+            attachments: issue
+                .links
+                .iter()
+                .map(|link| JiraAttachment {
+                    name: link.title.clone(),
+                    uri: link.url.clone(),
+                    attacher: issue.reporter.as_deref().map(jira_user_name),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl JiraImport {
+    /// One project holding `issues`, with every person they mention as a user
+    pub fn new(name: impl Into<String>, key: impl Into<String>, issues: &[IssueModel]) -> Self {
+        let mut users = BTreeMap::new();
+        for issue in issues {
+            let names = issue
+                .reporter
+                .iter()
+                .chain(&issue.assignee)
+                .chain(issue.comments.iter().map(|comment| &comment.author));
+            for full_name in names {
+                users
+                    .entry(jira_user_name(full_name))
+                    .or_insert_with(|| full_name.clone());
+            }
+        }
+        JiraImport {
+            users: users
+                .into_iter()
+                .map(|(name, full_name)| JiraUser { name, full_name })
+                .collect(),
+            projects: vec![JiraProject {
+                name: name.into(),
+                key: key.into(),
+                issues: issues.iter().map(JiraIssue::from).collect(),
+            }],
+        }
+    }
+}
+
+/// Export the cases matching a search as a Jira Cloud JSON import
+#[derive(Debug, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct JiraExportRequest {
+    /// Search query selecting the cases (required)
+    #[builder(into)]
+    query: String,
+    /// Jira project name (required)
+    #[builder(into)]
+    project_name: String,
+    /// Jira project key, e.g. `WID` (required)
+    #[builder(into)]
+    project_key: String,
+    /// Put the client's token in attachment URIs so the importer can download them
+    /// without logging on. Treat the export as a secret if set.
+    #[builder(default)]
+    authenticated_urls: bool,
+    /// API instance
+    client: FogBugzClient,
+}
+
+impl JiraExportRequest {
+    pub async fn send(&self) -> Result<JiraImport, ResponseError> {
+        let cases = self
+            .client
+            .search()
+            .query(self.query.clone())
+            .build()
+            .require_columns_for::<CaseDetails>()
+            .send_all()
+            .await?;
+        let mut issues = Vec::with_capacity(cases.len());
+        for mut case in cases {
+            if let Value::Array(events) = &mut case["events"] {
+                events.retain(|event| matches!(event, Value::Object(_)));
+            }
+            let case: CaseDetails = ResponseError::deserialize_cases(case)?;
+            let mut issue = IssueModel::from(case);
+            for link in &mut issue.links {
+                let attachment = Attachment {
+                    file_name: link.title.clone(),
+                    url: link.url.clone(),
+                };
+                let url = if self.authenticated_urls {
+                    self.client.attachment_url(&attachment)?
+                } else {
+                    self.client.attachment_site_url(&attachment)?
+                };
+                link.url = url.to_string();
+            }
+            issues.push(issue);
+        }
+        Ok(JiraImport::new(
+            self.project_name.clone(),
+            self.project_key.clone(),
+            &issues,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interop::{IssueComment, IssueLink};
+
+    #[test]
+    fn test_jira_import() {
+        let created: DateTime<Utc> = "2024-03-04T10:30:00Z".parse().unwrap();
+        let issue = IssueModel {
+            source_id: Some("42".to_string()),
+            project: Some("Widgets".to_string()),
+            title: "Export fails".to_string(),
+            body: "Steps to reproduce".to_string(),
+            comments: vec![IssueComment {
+                author: "John  Smith".to_string(),
+                created,
+                body: "Looking".to_string(),
+            }],
+            labels: vec!["bug".to_string(), "area:Data Export".to_string()],
+            reporter: Some("Jane Doe".to_string()),
+            created: Some(created),
+            assignee: Some("Jane Doe".to_string()),
+            status: IssueStatus::Closed,
+            links: vec![IssueLink {
+                title: "log.txt".to_string(),
+                url: "https://example.com/default.asp?pg=pgDownload".to_string(),
+            }],
+        };
+        let import = JiraImport::new("Widgets", "WID", &[issue]);
+        assert_eq!(
+            import.users,
+            vec![
+                JiraUser {
+                    name: "jane.doe".to_string(),
+                    full_name: "Jane Doe".to_string()
+                },
+                JiraUser {
+                    name: "john.smith".to_string(),
+                    full_name: "John  Smith".to_string()
+                },
+            ]
+        );
+        let json = serde_json::to_value(&import).unwrap();
+        let issue = &json["projects"][0]["issues"][0];
+        assert_eq!(issue["externalId"], "42");
+        assert_eq!(issue["issueType"], "Bug");
+        assert_eq!(issue["status"], "Closed");
+        assert_eq!(issue["labels"][1], "area:Data_Export");
+        assert_eq!(issue["comments"][0]["author"], "john.smith");
+        assert_eq!(issue["attachments"][0]["attacher"], "jane.doe");
+    }
+}
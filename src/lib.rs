@@ -1,19 +1,81 @@
+pub mod admin;
 pub mod api_client;
+pub mod archive;
+pub mod assignment_watch;
+pub mod attachments;
+pub mod auth;
+pub mod batch;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+pub mod budget;
+pub mod campaign;
 pub mod case_details;
+pub mod case_email;
+pub mod case_handle;
 pub mod case_management;
+pub mod case_tree;
+pub mod change_feed;
+pub mod changes;
+pub mod custom_fields;
 pub mod date;
+pub mod discussions;
+pub mod email;
+#[cfg(feature = "eml")]
+pub mod eml;
 pub mod enums;
+pub mod estimates;
+pub mod event_search;
 pub mod filter;
+pub mod filter_parse;
+pub mod filter_scope;
+pub mod hours;
+pub mod hours_budget;
 pub mod hours_report;
+pub mod hours_tree;
+pub mod interceptor;
+pub mod interop;
+pub mod interval_import;
+pub mod jira;
 pub mod list_cases;
 pub mod list_intervals;
+pub mod meetings;
+pub mod mentions;
+pub mod metadata_cache;
+#[cfg(test)]
+mod mock_server;
+pub mod ndjson;
 pub mod organization;
+pub mod paging;
+pub mod policy;
+pub mod preflight;
+pub mod privacy;
+pub mod progress;
 pub mod query;
+pub mod rate_limit;
+pub mod recovery;
+pub mod reports;
+pub mod resolver;
+pub mod retry;
+pub mod round_robin;
+pub mod scout;
 pub mod search;
+pub mod search_case;
+pub mod shutdown;
+pub mod signing;
+pub mod similar;
+pub mod state;
+pub mod subscriptions;
+pub mod template;
 pub mod time_tracking;
+pub mod timesheet;
+pub mod timezones;
+pub mod watch;
+pub mod wiki;
+pub mod working_schedule;
+#[cfg(feature = "xlsx")]
+pub mod xlsx;
 
 use core::fmt;
-#[cfg(feature = "leaky-bucket")]
 use std::sync::Arc;
 
 use bon::Builder;
@@ -25,13 +87,43 @@ use thiserror::Error;
 pub struct FogBugzClient {
     #[builder(into)]
     pub url: String,
+    /// Key the client was built with; `current_api_key` reflects `set_api_key`
     #[builder(into)]
     pub api_key: String,
+    /// Requests per second and burst, enforced across all clones of the client
+    #[builder(with = |limit: rate_limit::RateLimit| Arc::new(rate_limit::TokenBucket::new(limit)))]
+    rate_limit: Option<Arc<rate_limit::TokenBucket>>,
+    /// Externally built limiter, for sharing one between clients or finer control.
+    /// Applies in addition to `rate_limit`.
     #[cfg(feature = "leaky-bucket")]
     #[builder(into)]
     limiter: Option<Arc<RateLimiter>>,
     #[builder(default)]
     pub client: reqwest::Client,
+    /// Reject every command that could modify the tracker
+    #[builder(default)]
+    read_only: bool,
+    /// Allowlist or denylist of command names
+    #[builder(default)]
+    policy: policy::CommandPolicy,
+    /// Retry transient failures with exponential backoff
+    retry_policy: Option<retry::RetryPolicy>,
+    /// Keep projects, people, statuses and other org metadata for this long,
+    /// shared by all clones of the client; see `invalidate_metadata_cache`
+    #[builder(name = metadata_cache_ttl, with = |ttl: std::time::Duration| Arc::new(metadata_cache::MetadataCache::new(ttl)))]
+    metadata_cache: Option<Arc<metadata_cache::MetadataCache>>,
+    /// Called with the payload and headers of every request right before it is sent
+    #[builder(into)]
+    request_hook: Option<signing::RequestHook>,
+    /// Middleware run around every command, added with `with_interceptor`
+    #[builder(skip)]
+    interceptors: interceptor::Interceptors,
+    /// API key set with `set_api_key`, shared by all clones of the client
+    #[builder(skip)]
+    rotated_api_key: auth::SharedApiKey,
+    /// Session from `logon`, shared by all clones of the client
+    #[builder(skip)]
+    session: auth::SharedSession,
 }
 
 impl fmt::Debug for FogBugzClient {
@@ -39,6 +131,19 @@ impl fmt::Debug for FogBugzClient {
         f.debug_struct("FogbugzClient")
             .field("url", &self.url)
             .field("api_key", &"********")
+            .field(
+                "rate_limit",
+                &self.rate_limit.as_ref().map(|bucket| bucket.limit()),
+            )
+            .field("read_only", &self.read_only)
+            .field("policy", &self.policy)
+            .field("retry_policy", &self.retry_policy)
+            .field(
+                "metadata_cache_ttl",
+                &self.metadata_cache.as_ref().map(|cache| cache.ttl()),
+            )
+            .field("request_hook", &self.request_hook)
+            .field("interceptors", &self.interceptors)
             .finish()
     }
 }
@@ -59,9 +164,18 @@ impl FogBugzClient {
         Self {
             url: url.into(),
             api_key: api_key.into(),
+            rate_limit: None,
             #[cfg(feature = "leaky-bucket")]
             limiter: None,
             client: reqwest::Client::default(),
+            read_only: false,
+            policy: policy::CommandPolicy::AllowAll,
+            retry_policy: None,
+            metadata_cache: None,
+            request_hook: None,
+            interceptors: Default::default(),
+            rotated_api_key: Default::default(),
+            session: Default::default(),
         }
     }
     pub fn new_from_env() -> Self {
@@ -71,9 +185,35 @@ impl FogBugzClient {
         Self {
             url,
             api_key,
+            rate_limit: None,
             #[cfg(feature = "leaky-bucket")]
             limiter: None,
             client: reqwest::Client::default(),
+            read_only: false,
+            policy: policy::CommandPolicy::AllowAll,
+            retry_policy: None,
+            metadata_cache: None,
+            request_hook: None,
+            interceptors: Default::default(),
+            rotated_api_key: Default::default(),
+            session: Default::default(),
+        }
+    }
+    /// Copy of this client that rejects mutating commands with `ResponseError::ReadOnly`
+    pub fn read_only(&self) -> Self {
+        Self {
+            read_only: true,
+            ..self.clone()
+        }
+    }
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+    /// Copy of this client restricted to the commands `policy` permits
+    pub fn with_policy(&self, policy: policy::CommandPolicy) -> Self {
+        Self {
+            policy,
+            ..self.clone()
         }
     }
     pub fn list_cases(
@@ -89,6 +229,48 @@ impl FogBugzClient {
     > {
         case_details::CaseDetailsRequest::builder().client(self.clone())
     }
+    /// Poll for cases as they change
+    pub fn change_feed(
+        &self,
+    ) -> change_feed::ChangeFeedBuilder<change_feed::change_feed_builder::SetClient> {
+        change_feed::ChangeFeed::builder().client(self.clone())
+    }
+
+    /// Poll a search for cases whose assignee changed
+    pub fn watch_assignments(
+        &self,
+        query: impl Into<String>,
+    ) -> assignment_watch::AssignmentWatcherBuilder<
+        assignment_watch::assignment_watcher_builder::SetQuery<
+            assignment_watch::assignment_watcher_builder::SetClient,
+        >,
+    > {
+        assignment_watch::AssignmentWatcher::builder()
+            .client(self.clone())
+            .query(query)
+    }
+
+    /// Export the cases matching a search as a Jira Cloud JSON import
+    pub fn jira_export(
+        &self,
+    ) -> jira::JiraExportRequestBuilder<jira::jira_export_request_builder::SetClient> {
+        jira::JiraExportRequest::builder().client(self.clone())
+    }
+    /// Events of a case without the rest of the case
+    pub fn case_events(
+        &self,
+    ) -> case_details::EventsRequestBuilder<case_details::events_request_builder::SetClient> {
+        case_details::EventsRequest::builder().client(self.clone())
+    }
+    /// Handle for running common operations on one case
+    pub fn case(&self, case_id: u64) -> case_handle::CaseHandle {
+        case_handle::CaseHandle::new(self, case_id)
+    }
+    /// Use a saved filter (`sFilter`) as a source of cases and hours
+    pub fn filter_scope(&self, filter: impl Into<String>) -> filter_scope::FilterScope {
+        filter_scope::FilterScope::new(self, filter)
+    }
+
     pub fn search(
         &self,
     ) -> search::SearchRequestBuilder<search::search_request_builder::SetClient> {
@@ -125,6 +307,13 @@ impl FogBugzClient {
         case_management::NewCaseRequest::builder().client(self.clone())
     }
 
+    /// Submit a crash report BugzScout-style, deduplicated by its description
+    pub fn scout_report(
+        &self,
+    ) -> scout::ScoutReportBuilder<scout::scout_report_builder::SetClient> {
+        scout::ScoutReport::builder().client(self.clone())
+    }
+
     pub fn edit_case(
         &self,
     ) -> case_management::EditCaseRequestBuilder<
@@ -149,6 +338,14 @@ impl FogBugzClient {
         case_management::ResolveCaseRequest::builder().client(self.clone())
     }
 
+    pub fn resolve_as_duplicate(
+        &self,
+    ) -> case_management::ResolveAsDuplicateRequestBuilder<
+        case_management::resolve_as_duplicate_request_builder::SetClient,
+    > {
+        case_management::ResolveAsDuplicateRequest::builder().client(self.clone())
+    }
+
     pub fn reactivate_case(
         &self,
     ) -> case_management::ReactivateCaseRequestBuilder<
@@ -157,6 +354,14 @@ impl FogBugzClient {
         case_management::ReactivateCaseRequest::builder().client(self.clone())
     }
 
+    pub fn reopen_case(
+        &self,
+    ) -> case_management::ReopenCaseRequestBuilder<
+        case_management::reopen_case_request_builder::SetClient,
+    > {
+        case_management::ReopenCaseRequest::builder().client(self.clone())
+    }
+
     pub fn close_case(
         &self,
     ) -> case_management::CloseCaseRequestBuilder<
@@ -165,6 +370,256 @@ impl FogBugzClient {
         case_management::CloseCaseRequest::builder().client(self.clone())
     }
 
+    /// Apply one operation to many cases with bounded concurrency
+    pub fn batch_edit(
+        &self,
+    ) -> batch::BatchEditRequestBuilder<batch::batch_edit_request_builder::SetClient> {
+        batch::BatchEditRequest::builder().client(self.clone())
+    }
+
+    pub fn reply(
+        &self,
+    ) -> email::ReplyEmailRequestBuilder<email::reply_email_request_builder::SetClient> {
+        email::ReplyEmailRequest::builder().client(self.clone())
+    }
+
+    pub fn forward(
+        &self,
+    ) -> email::ForwardEmailRequestBuilder<email::forward_email_request_builder::SetClient> {
+        email::ForwardEmailRequest::builder().client(self.clone())
+    }
+
+    pub fn new_email(
+        &self,
+    ) -> email::NewEmailRequestBuilder<email::new_email_request_builder::SetClient> {
+        email::NewEmailRequest::builder().client(self.clone())
+    }
+
+    pub fn comment_campaign(
+        &self,
+    ) -> campaign::CommentCampaignBuilder<campaign::comment_campaign_builder::SetClient> {
+        campaign::CommentCampaign::builder().client(self.clone())
+    }
+
+    /// Redact or flag a person's email address across all cases (GDPR erasure)
+    pub fn scrub_person_data(
+        &self,
+        person_id: u32,
+    ) -> privacy::ScrubPersonDataBuilder<
+        privacy::scrub_person_data_builder::SetPersonId<
+            privacy::scrub_person_data_builder::SetClient,
+        >,
+    > {
+        privacy::ScrubPersonData::builder()
+            .client(self.clone())
+            .person_id(person_id)
+    }
+
+    // Administration Operations
+    pub fn new_project(
+        &self,
+        name: impl Into<String>,
+    ) -> admin::NewProjectRequestBuilder<
+        admin::new_project_request_builder::SetName<admin::new_project_request_builder::SetClient>,
+    > {
+        admin::NewProjectRequest::builder()
+            .client(self.clone())
+            .name(name)
+    }
+
+    pub fn new_area(
+        &self,
+        project_id: u32,
+        name: impl Into<String>,
+    ) -> admin::NewAreaRequestBuilder<
+        admin::new_area_request_builder::SetName<
+            admin::new_area_request_builder::SetProjectId<
+                admin::new_area_request_builder::SetClient,
+            >,
+        >,
+    > {
+        admin::NewAreaRequest::builder()
+            .client(self.clone())
+            .project_id(project_id)
+            .name(name)
+    }
+
+    pub fn new_person(
+        &self,
+        email: impl Into<String>,
+        full_name: impl Into<String>,
+    ) -> admin::NewPersonRequestBuilder<
+        admin::new_person_request_builder::SetFullName<
+            admin::new_person_request_builder::SetEmail<
+                admin::new_person_request_builder::SetClient,
+            >,
+        >,
+    > {
+        admin::NewPersonRequest::builder()
+            .client(self.clone())
+            .email(email)
+            .full_name(full_name)
+    }
+
+    pub fn edit_person(
+        &self,
+        person_id: u32,
+    ) -> admin::EditPersonRequestBuilder<
+        admin::edit_person_request_builder::SetPersonId<
+            admin::edit_person_request_builder::SetClient,
+        >,
+    > {
+        admin::EditPersonRequest::builder()
+            .client(self.clone())
+            .person_id(person_id)
+    }
+
+    pub fn delete_person(
+        &self,
+        person_id: u32,
+    ) -> admin::DeletePersonRequestBuilder<
+        admin::delete_person_request_builder::SetPersonId<
+            admin::delete_person_request_builder::SetClient,
+        >,
+    > {
+        admin::DeletePersonRequest::builder()
+            .client(self.clone())
+            .person_id(person_id)
+    }
+
+    // Notification Operations
+    pub fn subscribe(
+        &self,
+        target: subscriptions::NotificationTarget,
+    ) -> subscriptions::SubscribeRequestBuilder<
+        subscriptions::subscribe_request_builder::SetTarget<
+            subscriptions::subscribe_request_builder::SetClient,
+        >,
+    > {
+        subscriptions::SubscribeRequest::builder()
+            .client(self.clone())
+            .target(target)
+    }
+
+    pub fn unsubscribe(
+        &self,
+        target: subscriptions::NotificationTarget,
+    ) -> subscriptions::UnsubscribeRequestBuilder<
+        subscriptions::unsubscribe_request_builder::SetTarget<
+            subscriptions::unsubscribe_request_builder::SetClient,
+        >,
+    > {
+        subscriptions::UnsubscribeRequest::builder()
+            .client(self.clone())
+            .target(target)
+    }
+
+    /// Mark a case or article as read, e.g. for a service account's inbox
+    pub fn mark_viewed(
+        &self,
+        target: subscriptions::NotificationTarget,
+    ) -> subscriptions::MarkViewedRequestBuilder<
+        subscriptions::mark_viewed_request_builder::SetTarget<
+            subscriptions::mark_viewed_request_builder::SetClient,
+        >,
+    > {
+        subscriptions::MarkViewedRequest::builder()
+            .client(self.clone())
+            .target(target)
+    }
+
+    // Wiki Operations
+    pub fn list_wikis(
+        &self,
+    ) -> wiki::ListWikisRequestBuilder<wiki::list_wikis_request_builder::SetClient> {
+        wiki::ListWikisRequest::builder().client(self.clone())
+    }
+
+    pub fn list_articles(
+        &self,
+        wiki_id: u32,
+    ) -> wiki::ListArticlesRequestBuilder<
+        wiki::list_articles_request_builder::SetWikiId<
+            wiki::list_articles_request_builder::SetClient,
+        >,
+    > {
+        wiki::ListArticlesRequest::builder()
+            .client(self.clone())
+            .wiki_id(wiki_id)
+    }
+
+    pub fn view_article(
+        &self,
+        article_id: u32,
+    ) -> wiki::ViewArticleRequestBuilder<
+        wiki::view_article_request_builder::SetArticleId<
+            wiki::view_article_request_builder::SetClient,
+        >,
+    > {
+        wiki::ViewArticleRequest::builder()
+            .client(self.clone())
+            .article_id(article_id)
+    }
+
+    pub fn new_article(
+        &self,
+        wiki_id: u32,
+    ) -> wiki::NewArticleRequestBuilder<
+        wiki::new_article_request_builder::SetWikiId<wiki::new_article_request_builder::SetClient>,
+    > {
+        wiki::NewArticleRequest::builder()
+            .client(self.clone())
+            .wiki_id(wiki_id)
+    }
+
+    pub fn edit_article(
+        &self,
+        article_id: u32,
+    ) -> wiki::EditArticleRequestBuilder<
+        wiki::edit_article_request_builder::SetArticleId<
+            wiki::edit_article_request_builder::SetClient,
+        >,
+    > {
+        wiki::EditArticleRequest::builder()
+            .client(self.clone())
+            .article_id(article_id)
+    }
+
+    // Discussion Group Operations
+    pub fn list_discuss_groups(
+        &self,
+    ) -> discussions::ListDiscussGroupsRequestBuilder<
+        discussions::list_discuss_groups_request_builder::SetClient,
+    > {
+        discussions::ListDiscussGroupsRequest::builder().client(self.clone())
+    }
+
+    pub fn list_discussion(
+        &self,
+        group_id: u32,
+    ) -> discussions::ListDiscussionRequestBuilder<
+        discussions::list_discussion_request_builder::SetGroupId<
+            discussions::list_discussion_request_builder::SetClient,
+        >,
+    > {
+        discussions::ListDiscussionRequest::builder()
+            .client(self.clone())
+            .group_id(group_id)
+    }
+
+    pub fn list_discuss_topic(
+        &self,
+        topic_id: u32,
+    ) -> discussions::ListDiscussTopicRequestBuilder<
+        discussions::list_discuss_topic_request_builder::SetTopicId<
+            discussions::list_discuss_topic_request_builder::SetClient,
+        >,
+    > {
+        discussions::ListDiscussTopicRequest::builder()
+            .client(self.clone())
+            .topic_id(topic_id)
+    }
+
     // Time Tracking Operations
     pub fn start_work(
         &self,
@@ -188,6 +643,81 @@ impl FogBugzClient {
         time_tracking::NewIntervalRequest::builder().client(self.clone())
     }
 
+    pub fn edit_interval(
+        &self,
+    ) -> time_tracking::EditIntervalRequestBuilder<
+        time_tracking::edit_interval_request_builder::SetClient,
+    > {
+        time_tracking::EditIntervalRequest::builder().client(self.clone())
+    }
+
+    pub fn delete_interval(
+        &self,
+    ) -> time_tracking::DeleteIntervalRequestBuilder<
+        time_tracking::delete_interval_request_builder::SetClient,
+    > {
+        time_tracking::DeleteIntervalRequest::builder().client(self.clone())
+    }
+
+    /// Suggest cases to log a person's missing hours on a day against
+    pub fn timesheet_suggestions(
+        &self,
+        person_id: u32,
+        date: chrono::NaiveDate,
+    ) -> timesheet::TimesheetAutofillRequestBuilder<
+        timesheet::timesheet_autofill_request_builder::SetDate<
+            timesheet::timesheet_autofill_request_builder::SetPersonId<
+                timesheet::timesheet_autofill_request_builder::SetClient,
+            >,
+        >,
+    > {
+        timesheet::TimesheetAutofillRequest::builder()
+            .client(self.clone())
+            .person_id(person_id)
+            .date(date)
+    }
+
+    /// Log calendar events on a meetings case, skipping time already logged
+    pub fn fill_meetings(
+        &self,
+        events: impl Into<Vec<meetings::CalendarEvent>>,
+        meetings_case_id: u32,
+    ) -> meetings::MeetingFillRequestBuilder<
+        meetings::meeting_fill_request_builder::SetMeetingsCaseId<
+            meetings::meeting_fill_request_builder::SetEvents<
+                meetings::meeting_fill_request_builder::SetClient,
+            >,
+        >,
+    > {
+        meetings::MeetingFillRequest::builder()
+            .client(self.clone())
+            .events(events)
+            .meetings_case_id(meetings_case_id)
+    }
+
+    /// Create time intervals from entries exported by another time tracker
+    pub fn import_intervals(
+        &self,
+        records: impl Into<Vec<interval_import::IntervalRecord>>,
+    ) -> interval_import::IntervalImportRequestBuilder<
+        interval_import::interval_import_request_builder::SetRecords<
+            interval_import::interval_import_request_builder::SetClient,
+        >,
+    > {
+        interval_import::IntervalImportRequest::builder()
+            .client(self.clone())
+            .records(records)
+    }
+
+    /// Flag people whose intervals look shifted by a time zone import bug
+    pub fn interval_timezone_check(
+        &self,
+    ) -> timezones::IntervalTimezoneCheckBuilder<
+        timezones::interval_timezone_check_builder::SetClient,
+    > {
+        timezones::IntervalTimezoneCheck::builder().client(self.clone())
+    }
+
     // Hours Reporting Operations
     pub fn hours_remaining_report(
         &self,
@@ -213,7 +743,100 @@ pub enum ResponseError {
     #[error(transparent)]
     UrlError(#[from] url::ParseError),
     #[error("FogBugz error: {0}")]
-    FogbugzError(serde_json::Value),
+    FogbugzError(#[from] api_client::FogBugzApiError),
     #[error(transparent)]
     JsonError(#[from] serde_json::Error),
+    #[error("Person not found: {0}")]
+    PersonNotFound(String),
+    #[error("Project not found: {0}")]
+    ProjectNotFound(String),
+    #[error("Milestone not found: {0}")]
+    MilestoneNotFound(String),
+    #[error("Area not found: {0}")]
+    AreaNotFound(String),
+    #[error("No duplicate status for category {0}")]
+    NoDuplicateStatus(u32),
+    #[error(transparent)]
+    StateStoreError(#[from] state::StateStoreError),
+    #[error("Column {0} is required but was not returned; add it to the requested cols")]
+    MissingColumn(enums::Column),
+    #[error("Column {0} was requested but the target type has no field for it")]
+    UnexpectedColumn(String),
+    #[error("Command {0} is not allowed on a read-only client")]
+    ReadOnly(String),
+    #[error("Command {0} is not allowed by the client's command policy")]
+    CommandNotAllowed(String),
+    #[error("Search query is {length} characters long, more than the {max} FogBugz accepts")]
+    TooLongQuery { length: usize, max: usize },
+    #[error(transparent)]
+    AuthError(#[from] auth::AuthError),
+    #[error("Attachment error: {0}")]
+    AttachmentError(String),
+    #[cfg(feature = "eml")]
+    #[error(transparent)]
+    EmlError(#[from] eml::EmlError),
+    #[error("Budget exceeded: more than {max} {limit}")]
+    BudgetExceeded {
+        limit: budget::BudgetLimit,
+        max: usize,
+    },
+}
+
+impl ResponseError {
+    /// Whether sending the same request again may succeed: connection failures,
+    /// timeouts, rate limiting and 5xx responses. The client's `retry_policy`
+    /// retries exactly these.
+    pub fn is_retryable(&self) -> bool {
+        retry::is_transient(self)
+    }
+
+    /// Whether the token or credentials were rejected, or the user may not
+    /// perform the action
+    pub fn is_auth_error(&self) -> bool {
+        use api_client::FogBugzApiError as Api;
+        match self {
+            Self::FogbugzError(
+                Api::LogonFailed { .. } | Api::InvalidToken { .. } | Api::PermissionDenied { .. },
+            )
+            | Self::AuthError(_) => true,
+            Self::FogbugzError(Api::ServerError { status, .. }) => matches!(status, 401 | 403),
+            Self::RequestError(e) => e.status().is_some_and(|status| {
+                status == reqwest::StatusCode::UNAUTHORIZED
+                    || status == reqwest::StatusCode::FORBIDDEN
+            }),
+            _ => false,
+        }
+    }
+
+    /// Whether the case, person, project, milestone or area doesn't exist
+    pub fn is_not_found(&self) -> bool {
+        match self {
+            Self::FogbugzError(api_client::FogBugzApiError::CaseNotFound { .. })
+            | Self::PersonNotFound(_)
+            | Self::ProjectNotFound(_)
+            | Self::MilestoneNotFound(_)
+            | Self::AreaNotFound(_) => true,
+            Self::FogbugzError(api_client::FogBugzApiError::ServerError { status, .. }) => {
+                *status == 404
+            }
+            Self::RequestError(e) => e.status() == Some(reqwest::StatusCode::NOT_FOUND),
+            _ => false,
+        }
+    }
+
+    /// Deserialize case data, reporting a missing FogBugz column as `MissingColumn`
+    /// instead of a generic serde error
+    pub(crate) fn deserialize_cases<T: serde::de::DeserializeOwned>(
+        value: serde_json::Value,
+    ) -> Result<T, ResponseError> {
+        serde_json::from_value(value).map_err(|e| {
+            let message = e.to_string();
+            message
+                .strip_prefix("missing field `")
+                .and_then(|rest| rest.split('`').next())
+                .and_then(|field| field.parse::<enums::Column>().ok())
+                .map(ResponseError::MissingColumn)
+                .unwrap_or(ResponseError::JsonError(e))
+        })
+    }
 }
@@ -1,7 +1,19 @@
+use std::fmt;
+
 use bon::Builder;
+use futures::{Stream, TryStreamExt};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
-use crate::{FogBugzClient, ResponseError, enums::Column, filter::FogBugzSearchBuilder};
+use crate::{
+    FogBugzClient, ResponseError,
+    budget::{Budget, BudgetTracker},
+    enums::{Column, RequiredColumns, add_required_columns},
+    filter::FogBugzSearchBuilder,
+    paging::{DEFAULT_PAGE_SIZE, pages},
+    search::with_stable_order,
+    search_case::SearchCase,
+};
 
 #[derive(Debug, Serialize, Builder)]
 #[builder(state_mod(vis = "pub(crate)"))]
@@ -14,6 +26,16 @@ pub struct ListCasesRequest {
     filter: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max: Option<u32>,
+    /// Offset of the first case to return
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<u32>,
+    /// Append `OrderBy:ixBug` to search queries so paged results are deterministic
+    #[serde(skip)]
+    #[builder(default)]
+    stable_order: bool,
+    /// Fail with `BudgetExceeded` instead of returning more cases than this allows
+    #[serde(skip)]
+    budget: Option<Budget>,
     #[serde(skip)]
     client: FogBugzClient,
 }
@@ -24,6 +46,12 @@ impl<S: list_cases_request_builder::State> ListCasesRequestBuilder<S> {
         self
     }
 
+    /// Add the columns `T` needs to deserialize, keeping any already requested
+    pub fn require_columns_for<T: RequiredColumns>(mut self) -> Self {
+        add_required_columns::<T>(self.cols.get_or_insert_with(Vec::new));
+        self
+    }
+
     pub fn search_filter(
         self,
         search_builder: FogBugzSearchBuilder,
@@ -44,71 +72,187 @@ pub struct Case {
     #[serde(rename = "sProject")]
     pub project: String,
     #[serde(rename = "sTitle")]
-    pub titile: String,
+    pub title: String,
+}
+
+impl RequiredColumns for Case {
+    fn required_columns() -> &'static [Column] {
+        &[
+            Column::CaseId,
+            Column::ProjectId,
+            Column::Project,
+            Column::Title,
+        ]
+    }
+}
+
+impl fmt::Display for Case {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} [{}] {}", self.case_id, self.project, self.title)
+    }
 }
 
+/// A listed case with whichever columns were requested
+pub type CaseSummary = SearchCase;
+
 impl ListCasesRequest {
-    pub async fn send(&self) -> Result<Vec<Case>, ResponseError> {
+    /// Cases with the requested columns
+    pub async fn send(&self) -> Result<Vec<CaseSummary>, ResponseError> {
+        let mut budget = BudgetTracker::new(self.budget);
+        budget.request()?;
+        let max = self
+            .budget
+            .map_or(self.max, |budget| budget.cap_max(self.max));
+        let cases = self.fetch(self.start, max, self.stable_order).await?;
+        budget.cases(cases.len())?;
+        Ok(cases)
+    }
+
+    /// Iterate over all matching cases a page at a time (`max` cases per page),
+    /// starting at `start`. Search queries are always ordered by case ID as a
+    /// tiebreaker so no case is skipped or repeated between pages.
+    pub fn paged(&self) -> impl Stream<Item = Result<Vec<CaseSummary>, ResponseError>> + '_ {
+        pages(
+            self.start.unwrap_or(0),
+            self.max.unwrap_or(DEFAULT_PAGE_SIZE),
+            self.budget,
+            move |start, max| self.fetch(Some(start), Some(max), true),
+        )
+    }
+
+    /// Fetch every matching case, following pages until the server is exhausted
+    pub async fn send_all(&self) -> Result<Vec<CaseSummary>, ResponseError> {
+        self.paged().try_concat().await
+    }
+
+    /// The FogBugz response as JSON, without typing the cases
+    pub async fn send_raw(&self) -> Result<Value, ResponseError> {
+        self.fetch_raw(self.start, self.max, self.stable_order)
+            .await
+    }
+
+    /// Requested columns, always including `ixBug`; `Case`'s columns if none were requested
+    fn columns(&self) -> Vec<String> {
+        let mut cols = match &self.cols {
+            Some(cols) => cols.clone(),
+            None => Vec::new(),
+        };
+        if cols.is_empty() {
+            add_required_columns::<Case>(&mut cols);
+        } else if !cols.iter().any(|c| c == "ixBug") {
+            cols.push("ixBug".to_string());
+        }
+        cols
+    }
+
+    async fn fetch(
+        &self,
+        start: Option<u32>,
+        max: Option<u32>,
+        stable_order: bool,
+    ) -> Result<Vec<CaseSummary>, ResponseError> {
+        let response_json = self.fetch_raw(start, max, stable_order).await?;
+        ResponseError::deserialize_cases(response_json["data"]["cases"].clone())
+    }
+
+    async fn fetch_raw(
+        &self,
+        start: Option<u32>,
+        max: Option<u32>,
+        stable_order: bool,
+    ) -> Result<Value, ResponseError> {
         // Check if this is a search filter (FogBugzSearchBuilder) or a saved filter ID
         let search_filter = self.filter.as_ref().map(|f| f.trim()).unwrap_or("");
+        let cols = self.columns();
 
-        let response_json = if search_filter.is_empty() || search_filter.parse::<u32>().is_ok() {
+        if search_filter.is_empty() || search_filter.parse::<u32>().is_ok() {
             // Empty filter or numeric filter ID -> use listCases command
-            let mut cols = self.cols.clone().unwrap_or_default();
-            // Ensure required fields for Case struct are included
-            if !cols.iter().any(|c| c == "ixBug") {
-                cols.push("ixBug".to_string());
-            }
-            if !cols.iter().any(|c| c == "ixProject") {
-                cols.push("ixProject".to_string());
-            }
-            if !cols.iter().any(|c| c == "sProject") {
-                cols.push("sProject".to_string());
-            }
-            if !cols.iter().any(|c| c == "sTitle") {
-                cols.push("sTitle".to_string());
-            }
-
             let params = serde_json::json!({
                 "sFilter": search_filter,
                 "cols": cols,
-                "max": self.max,
+                "max": max,
+                "start": start,
             });
-            self.client.send_list_cases(params).await?
+            self.client.send_list_cases(params).await
         } else {
             // Non-numeric filter (search query) -> use search command instead
-            let mut cols = self.cols.clone().unwrap_or_default();
-            // Ensure required fields for Case struct are included
-            if !cols.iter().any(|c| c == "ixBug") {
-                cols.push("ixBug".to_string());
-            }
-            if !cols.iter().any(|c| c == "ixProject") {
-                cols.push("ixProject".to_string());
-            }
-            if !cols.iter().any(|c| c == "sProject") {
-                cols.push("sProject".to_string());
-            }
-            if !cols.iter().any(|c| c == "sTitle") {
-                cols.push("sTitle".to_string());
-            }
-
+            let query = if stable_order {
+                with_stable_order(search_filter)
+            } else {
+                search_filter.to_string()
+            };
             let params = serde_json::json!({
-                "q": search_filter,
+                "q": query,
                 "cols": cols,
-                "max": self.max,
+                "max": max,
+                "start": start,
             });
-            self.client.send_search(params).await?
-        };
-
-        // Parse the cases from the response
-        let cases = serde_json::from_value(response_json["data"]["cases"].clone())?;
-        Ok(cases)
+            self.client.send_search(params).await
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::filter::StatusFilter;
+
+    #[test]
+    fn test_missing_column_error_names_column() {
+        let cases = serde_json::json!([{ "ixBug": 1, "ixProject": 2, "sProject": "Widget" }]);
+        let err = ResponseError::deserialize_cases::<Vec<Case>>(cases).unwrap_err();
+        assert!(matches!(err, ResponseError::MissingColumn(Column::Title)));
+
+        let api = FogBugzClient::new("https://example.com", "test_key");
+        let request = api
+            .list_cases()
+            .cols(&[Column::Title, Column::Area])
+            .require_columns_for::<Case>()
+            .build();
+        assert_eq!(
+            request.cols,
+            Some(vec![
+                "sTitle".to_string(),
+                "sArea".to_string(),
+                "ixBug".to_string(),
+                "ixProject".to_string(),
+                "sProject".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_requested_columns() {
+        let api = FogBugzClient::new("https://example.com", "test_key");
+        let request = api.list_cases().build();
+        assert_eq!(
+            request.columns(),
+            ["ixBug", "ixProject", "sProject", "sTitle"]
+        );
+
+        let request = api
+            .list_cases()
+            .cols(&[Column::Title, Column::PersonAssignedTo])
+            .build();
+        assert_eq!(request.columns(), ["sTitle", "sPersonAssignedTo", "ixBug"]);
+        let cases: Vec<CaseSummary> = ResponseError::deserialize_cases(serde_json::json!([
+            { "ixBug": 1, "sTitle": "Crash", "sPersonAssignedTo": "Jane" }
+        ]))
+        .unwrap();
+        assert_eq!(cases[0].assigned_to.as_deref(), Some("Jane"));
+        assert_eq!(cases[0].project, None);
+    }
+
+    #[test]
+    fn test_case_display() {
+        let case = Case {
+            case_id: 62020,
+            project_id: 3,
+            project: "Widget Factory".to_string(),
+            title: "Fix login page".to_string(),
+        };
+        assert_eq!(case.to_string(), "#62020 [Widget Factory] Fix login page");
+    }
 
     #[tokio::test]
     async fn test_list_cases_request() {
@@ -165,7 +309,9 @@ mod tests {
             .build();
 
         // Test 1: Search filter (should use search command internally)
-        let search_filter = FogBugzSearchBuilder::new().status("Active").build();
+        let search_filter = FogBugzSearchBuilder::new()
+            .status(StatusFilter::Active)
+            .build();
 
         let request = api
             .list_cases()
@@ -184,7 +330,7 @@ mod tests {
 
         // Verify we got Active cases
         // Note: We can't assert status field since we didn't request it
-        assert!(res.iter().all(|case| case.case_id > 0));
+        assert!(res.iter().all(|case| case.case_id.is_some_and(|id| id > 0)));
 
         // Test 2: Saved filter ID (should use listCases command)
         let request = api
@@ -201,7 +347,7 @@ mod tests {
 
         let res = request.send().await.unwrap();
         assert!(!res.is_empty());
-        assert!(res.iter().all(|case| case.case_id > 0));
+        assert!(res.iter().all(|case| case.case_id.is_some_and(|id| id > 0)));
 
         // Test 3: Empty filter (should use listCases command)
         let request = api
@@ -218,7 +364,7 @@ mod tests {
 
         let res = request.send().await.unwrap();
         assert!(!res.is_empty());
-        assert!(res.iter().all(|case| case.case_id > 0));
+        assert!(res.iter().all(|case| case.case_id.is_some_and(|id| id > 0)));
     }
 
     #[tokio::test]
@@ -246,7 +392,7 @@ mod tests {
         // with values like "status:Active" or "status:active".
         // For now, using an empty filter string which works correctly.
         let _search_filter = FogBugzSearchBuilder::new()
-            .status("Active")
+            .status(StatusFilter::Active)
             .order_by("Priority", false);
 
         let request = api
@@ -266,8 +412,8 @@ mod tests {
 
         // Verify we got the expected columns
         let first_case = &res[0];
-        assert!(first_case.case_id > 0);
-        assert!(!first_case.project.is_empty());
-        assert!(!first_case.titile.is_empty());
+        assert!(first_case.case_id.is_some_and(|id| id > 0));
+        assert!(first_case.project.as_ref().is_some_and(|p| !p.is_empty()));
+        assert!(first_case.title.as_ref().is_some_and(|t| !t.is_empty()));
     }
 }
@@ -0,0 +1,192 @@
+use bon::Builder;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::{FogBugzClient, ResponseError};
+
+/// A calendar event, e.g. from an iCal feed or a calendar API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CalendarEvent {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub title: String,
+}
+
+/// An interval logged (or, on a dry run, to be logged) for part of a meeting
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MeetingInterval {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub title: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct MeetingFillReport {
+    pub created: Vec<MeetingInterval>,
+    /// Events already covered by logged time, or by earlier overlapping events
+    pub skipped: Vec<CalendarEvent>,
+}
+
+/// Parts of `start..end` not covered by any of `busy`, in order
+fn uncovered(
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    busy: &[(DateTime<Utc>, DateTime<Utc>)],
+) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut busy: Vec<_> = busy
+        .iter()
+        .filter(|(busy_start, busy_end)| *busy_start < end && *busy_end > start)
+        .copied()
+        .collect();
+    busy.sort();
+    let mut gaps = Vec::new();
+    let mut cursor = start;
+    for (busy_start, busy_end) in busy {
+        if busy_start > cursor {
+            gaps.push((cursor, busy_start));
+        }
+        cursor = cursor.max(busy_end);
+    }
+    if cursor < end {
+        gaps.push((cursor, end));
+    }
+    gaps
+}
+
+/// Intervals filling each event's time not yet in `busy`, dropping gaps shorter
+/// than `min_length`. Events are taken by start time and each one's intervals
+/// count as busy for the next, so overlapping meetings aren't logged twice.
+fn plan(
+    events: &[CalendarEvent],
+    mut busy: Vec<(DateTime<Utc>, DateTime<Utc>)>,
+    min_length: Duration,
+) -> MeetingFillReport {
+    let mut events: Vec<&CalendarEvent> = events.iter().filter(|e| e.end > e.start).collect();
+    events.sort_by_key(|event| (event.start, event.end));
+    let mut report = MeetingFillReport::default();
+    for event in events {
+        let gaps: Vec<_> = uncovered(event.start, event.end, &busy)
+            .into_iter()
+            .filter(|(start, end)| *end - *start >= min_length)
+            .collect();
+        if gaps.is_empty() {
+            report.skipped.push(event.clone());
+            continue;
+        }
+        for (start, end) in gaps {
+            busy.push((start, end));
+            report.created.push(MeetingInterval {
+                start,
+                end,
+                title: event.title.clone(),
+            });
+        }
+    }
+    report
+}
+
+/// Log calendar events as intervals on a designated meetings case, skipping time
+/// the person already logged, so timesheets are complete without manual entry.
+#[derive(Debug, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct MeetingFillRequest {
+    /// Events to log (required)
+    #[builder(into)]
+    events: Vec<CalendarEvent>,
+    /// Case the meeting time is logged on, e.g. "Meetings" (required)
+    meetings_case_id: u32,
+    /// Person whose timesheet to fill; the token owner if not set.
+    /// Logging time for others requires administrator rights (optional)
+    person_id: Option<u32>,
+    /// Uncovered parts shorter than this are not logged
+    #[builder(default = Duration::minutes(5))]
+    min_length: Duration,
+    /// Work out the intervals without creating them
+    #[builder(default)]
+    dry_run: bool,
+    /// API instance
+    client: FogBugzClient,
+}
+
+impl MeetingFillRequest {
+    pub async fn send(&self) -> Result<MeetingFillReport, ResponseError> {
+        let from = self.events.iter().map(|event| event.start).min();
+        let to = self.events.iter().map(|event| event.end).max();
+        let (Some(from), Some(to)) = (from, to) else {
+            return Ok(MeetingFillReport::default());
+        };
+        let busy = self
+            .client
+            .list_time_intervals(self.person_id, Some(from), Some(to))
+            .await?
+            .into_iter()
+            .filter(|interval| !interval.is_deleted)
+            .map(|interval| (interval.start_time, interval.end_time))
+            .collect();
+        let report = plan(&self.events, busy, self.min_length);
+        if self.dry_run {
+            return Ok(report);
+        }
+        for interval in &report.created {
+            self.client
+                .new_interval()
+                .case_id(self.meetings_case_id)
+                .start_time(interval.start)
+                .end_time(interval.end)
+                .title(interval.title.clone())
+                .maybe_person_id(self.person_id)
+                .build()
+                .send()
+                .await?;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(time: &str) -> DateTime<Utc> {
+        format!("2024-03-04T{time}:00Z").parse().unwrap()
+    }
+
+    fn event(start: &str, end: &str, title: &str) -> CalendarEvent {
+        CalendarEvent {
+            start: at(start),
+            end: at(end),
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_plan_skips_logged_time() {
+        let events = [
+            event("13:00", "14:00", "Planning"),
+            event("09:00", "10:00", "Standup"),
+            event("13:30", "14:30", "Overlapping"),
+            event("11:00", "11:30", "Covered"),
+        ];
+        let busy = vec![
+            (at("09:15"), at("09:30")),
+            (at("09:57"), at("10:30")),
+            (at("11:00"), at("12:00")),
+        ];
+        let report = plan(&events, busy, Duration::minutes(5));
+        let created: Vec<(DateTime<Utc>, DateTime<Utc>, &str)> = report
+            .created
+            .iter()
+            .map(|interval| (interval.start, interval.end, interval.title.as_str()))
+            .collect();
+        assert_eq!(
+            created,
+            vec![
+                (at("09:00"), at("09:15"), "Standup"),
+                (at("09:30"), at("09:57"), "Standup"),
+                (at("13:00"), at("14:00"), "Planning"),
+                (at("14:00"), at("14:30"), "Overlapping"),
+            ]
+        );
+        assert_eq!(report.skipped, vec![event("11:00", "11:30", "Covered")]);
+    }
+}
@@ -0,0 +1,121 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use crate::{
+    FogBugzClient, ResponseError,
+    case_details::Event,
+    organization::{Person, find_person},
+};
+
+/// Email addresses anywhere in the text
+static EMAIL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+").unwrap());
+
+/// `@handle` or `@"Full Name"` not preceded by a word character (so emails don't count)
+static MENTION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(?:^|[^\w.+-])@(?:"([^"]+)"|([\w.\-]*\w))"#).unwrap());
+
+/// Lowercase and strip separators so `@jane.doe`, `@jane_doe` and "Jane Doe" compare equal
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Person references found in text: `@handle`, `@"Full Name"` and email addresses,
+/// in order of appearance
+pub fn extract_mentions(text: &str) -> Vec<String> {
+    let mut mentions: Vec<(usize, String)> = EMAIL
+        .find_iter(text)
+        .map(|m| (m.start(), m.as_str().to_string()))
+        .collect();
+    mentions.extend(MENTION.captures_iter(text).filter_map(|captures| {
+        let name = captures.get(1).or_else(|| captures.get(2))?;
+        Some((name.start(), name.as_str().to_string()))
+    }));
+    mentions.sort_by_key(|(position, _)| *position);
+    mentions.into_iter().map(|(_, mention)| mention).collect()
+}
+
+/// Resolve mentions in `text` against a list of people, by email, full name or a
+/// handle matching the full name without separators (e.g. `@jane.doe` or `@janedoe`).
+/// Each person is returned at most once.
+pub fn resolve_mentions<'a>(text: &str, people: &'a [Person]) -> Vec<&'a Person> {
+    let mut resolved: Vec<&Person> = Vec::new();
+    for mention in extract_mentions(text) {
+        let normalized = normalize(&mention);
+        let person = find_person(people, &mention).or_else(|| {
+            people
+                .iter()
+                .find(|person| !normalized.is_empty() && normalize(&person.full_name) == normalized)
+        });
+        if let Some(person) = person
+            && !resolved.iter().any(|p| p.id == person.id)
+        {
+            resolved.push(person);
+        }
+    }
+    resolved
+}
+
+impl FogBugzClient {
+    /// People mentioned in an event's text
+    pub async fn event_mentions(&self, event: &Event) -> Result<Vec<Person>, ResponseError> {
+        let people = self.list_people().await?;
+        Ok(resolve_mentions(&event.content, &people)
+            .into_iter()
+            .cloned()
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn person(id: u32, full_name: &str, email: &str) -> Person {
+        serde_json::from_value(serde_json::json!({
+            "ixPerson": id,
+            "sFullName": full_name,
+            "sEmail": email,
+            "sPhone": "",
+            "fAdministrator": false,
+            "fCommunity": false,
+            "fVirtual": false,
+            "fDeleted": false,
+            "fNotify": true,
+            "sHomepage": "",
+            "sLocale": "*",
+            "sLanguage": "*",
+            "sTimeZoneKey": "*"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_extract_mentions() {
+        let text = "Thanks @jane.doe! Can @\"John Smith\" check with ops@example.com?";
+        assert_eq!(
+            extract_mentions(text),
+            vec!["jane.doe", "John Smith", "ops@example.com"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_mentions() {
+        let people = vec![
+            person(1, "Jane Doe", "jane@example.com"),
+            person(2, "John Smith", "john@example.com"),
+            person(3, "Ops Team", "ops@example.com"),
+        ];
+        let text =
+            "@janedoe and @\"John Smith\" please sync with ops@example.com (cc @jane_doe, @nobody)";
+        let ids: Vec<u32> = resolve_mentions(text, &people)
+            .iter()
+            .map(|person| person.id)
+            .collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+    }
+}
@@ -0,0 +1,116 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde_json::Value;
+
+use crate::{FogBugzClient, ResponseError};
+
+/// Responses of org metadata commands (`listProjects`, `listPeople`, ...),
+/// kept for a fixed time. Shared by all clones of the client it is configured on.
+#[derive(Debug)]
+pub(crate) struct MetadataCache {
+    ttl: Duration,
+    /// Response and when it was fetched, by command and parameters
+    entries: Mutex<HashMap<String, (Instant, Value)>>,
+}
+
+impl MetadataCache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    fn get(&self, key: &str) -> Option<Value> {
+        let mut entries = self.entries.lock().ok()?;
+        match entries.get(key) {
+            Some((fetched, response)) if fetched.elapsed() < self.ttl => Some(response.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, key: String, response: Value) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.insert(key, (Instant::now(), response));
+        }
+    }
+
+    fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+impl FogBugzClient {
+    /// Send a metadata command, answering from the metadata cache while the
+    /// last response for the same parameters is fresh
+    pub(crate) async fn send_cached_command(
+        &self,
+        cmd: &str,
+        params: Value,
+    ) -> Result<Value, ResponseError> {
+        let Some(cache) = &self.metadata_cache else {
+            return self.send_command(cmd, params).await;
+        };
+        let key = format!("{cmd} {params}");
+        if let Some(response) = cache.get(&key) {
+            return Ok(response);
+        }
+        let response = self.send_command(cmd, params).await?;
+        cache.insert(key, response.clone());
+        Ok(response)
+    }
+
+    /// Drop all cached metadata, e.g. after adding a project or person
+    pub fn invalidate_metadata_cache(&self) {
+        if let Some(cache) = &self.metadata_cache {
+            cache.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_metadata_cache_serves_until_invalidated() {
+        let api = FogBugzClient::builder()
+            .url("http://localhost:1")
+            .api_key("token")
+            .metadata_cache_ttl(Duration::from_secs(60))
+            .build();
+        api.metadata_cache.as_ref().unwrap().insert(
+            "listProjects {}".to_string(),
+            serde_json::json!({ "data": { "projects": [] } }),
+        );
+
+        let projects = api.clone().list_projects().await.unwrap();
+        assert!(projects.is_empty());
+
+        api.invalidate_metadata_cache();
+        assert!(api.list_projects().await.is_err());
+    }
+
+    #[test]
+    fn test_metadata_cache_expires() {
+        let cache = MetadataCache::new(Duration::from_millis(10));
+        cache.insert("listPeople {}".to_string(), Value::Null);
+        assert_eq!(cache.get("listPeople {}"), Some(Value::Null));
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(cache.get("listPeople {}"), None);
+    }
+}
@@ -0,0 +1,183 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+use thiserror::Error;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::{ResponseError, change_feed::ChangeFeed};
+
+#[derive(Debug, Error)]
+pub enum NdjsonError {
+    #[error("Writing NDJSON failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    JsonError(#[from] serde_json::Error),
+    #[error(transparent)]
+    Source(#[from] ResponseError),
+}
+
+/// What has been written to the current writer
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SinkStats {
+    pub lines: u64,
+    pub bytes: u64,
+}
+
+/// Decides when to switch to a new writer, e.g. a new file per hour or per 100 MB
+#[async_trait]
+pub trait Rotation<W: Send + 'static>: Send {
+    /// Called after every line; return a writer to continue with instead of the current one
+    async fn next_writer(&mut self, stats: &SinkStats) -> std::io::Result<Option<W>>;
+
+    /// Called with a writer once it has been flushed and shut down, e.g. to upload it
+    async fn closed(&mut self, _writer: W) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<W: Send + 'static, R: Rotation<W> + ?Sized> Rotation<W> for &mut R {
+    async fn next_writer(&mut self, stats: &SinkStats) -> std::io::Result<Option<W>> {
+        (**self).next_writer(stats).await
+    }
+
+    async fn closed(&mut self, writer: W) -> std::io::Result<()> {
+        (**self).closed(writer).await
+    }
+}
+
+/// Keep writing to the same writer
+#[derive(Debug, Default)]
+pub struct NoRotation;
+
+#[async_trait]
+impl<W: Send + 'static> Rotation<W> for NoRotation {
+    async fn next_writer(&mut self, _stats: &SinkStats) -> std::io::Result<Option<W>> {
+        Ok(None)
+    }
+}
+
+/// Start a new numbered file (`{prefix}-1.ndjson`, `{prefix}-2.ndjson`, ...) once the
+/// current one reaches `max_bytes`
+#[derive(Debug)]
+pub struct FileRotation {
+    pub dir: PathBuf,
+    pub prefix: String,
+    pub max_bytes: u64,
+    index: u32,
+}
+
+impl FileRotation {
+    pub fn new(dir: impl Into<PathBuf>, prefix: impl Into<String>, max_bytes: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            prefix: prefix.into(),
+            max_bytes,
+            index: 0,
+        }
+    }
+
+    /// Create the next file in the sequence
+    pub async fn open_next(&mut self) -> std::io::Result<tokio::fs::File> {
+        self.index += 1;
+        let path = self
+            .dir
+            .join(format!("{}-{}.ndjson", self.prefix, self.index));
+        tokio::fs::File::create(path).await
+    }
+}
+
+#[async_trait]
+impl Rotation<tokio::fs::File> for FileRotation {
+    async fn next_writer(&mut self, stats: &SinkStats) -> std::io::Result<Option<tokio::fs::File>> {
+        if stats.bytes < self.max_bytes {
+            return Ok(None);
+        }
+        Ok(Some(self.open_next().await?))
+    }
+}
+
+/// Write every item of `items` as one line of JSON until the stream ends or yields
+/// an error. Returns what was written to the last writer.
+pub async fn write_ndjson<S, T, W, R>(
+    items: S,
+    mut writer: W,
+    mut rotation: R,
+) -> Result<SinkStats, NdjsonError>
+where
+    S: Stream<Item = Result<T, ResponseError>>,
+    T: Serialize,
+    W: AsyncWrite + Unpin + Send + 'static,
+    R: Rotation<W>,
+{
+    let mut items = std::pin::pin!(items);
+    let mut stats = SinkStats::default();
+    while let Some(item) = items.next().await {
+        let mut line = serde_json::to_vec(&item?)?;
+        line.push(b'\n');
+        writer.write_all(&line).await?;
+        writer.flush().await?;
+        stats.lines += 1;
+        stats.bytes += line.len() as u64;
+        if let Some(next) = rotation.next_writer(&stats).await? {
+            let mut previous = std::mem::replace(&mut writer, next);
+            previous.shutdown().await?;
+            rotation.closed(previous).await?;
+            stats = SinkStats::default();
+        }
+    }
+    writer.shutdown().await?;
+    Ok(stats)
+}
+
+impl ChangeFeed {
+    /// Ship the feed to `writer` as NDJSON. Runs until a poll fails or writing does.
+    pub async fn write_ndjson<W, R>(&self, writer: W, rotation: R) -> Result<SinkStats, NdjsonError>
+    where
+        W: AsyncWrite + Unpin + Send + 'static,
+        R: Rotation<W>,
+    {
+        write_ndjson(self.stream(), writer, rotation).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Rotates after every two lines and keeps the finished buffers
+    #[derive(Default)]
+    struct EveryTwoLines {
+        closed: Vec<Vec<u8>>,
+    }
+
+    #[async_trait]
+    impl Rotation<Vec<u8>> for EveryTwoLines {
+        async fn next_writer(&mut self, stats: &SinkStats) -> std::io::Result<Option<Vec<u8>>> {
+            Ok((stats.lines == 2).then(Vec::new))
+        }
+
+        async fn closed(&mut self, writer: Vec<u8>) -> std::io::Result<()> {
+            self.closed.push(writer);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_ndjson_rotates() {
+        let items = futures::stream::iter(
+            (1..=3).map(|id| Ok::<_, ResponseError>(serde_json::json!({ "ixBug": id }))),
+        );
+        let mut rotation = EveryTwoLines::default();
+        let stats = write_ndjson(items, Vec::new(), &mut rotation)
+            .await
+            .unwrap();
+        assert_eq!(stats.lines, 1);
+        assert_eq!(
+            String::from_utf8(rotation.closed[0].clone()).unwrap(),
+            "{\"ixBug\":1}\n{\"ixBug\":2}\n"
+        );
+    }
+}
@@ -1,6 +1,10 @@
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
 
-use crate::{FogBugzClient, ResponseError};
+use crate::{
+    FogBugzClient, ResponseError, changes::ChangesLanguage, enums::Column, list_cases::CaseSummary,
+};
 
 /// A FogBugz project
 #[derive(Debug, Deserialize, Serialize)]
@@ -25,8 +29,14 @@ pub struct Project {
     pub is_deleted: bool,
 }
 
+impl fmt::Display for Project {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (#{}, owner: {})", self.name, self.id, self.owner)
+    }
+}
+
 /// A FogBugz user/person
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Person {
     #[serde(rename = "ixPerson")]
     pub id: u32,
@@ -56,6 +66,48 @@ pub struct Person {
     pub timezone: String,
 }
 
+/// A FogBugz permission group (team)
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Group {
+    #[serde(rename = "ixGroup")]
+    pub id: u32,
+    #[serde(rename = "sName")]
+    pub name: String,
+}
+
+/// A reference to a person, either by `ixPerson` or by full name/email
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PersonRef {
+    Id(u32),
+    Name(String),
+}
+
+impl From<u32> for PersonRef {
+    fn from(id: u32) -> Self {
+        PersonRef::Id(id)
+    }
+}
+
+impl From<&str> for PersonRef {
+    fn from(name: &str) -> Self {
+        PersonRef::Name(name.to_string())
+    }
+}
+
+impl From<String> for PersonRef {
+    fn from(name: String) -> Self {
+        PersonRef::Name(name)
+    }
+}
+
+/// Find a person by full name or email (case-insensitive)
+pub fn find_person<'a>(people: &'a [Person], name_or_email: &str) -> Option<&'a Person> {
+    let needle = name_or_email.trim();
+    people.iter().find(|person| {
+        person.full_name.eq_ignore_ascii_case(needle) || person.email.eq_ignore_ascii_case(needle)
+    })
+}
+
 /// A FogBugz area within a project
 #[derive(Debug, Deserialize, Serialize)]
 pub struct Area {
@@ -73,6 +125,23 @@ pub struct Area {
     pub area_type: u32,
 }
 
+/// A case tag and how many cases use it
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Tag {
+    #[serde(rename = "ixTag")]
+    pub id: u32,
+    #[serde(rename = "sTag")]
+    pub name: String,
+    #[serde(rename = "cTagUses", default)]
+    pub count: u32,
+}
+
+impl fmt::Display for Tag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.count)
+    }
+}
+
 /// A FogBugz category
 #[derive(Debug, Deserialize, Serialize)]
 pub struct CategoryInfo {
@@ -148,11 +217,24 @@ pub struct Filter {
     pub description: Option<String>,
 }
 
+/// Site-wide settings returned by `viewSettings`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SiteSettings {
+    /// Site language, e.g. `en-us`
+    #[serde(rename = "sLanguage", alias = "sCulture", default)]
+    pub language: Option<String>,
+    /// Windows time zone key of the site, used for people whose key is `*`
+    #[serde(rename = "sTimeZoneKey", default)]
+    pub timezone: Option<String>,
+    #[serde(flatten)]
+    pub other: serde_json::Map<String, serde_json::Value>,
+}
+
 impl FogBugzClient {
     /// List all projects
     pub async fn list_projects(&self) -> Result<Vec<Project>, ResponseError> {
         let response = self
-            .send_command("listProjects", serde_json::json!({}))
+            .send_cached_command("listProjects", serde_json::json!({}))
             .await?;
         let projects = serde_json::from_value(response["data"]["projects"].clone())?;
         Ok(projects)
@@ -165,18 +247,59 @@ impl FogBugzClient {
             "fIncludeCommunity": true,
             "fIncludeVirtual": false
         });
-        let response = self.send_command("listPeople", params).await?;
+        let response = self.send_cached_command("listPeople", params).await?;
+        let people = serde_json::from_value(response["data"]["people"].clone())?;
+        Ok(people)
+    }
+
+    /// List permission groups, on sites that expose them via `listGroups`
+    pub async fn list_groups(&self) -> Result<Vec<Group>, ResponseError> {
+        let response = self
+            .send_cached_command("listGroups", serde_json::json!({}))
+            .await?;
+        let groups = serde_json::from_value(response["data"]["groups"].clone())?;
+        Ok(groups)
+    }
+
+    /// List the members of a permission group
+    pub async fn list_group_people(&self, group_id: u32) -> Result<Vec<Person>, ResponseError> {
+        let params = serde_json::json!({
+            "ixGroup": group_id,
+            "fIncludeNormal": true,
+            "fIncludeCommunity": true,
+            "fIncludeVirtual": false
+        });
+        let response = self.send_cached_command("listPeople", params).await?;
         let people = serde_json::from_value(response["data"]["people"].clone())?;
         Ok(people)
     }
 
+    /// Person IDs of a group's members, for scoping reports to a team
+    pub async fn group_member_ids(&self, group_id: u32) -> Result<Vec<u32>, ResponseError> {
+        let people = self.list_group_people(group_id).await?;
+        Ok(people.into_iter().map(|person| person.id).collect())
+    }
+
+    /// Resolve a person reference to its `ixPerson`, looking names up in the people list
+    pub async fn resolve_person_ref(&self, person: &PersonRef) -> Result<u32, ResponseError> {
+        match person {
+            PersonRef::Id(id) => Ok(*id),
+            PersonRef::Name(name) => {
+                let people = self.list_people().await?;
+                find_person(&people, name)
+                    .map(|person| person.id)
+                    .ok_or_else(|| ResponseError::PersonNotFound(name.clone()))
+            }
+        }
+    }
+
     /// List areas for a specific project
     pub async fn list_areas(&self, project_id: Option<u32>) -> Result<Vec<Area>, ResponseError> {
         let mut params = serde_json::json!({});
         if let Some(id) = project_id {
             params["ixProject"] = id.into();
         }
-        let response = self.send_command("listAreas", params).await?;
+        let response = self.send_cached_command("listAreas", params).await?;
         let areas = serde_json::from_value(response["data"]["areas"].clone())?;
         Ok(areas)
     }
@@ -184,16 +307,25 @@ impl FogBugzClient {
     /// List all categories
     pub async fn list_categories(&self) -> Result<Vec<CategoryInfo>, ResponseError> {
         let response = self
-            .send_command("listCategories", serde_json::json!({}))
+            .send_cached_command("listCategories", serde_json::json!({}))
             .await?;
         let categories = serde_json::from_value(response["data"]["categories"].clone())?;
         Ok(categories)
     }
 
+    /// List all tags in use
+    pub async fn list_tags(&self) -> Result<Vec<Tag>, ResponseError> {
+        let response = self
+            .send_cached_command("listTags", serde_json::json!({}))
+            .await?;
+        let tags = serde_json::from_value(response["data"]["tags"].clone())?;
+        Ok(tags)
+    }
+
     /// List all priorities
     pub async fn list_priorities(&self) -> Result<Vec<Priority>, ResponseError> {
         let response = self
-            .send_command("listPriorities", serde_json::json!({}))
+            .send_cached_command("listPriorities", serde_json::json!({}))
             .await?;
         let priorities = serde_json::from_value(response["data"]["priorities"].clone())?;
         Ok(priorities)
@@ -208,7 +340,7 @@ impl FogBugzClient {
         if let Some(id) = category_id {
             params["ixCategory"] = id.into();
         }
-        let response = self.send_command("listStatuses", params).await?;
+        let response = self.send_cached_command("listStatuses", params).await?;
         let statuses = serde_json::from_value(response["data"]["statuses"].clone())?;
         Ok(statuses)
     }
@@ -222,11 +354,31 @@ impl FogBugzClient {
         if let Some(id) = project_id {
             params["ixProject"] = id.into();
         }
-        let response = self.send_command("listFixFors", params).await?;
+        let response = self.send_cached_command("listFixFors", params).await?;
         let milestones = serde_json::from_value(response["data"]["fixfors"].clone())?;
         Ok(milestones)
     }
 
+    /// Fetch site settings (language, time zone, ...)
+    pub async fn view_settings(&self) -> Result<SiteSettings, ResponseError> {
+        let response = self
+            .send_command("viewSettings", serde_json::json!({}))
+            .await?;
+        let settings = serde_json::from_value(response["data"].clone())?;
+        Ok(settings)
+    }
+
+    /// Rules for parsing event changes text on this site, or `None` if the
+    /// site language isn't supported (changes are then kept as raw text).
+    /// Sites that don't report a language are assumed to be English.
+    pub async fn changes_language(&self) -> Result<Option<ChangesLanguage>, ResponseError> {
+        let settings = self.view_settings().await?;
+        Ok(settings.language.as_deref().map_or(
+            Some(ChangesLanguage::English),
+            ChangesLanguage::from_site_language,
+        ))
+    }
+
     /// List all saved filters
     pub async fn list_filters(&self) -> Result<Vec<Filter>, ResponseError> {
         let response = self.send_list_filters().await?;
@@ -291,12 +443,144 @@ impl FogBugzClient {
 
         Ok(filters)
     }
+
+    /// Make a saved filter the current user's current filter
+    pub async fn set_current_filter(&self, filter_id: &str) -> Result<(), ResponseError> {
+        self.send_command(
+            "setCurrentFilter",
+            serde_json::json!({ "sFilter": filter_id }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Cases of a saved filter, with `cols` and the case ID. Lists the filter
+    /// with `listCases`, leaving the user's current filter unchanged.
+    pub async fn list_cases_for_filter(
+        &self,
+        filter_id: &str,
+        cols: &[Column],
+    ) -> Result<Vec<CaseSummary>, ResponseError> {
+        let mut cols: Vec<String> = cols.iter().map(|col| col.to_string()).collect();
+        if !cols.contains(&Column::CaseId.to_string()) {
+            cols.push(Column::CaseId.to_string());
+        }
+        let response = self
+            .send_list_cases(serde_json::json!({ "sFilter": filter_id, "cols": cols }))
+            .await?;
+        ResponseError::deserialize_cases(response["data"]["cases"].clone())
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{Person, find_person};
     use crate::FogBugzClient;
 
+    fn person(id: u32, full_name: &str, email: &str) -> Person {
+        serde_json::from_value(serde_json::json!({
+            "ixPerson": id,
+            "sFullName": full_name,
+            "sEmail": email,
+            "sPhone": "",
+            "fAdministrator": false,
+            "fCommunity": false,
+            "fVirtual": false,
+            "fDeleted": false,
+            "fNotify": true,
+            "sHomepage": "",
+            "sLocale": "*",
+            "sLanguage": "*",
+            "sTimeZoneKey": "*"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_find_person_by_name_or_email() {
+        let people = vec![
+            person(1, "Jane Doe", "jane@example.com"),
+            person(2, "John Smith", "john@example.com"),
+        ];
+
+        assert_eq!(find_person(&people, "jane doe").unwrap().id, 1);
+        assert_eq!(find_person(&people, "JOHN@example.com").unwrap().id, 2);
+        assert!(find_person(&people, "Nobody").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_list_groups_and_members() {
+        use crate::mock_server::MockServer;
+
+        let server = MockServer::start(|payload| match payload["cmd"].as_str() {
+            Some("listGroups") => serde_json::json!({
+                "data": { "groups": [
+                    { "ixGroup": 3, "sName": "Support" },
+                    { "ixGroup": 4, "sName": "Developers" }
+                ] },
+                "errors": []
+            }),
+            _ => serde_json::json!({
+                "data": { "people": [
+                    person(7, "Jane Doe", "jane@example.com"),
+                    person(9, "John Smith", "john@example.com")
+                ] },
+                "errors": []
+            }),
+        })
+        .await;
+        let api = FogBugzClient::new(server.url(), "token");
+
+        let groups = api.list_groups().await.unwrap();
+        let names: Vec<(u32, &str)> = groups
+            .iter()
+            .map(|group| (group.id, group.name.as_str()))
+            .collect();
+        assert_eq!(names, vec![(3, "Support"), (4, "Developers")]);
+
+        let people = api.list_group_people(3).await.unwrap();
+        assert_eq!(people[0].full_name, "Jane Doe");
+        assert_eq!(api.group_member_ids(3).await.unwrap(), vec![7, 9]);
+
+        let requests = server.requests();
+        assert_eq!(requests[0]["cmd"], "listGroups");
+        for payload in &requests[1..] {
+            assert_eq!(payload["cmd"], "listPeople");
+            assert_eq!(payload["ixGroup"], 3);
+            assert_eq!(payload["fIncludeNormal"], true);
+            assert_eq!(payload["fIncludeCommunity"], true);
+            assert_eq!(payload["fIncludeVirtual"], false);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_cases_for_filter_keeps_current_filter() {
+        use std::sync::{Arc, Mutex};
+
+        use crate::{enums::Column, interceptor::RequestContext};
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let log = sent.clone();
+        let api = FogBugzClient::new("http://localhost:1", "token")
+            .read_only()
+            .with_interceptor(move |request: &mut RequestContext<'_>| {
+                log.lock()
+                    .unwrap()
+                    .push((request.cmd.to_string(), request.payload.clone()));
+            });
+
+        let result = api.list_cases_for_filter("ez349", &[Column::Title]).await;
+        assert!(!matches!(result, Err(crate::ResponseError::ReadOnly(_))));
+
+        let sent = sent.lock().unwrap();
+        assert!(!sent.is_empty());
+        for (cmd, payload) in sent.iter() {
+            assert_eq!(cmd, "listCases");
+            assert_eq!(payload["sFilter"], "ez349");
+            assert_eq!(payload["cols"], serde_json::json!(["sTitle", "ixBug"]));
+        }
+    }
+
     #[tokio::test]
     async fn test_list_projects() {
         let api_key = std::env::var("FOGBUGZ_API_KEY").unwrap();
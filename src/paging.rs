@@ -0,0 +1,73 @@
+use std::future::Future;
+
+use futures::{Stream, stream};
+
+use crate::{
+    ResponseError,
+    budget::{Budget, BudgetTracker},
+};
+
+/// Page size used by `paged()`/`send_all()` when the request has no `max`
+pub const DEFAULT_PAGE_SIZE: u32 = 500;
+
+/// Fetch pages with `fetch(start, max)` until the server returns a short page.
+/// The budget applies to the whole iteration, not to a single page.
+pub(crate) fn pages<'a, T, F, Fut>(
+    start: u32,
+    page_size: u32,
+    budget: Option<Budget>,
+    fetch: F,
+) -> impl Stream<Item = Result<Vec<T>, ResponseError>> + 'a
+where
+    T: 'a,
+    F: Fn(u32, u32) -> Fut + 'a,
+    Fut: Future<Output = Result<Vec<T>, ResponseError>> + 'a,
+{
+    let page_size = page_size.max(1);
+    let state = (Some(start), BudgetTracker::new(budget));
+    stream::try_unfold(state, move |(next, mut budget)| {
+        let page = next.map(|start| budget.request().map(|()| fetch(start, page_size)));
+        async move {
+            let Some(page) = page else {
+                return Ok(None);
+            };
+            let page = page?.await?;
+            budget.cases(page.len())?;
+            let next = next
+                .filter(|_| page.len() as u32 >= page_size)
+                .map(|start| start + page.len() as u32);
+            Ok(Some((page, (next, budget))))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::TryStreamExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_pages_until_short_page() {
+        let all: Vec<u32> = (0..7).collect();
+        let fetch = |start: u32, max: u32| {
+            let page: Vec<u32> = all
+                .iter()
+                .copied()
+                .skip(start as usize)
+                .take(max as usize)
+                .collect();
+            async move { Ok(page) }
+        };
+        let all_pages: Vec<Vec<u32>> = pages(0, 3, None, fetch).try_collect().await.unwrap();
+        assert_eq!(all_pages, vec![vec![0, 1, 2], vec![3, 4, 5], vec![6]]);
+
+        let over_budget: Result<Vec<Vec<u32>>, _> = pages(0, 3, Some(Budget::max_cases(4)), fetch)
+            .try_collect()
+            .await;
+        assert!(matches!(
+            over_budget,
+            Err(ResponseError::BudgetExceeded { max: 4, .. })
+        ));
+    }
+}
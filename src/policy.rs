@@ -0,0 +1,57 @@
+use std::{collections::HashSet, sync::Arc};
+
+/// Which API commands a client may send, enforced before any request is made.
+/// Lets embedders sandbox what code built on top of the client can do.
+#[derive(Debug, Clone, Default)]
+pub enum CommandPolicy {
+    #[default]
+    AllowAll,
+    /// Only these commands may be sent
+    Allow(Arc<HashSet<String>>),
+    /// Every command except these may be sent
+    Deny(Arc<HashSet<String>>),
+}
+
+impl CommandPolicy {
+    pub fn allow<I, S>(commands: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        CommandPolicy::Allow(Arc::new(commands.into_iter().map(Into::into).collect()))
+    }
+
+    pub fn deny<I, S>(commands: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        CommandPolicy::Deny(Arc::new(commands.into_iter().map(Into::into).collect()))
+    }
+
+    pub fn permits(&self, cmd: &str) -> bool {
+        match self {
+            CommandPolicy::AllowAll => true,
+            CommandPolicy::Allow(commands) => commands.contains(cmd),
+            CommandPolicy::Deny(commands) => !commands.contains(cmd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_policy() {
+        assert!(CommandPolicy::default().permits("edit"));
+
+        let allow = CommandPolicy::allow(["search", "listPeople"]);
+        assert!(allow.permits("search"));
+        assert!(!allow.permits("edit"));
+
+        let deny = CommandPolicy::deny(["close", "resolve"]);
+        assert!(deny.permits("edit"));
+        assert!(!deny.permits("close"));
+    }
+}
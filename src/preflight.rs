@@ -0,0 +1,104 @@
+use std::time::{Duration, Instant};
+
+use chrono::FixedOffset;
+use url::Url;
+
+use crate::{FogBugzClient, ResponseError, timezones::standard_offset};
+
+/// API versions a site announces in `api.xml`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiVersion {
+    pub version: u32,
+    /// Oldest version the site still accepts
+    pub min_version: u32,
+}
+
+/// What `FogBugzClient::preflight` found out about the site
+#[derive(Debug, Clone)]
+pub struct PreflightReport {
+    /// `None` if the site doesn't serve `api.xml`
+    pub api_version: Option<ApiVersion>,
+    /// Windows time zone key of the site
+    pub site_timezone: Option<String>,
+    /// Standard UTC offset of the site time zone, if the key is known
+    pub site_offset: Option<FixedOffset>,
+    pub projects: usize,
+    pub people: usize,
+    /// Whether the lists were kept in the metadata cache
+    pub cache_primed: bool,
+    pub elapsed: Duration,
+}
+
+/// Read `<version>` and `<minversion>` out of `api.xml`
+fn parse_api_xml(xml: &str) -> Option<ApiVersion> {
+    let tag = |name: &str| {
+        let open = format!("<{name}>");
+        let start = xml.find(&open)? + open.len();
+        let end = start + xml[start..].find('<')?;
+        xml[start..end].trim().parse().ok()
+    };
+    let version = tag("version")?;
+    Some(ApiVersion {
+        version,
+        min_version: tag("minversion").unwrap_or(version),
+    })
+}
+
+impl FogBugzClient {
+    /// Check the client is usable before serving anything: the token is
+    /// accepted, and the API version, site time zone and org metadata can be
+    /// read. The lists are fetched concurrently and, with `metadata_cache_ttl`
+    /// configured, stay cached for the first requests.
+    pub async fn preflight(&self) -> Result<PreflightReport, ResponseError> {
+        let started = Instant::now();
+        // Cheapest authenticated command; a bad token or URL fails here
+        let settings = self.view_settings().await?;
+        let (api_version, projects, people, _, _) = futures::try_join!(
+            self.api_version(),
+            self.list_projects(),
+            self.list_people(),
+            self.list_categories(),
+            self.list_priorities(),
+        )?;
+        let site_offset = settings.timezone.as_deref().and_then(standard_offset);
+        Ok(PreflightReport {
+            api_version,
+            site_timezone: settings.timezone,
+            site_offset,
+            projects: projects.len(),
+            people: people.len(),
+            cache_primed: self.metadata_cache.is_some(),
+            elapsed: started.elapsed(),
+        })
+    }
+
+    /// API version the site announces, or `None` if it has no `api.xml`
+    pub async fn api_version(&self) -> Result<Option<ApiVersion>, ResponseError> {
+        let url = Url::parse(&self.url)?.join("api.xml")?;
+        self.acquire_permit().await;
+        let response = self.client.get(url).send().await?;
+        if !response.status().is_success() {
+            return Ok(None);
+        }
+        Ok(parse_api_xml(&response.text().await?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_api_xml() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<response><version>8</version><minversion>1</minversion><url>api.asp?</url></response>"#;
+        assert_eq!(
+            parse_api_xml(xml),
+            Some(ApiVersion {
+                version: 8,
+                min_version: 1
+            })
+        );
+        assert_eq!(parse_api_xml("<html>Not found</html>"), None);
+    }
+}
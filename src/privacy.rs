@@ -0,0 +1,190 @@
+use bon::Builder;
+use regex::{Regex, RegexBuilder};
+use serde_json::Value;
+
+use crate::{FogBugzClient, ResponseError, budget::Budget, enums::Column, organization::Person};
+
+/// Find a person's email address in case titles and events and redact or flag it,
+/// for GDPR erasure requests.
+///
+/// FogBugz can't rewrite past events, so each affected case gets one edit: the title
+/// is redacted if it contains the address, and a comment lists the events that do,
+/// so an administrator can delete them.
+#[derive(Debug, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct ScrubPersonData {
+    /// `ixPerson` of the person whose data should be erased (required)
+    person_id: u32,
+    /// Text that replaces the email address in titles
+    #[builder(into, default = "[redacted]".to_string())]
+    replacement: String,
+    /// Report what would be changed without editing any case
+    #[builder(default)]
+    dry_run: bool,
+    /// Limit on the cases searched, in case the address is unexpectedly common
+    budget: Option<Budget>,
+    /// API instance
+    client: FogBugzClient,
+}
+
+/// What was done (or would be done) to one case
+#[derive(Debug)]
+pub struct ScrubbedCase {
+    pub case_id: u64,
+    /// The title contained the address and was redacted
+    pub title_redacted: bool,
+    /// Events whose text contains the address
+    pub event_ids: Vec<u64>,
+    /// Result of the edit; `None` in dry-run mode
+    pub result: Option<Result<(), ResponseError>>,
+}
+
+#[derive(Debug)]
+pub struct ScrubReport {
+    pub person_id: u32,
+    pub email: String,
+    pub dry_run: bool,
+    pub cases: Vec<ScrubbedCase>,
+}
+
+impl ScrubReport {
+    pub fn failed(&self) -> usize {
+        self.cases
+            .iter()
+            .filter(|case| matches!(case.result, Some(Err(_))))
+            .count()
+    }
+}
+
+/// Case-insensitive matcher for an email address
+fn email_pattern(email: &str) -> Regex {
+    RegexBuilder::new(&regex::escape(email))
+        .case_insensitive(true)
+        .build()
+        .expect("escaped pattern is valid")
+}
+
+/// Affected title and events of a case as returned by search with the `events` column
+fn scan_case(case: &Value, pattern: &Regex, replacement: &str) -> (Option<String>, Vec<u64>) {
+    let title = case["sTitle"].as_str().unwrap_or_default();
+    let redacted_title = pattern
+        .is_match(title)
+        .then(|| pattern.replace_all(title, replacement).into_owned());
+    let event_ids = case["events"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|event| pattern.is_match(event["s"].as_str().unwrap_or_default()))
+        .filter_map(|event| event["ixBugEvent"].as_u64())
+        .collect();
+    (redacted_title, event_ids)
+}
+
+fn annotation(person: &Person, event_ids: &[u64]) -> String {
+    let ids: Vec<String> = event_ids.iter().map(|id| id.to_string()).collect();
+    format!(
+        "Personal data of person #{} is subject to an erasure request. Events containing it: {}",
+        person.id,
+        if ids.is_empty() {
+            "none".to_string()
+        } else {
+            ids.join(", ")
+        }
+    )
+}
+
+impl ScrubPersonData {
+    pub async fn send(&self) -> Result<ScrubReport, ResponseError> {
+        let people = self.client.list_people().await?;
+        let person = people
+            .iter()
+            .find(|person| person.id == self.person_id)
+            .ok_or_else(|| ResponseError::PersonNotFound(self.person_id.to_string()))?;
+        let mut report = ScrubReport {
+            person_id: person.id,
+            email: person.email.clone(),
+            dry_run: self.dry_run,
+            cases: Vec::new(),
+        };
+        if person.email.trim().is_empty() {
+            return Ok(report);
+        }
+
+        let pattern = email_pattern(person.email.trim());
+        let cases = self
+            .client
+            .search()
+            .query(format!("\"{}\"", person.email.trim()))
+            .cols(vec![
+                Column::CaseId.to_string(),
+                Column::Title.to_string(),
+                Column::Events.to_string(),
+            ])
+            .maybe_budget(self.budget)
+            .build()
+            .send_all()
+            .await?;
+
+        for case in &cases {
+            let Some(case_id) = case["ixBug"].as_u64() else {
+                continue;
+            };
+            let (redacted_title, event_ids) = scan_case(case, &pattern, &self.replacement);
+            if redacted_title.is_none() && event_ids.is_empty() {
+                continue;
+            }
+            let result = if self.dry_run {
+                None
+            } else {
+                let edit = self
+                    .client
+                    .edit_case()
+                    .case_id(case_id)
+                    .maybe_title(redacted_title.clone())
+                    .event(annotation(person, &event_ids))
+                    .build()
+                    .send()
+                    .await;
+                Some(edit.map(|_| ()))
+            };
+            report.cases.push(ScrubbedCase {
+                case_id,
+                title_redacted: redacted_title.is_some(),
+                event_ids,
+                result,
+            });
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_case_finds_email_case_insensitively() {
+        let case = serde_json::json!({
+            "ixBug": 62020,
+            "sTitle": "Login fails for Jane.Doe@example.com",
+            "events": [
+                { "ixBugEvent": 1, "s": "Reported by jane.doe@example.com" },
+                { "ixBugEvent": 2, "s": "Looking into it" },
+                { "ixBugEvent": 3, "s": "Replied to JANE.DOE@EXAMPLE.COM" }
+            ]
+        });
+        let pattern = email_pattern("jane.doe@example.com");
+        let (title, events) = scan_case(&case, &pattern, "[redacted]");
+        assert_eq!(title.as_deref(), Some("Login fails for [redacted]"));
+        assert_eq!(events, vec![1, 3]);
+
+        // The dot in the address must not match any character
+        let (title, events) = scan_case(
+            &serde_json::json!({ "sTitle": "janexdoe@example.com", "events": [] }),
+            &pattern,
+            "[redacted]",
+        );
+        assert_eq!(title, None);
+        assert!(events.is_empty());
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    fmt,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Snapshot of a long-running, multi-request operation
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    /// Items finished so far (successfully or not)
+    pub done: usize,
+    pub total: usize,
+    /// Description of the item just processed
+    pub current: Option<String>,
+    /// Estimated time until the operation completes
+    pub eta: Option<Duration>,
+}
+
+impl Progress {
+    pub fn is_finished(&self) -> bool {
+        self.done >= self.total
+    }
+}
+
+/// Callback invoked after every processed item of a long operation
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(&Progress) + Send + Sync>);
+
+impl ProgressCallback {
+    pub fn new(callback: impl Fn(&Progress) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+}
+
+impl<F: Fn(&Progress) + Send + Sync + 'static> From<F> for ProgressCallback {
+    fn from(callback: F) -> Self {
+        Self::new(callback)
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
+
+/// Counts processed items and reports them to an optional callback
+pub(crate) struct ProgressTracker<'a> {
+    callback: Option<&'a ProgressCallback>,
+    total: usize,
+    done: usize,
+    started: Instant,
+    /// Fastest pace the rate limiter allows, if any
+    min_interval: Option<Duration>,
+}
+
+impl<'a> ProgressTracker<'a> {
+    pub(crate) fn new(
+        callback: Option<&'a ProgressCallback>,
+        total: usize,
+        min_interval: Option<Duration>,
+    ) -> Self {
+        Self {
+            callback,
+            total,
+            done: 0,
+            started: Instant::now(),
+            min_interval,
+        }
+    }
+
+    /// Record one more finished item and notify the callback
+    pub(crate) fn advance(&mut self, current: impl fmt::Display) {
+        self.done += 1;
+        if let Some(callback) = self.callback {
+            let progress = Progress {
+                done: self.done,
+                total: self.total,
+                current: Some(current.to_string()),
+                eta: self.eta(self.started.elapsed()),
+            };
+            (callback.0)(&progress);
+        }
+    }
+
+    /// Remaining items at the observed pace, but never faster than the rate
+    /// limiter would let them through
+    fn eta(&self, elapsed: Duration) -> Option<Duration> {
+        let remaining = self.total.saturating_sub(self.done) as u32;
+        if remaining == 0 {
+            return Some(Duration::ZERO);
+        }
+        let observed = (self.done > 0).then(|| elapsed / self.done as u32);
+        let per_item = match (observed, self.min_interval) {
+            (Some(observed), Some(min)) => observed.max(min),
+            (observed, min) => observed.or(min)?,
+        };
+        Some(per_item * remaining)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    #[test]
+    fn test_eta_respects_rate_limiter() {
+        let mut tracker = ProgressTracker::new(None, 10, Some(Duration::from_secs(1)));
+        assert_eq!(tracker.eta(Duration::ZERO), Some(Duration::from_secs(10)));
+
+        tracker.done = 2;
+        // Observed pace is faster than the limiter allows
+        assert_eq!(
+            tracker.eta(Duration::from_millis(200)),
+            Some(Duration::from_secs(8))
+        );
+        // Observed pace is slower than the limiter
+        assert_eq!(
+            tracker.eta(Duration::from_secs(6)),
+            Some(Duration::from_secs(24))
+        );
+
+        let unlimited = ProgressTracker::new(None, 10, None);
+        assert_eq!(unlimited.eta(Duration::ZERO), None);
+    }
+
+    #[test]
+    fn test_tracker_reports_each_item() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let callback = ProgressCallback::new(move |progress: &Progress| {
+            sink.lock().unwrap().push(progress.clone());
+        });
+
+        let mut tracker = ProgressTracker::new(Some(&callback), 2, None);
+        tracker.advance("case 1");
+        tracker.advance("case 2");
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 2);
+        assert_eq!(seen[0].done, 1);
+        assert_eq!(seen[0].current.as_deref(), Some("case 1"));
+        assert!(seen[1].is_finished());
+        assert_eq!(seen[1].eta, Some(Duration::ZERO));
+    }
+}
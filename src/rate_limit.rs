@@ -0,0 +1,156 @@
+use std::time::Duration;
+
+use tokio::{sync::Mutex, time::Instant};
+
+/// Slowest rate a `RateLimit` paces at, one request an hour; zero, negative
+/// and NaN rates are raised to it
+pub const MIN_REQUESTS_PER_SECOND: f64 = 1.0 / 3600.0;
+
+/// How fast a client may send requests: a steady rate plus a burst allowance.
+/// Shared by all clones of the client it is configured on.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    pub requests_per_second: f64,
+    /// Requests that may be sent at once after a quiet period
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// At least `MIN_REQUESTS_PER_SECOND`
+    pub fn per_second(requests_per_second: f64) -> Self {
+        Self {
+            requests_per_second: clamp_rate(requests_per_second),
+            burst: 1,
+        }
+    }
+
+    pub fn per_minute(requests_per_minute: f64) -> Self {
+        Self::per_second(requests_per_minute / 60.0)
+    }
+
+    pub fn with_burst(self, burst: u32) -> Self {
+        Self {
+            burst: burst.max(1),
+            ..self
+        }
+    }
+
+    /// Average gap between requests
+    pub fn interval(&self) -> Duration {
+        Duration::from_secs_f64(1.0 / self.rate())
+    }
+
+    /// `requests_per_second`, raised to `MIN_REQUESTS_PER_SECOND` if set lower
+    /// on the field directly
+    fn rate(&self) -> f64 {
+        clamp_rate(self.requests_per_second)
+    }
+}
+
+fn clamp_rate(requests_per_second: f64) -> f64 {
+    if requests_per_second >= MIN_REQUESTS_PER_SECOND {
+        requests_per_second
+    } else {
+        MIN_REQUESTS_PER_SECOND
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+/// Token bucket enforcing a `RateLimit`; waiters are served in order
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    limit: RateLimit,
+    bucket: Mutex<Bucket>,
+}
+
+impl TokenBucket {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        Self {
+            limit,
+            bucket: Mutex::new(Bucket {
+                tokens: limit.burst.max(1) as f64,
+                refilled: Instant::now(),
+            }),
+        }
+    }
+
+    pub(crate) fn limit(&self) -> RateLimit {
+        self.limit
+    }
+
+    /// Wait until a request may be sent
+    pub(crate) async fn acquire(&self) {
+        // Holding the lock while sleeping makes later callers queue behind this one
+        let mut bucket = self.bucket.lock().await;
+        self.refill(&mut bucket);
+        if bucket.tokens < 1.0 {
+            let missing = 1.0 - bucket.tokens;
+            tokio::time::sleep(self.limit.interval().mul_f64(missing)).await;
+            self.refill(&mut bucket);
+        }
+        bucket.tokens = (bucket.tokens - 1.0).max(0.0);
+    }
+
+    /// Block the thread until a request may be sent; not for use inside an async runtime
+    #[cfg(feature = "blocking")]
+    pub(crate) fn acquire_blocking(&self) {
+        let mut bucket = self.bucket.blocking_lock();
+        self.refill(&mut bucket);
+        if bucket.tokens < 1.0 {
+            let missing = 1.0 - bucket.tokens;
+            std::thread::sleep(self.limit.interval().mul_f64(missing));
+            self.refill(&mut bucket);
+        }
+        bucket.tokens = (bucket.tokens - 1.0).max(0.0);
+    }
+
+    fn refill(&self, bucket: &mut Bucket) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(bucket.refilled).as_secs_f64();
+        bucket.tokens =
+            (bucket.tokens + elapsed * self.limit.rate()).min(self.limit.burst.max(1) as f64);
+        bucket.refilled = now;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_token_bucket_allows_burst_then_paces() {
+        let bucket = TokenBucket::new(RateLimit::per_second(50.0).with_burst(2));
+        let started = Instant::now();
+        bucket.acquire().await;
+        bucket.acquire().await;
+        assert_eq!(started.elapsed(), Duration::ZERO);
+        bucket.acquire().await;
+        bucket.acquire().await;
+        assert!(started.elapsed() >= Duration::from_millis(40));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_zero_rate_paces_at_minimum() {
+        for limit in [
+            RateLimit::per_second(0.0),
+            RateLimit::per_minute(-5.0),
+            RateLimit::per_second(f64::NAN),
+            RateLimit {
+                requests_per_second: 0.0,
+                burst: 1,
+            },
+        ] {
+            assert_eq!(limit.interval(), Duration::from_secs(3600));
+            let bucket = TokenBucket::new(limit);
+            let started = Instant::now();
+            bucket.acquire().await;
+            bucket.acquire().await;
+            assert!(started.elapsed() >= Duration::from_secs(3599));
+        }
+    }
+}
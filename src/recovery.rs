@@ -0,0 +1,209 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    FogBugzClient, ResponseError,
+    api_client::FogBugzApiError,
+    case_management::CaseResponse,
+    enums::Column,
+    filter::{FogBugzSearchBuilder, StatusFilter},
+    list_cases::CaseSummary,
+    organization::Status,
+};
+
+/// Columns returned for cases found by the recovery searches
+const RECOVERY_COLUMNS: [Column; 7] = [
+    Column::CaseId,
+    Column::Title,
+    Column::Project,
+    Column::IsOpen,
+    Column::Status,
+    Column::StatusName,
+    Column::DateClosed,
+];
+
+/// What `recover_case` did to bring a case back
+#[derive(Debug, Clone)]
+pub enum Recovery {
+    /// The case was closed and is open again
+    Reopened(CaseResponse),
+    /// The case was resolved, e.g. as won't fix, and is active again
+    Reactivated(CaseResponse),
+    /// The case was already active; nothing was sent
+    AlreadyActive,
+}
+
+/// Query matching any of the statuses FogBugz treats as deleting the case
+/// (`fDeleted`, e.g. "Won't Fix"), or `None` if the site has none
+fn deleted_statuses_query(statuses: &[Status]) -> Option<String> {
+    let names: BTreeSet<&str> = statuses
+        .iter()
+        .filter(|status| status.is_deleted)
+        .map(|status| status.name.as_str())
+        .collect();
+    if names.is_empty() {
+        return None;
+    }
+    let query = FogBugzSearchBuilder::new()
+        .or(|or| names.iter().fold(or, |or, name| or.axis("status", name)))
+        .build();
+    Some(query)
+}
+
+/// Find cases that normal searches hide from recovery tooling, and bring them back.
+/// FogBugz doesn't remove cases; "deleted" cases are ones resolved with a status
+/// flagged `fDeleted`.
+impl FogBugzClient {
+    /// Closed cases, optionally narrowed by another search query
+    pub async fn search_closed_cases(
+        &self,
+        query: Option<&str>,
+    ) -> Result<Vec<CaseSummary>, ResponseError> {
+        let closed = FogBugzSearchBuilder::new()
+            .status(StatusFilter::Closed)
+            .build();
+        self.search_recovery(closed, query).await
+    }
+
+    /// Cases resolved or closed with a deleting status, optionally narrowed by
+    /// another search query
+    pub async fn search_deleted_cases(
+        &self,
+        query: Option<&str>,
+    ) -> Result<Vec<CaseSummary>, ResponseError> {
+        let statuses = self.list_statuses(None).await?;
+        let Some(deleted) = deleted_statuses_query(&statuses) else {
+            return Ok(Vec::new());
+        };
+        self.search_recovery(deleted, query).await
+    }
+
+    async fn search_recovery(
+        &self,
+        mut search: String,
+        query: Option<&str>,
+    ) -> Result<Vec<CaseSummary>, ResponseError> {
+        if let Some(extra) = query.filter(|extra| !extra.trim().is_empty()) {
+            search = format!("{search} ({extra})");
+        }
+        let cases = self
+            .search()
+            .query(search)
+            .cols(RECOVERY_COLUMNS.iter().map(Column::to_string).collect())
+            .build()
+            .send_all()
+            .await?;
+        ResponseError::deserialize_cases(serde_json::Value::Array(cases))
+    }
+
+    /// Make a closed or resolved (including deleted) case active again:
+    /// closed cases are reopened, resolved ones reactivated
+    pub async fn recover_case(
+        &self,
+        case_id: u64,
+        event: Option<&str>,
+    ) -> Result<Recovery, ResponseError> {
+        let cases = self
+            .search()
+            .query(case_id.to_string())
+            .cols(vec![
+                Column::CaseId.to_string(),
+                Column::IsOpen.to_string(),
+                Column::Status.to_string(),
+            ])
+            .build()
+            .send()
+            .await?;
+        let case = cases["data"]["cases"]
+            .as_array()
+            .and_then(|cases| {
+                cases
+                    .iter()
+                    .find(|case| case[Column::CaseId.as_ref()].as_u64() == Some(case_id))
+            })
+            .cloned()
+            .ok_or_else(|| FogBugzApiError::CaseNotFound {
+                message: format!("Case {case_id} not found"),
+            })?;
+
+        if case[Column::IsOpen.as_ref()].as_bool() == Some(false) {
+            let response = self
+                .reopen_case()
+                .case_id(case_id)
+                .maybe_event(event)
+                .build()
+                .send()
+                .await?;
+            return Ok(Recovery::Reopened(response));
+        }
+
+        let status_id = case[Column::Status.as_ref()].as_u64();
+        let statuses = self.list_statuses(None).await?;
+        let is_resolved = statuses
+            .iter()
+            .any(|status| Some(u64::from(status.id)) == status_id && status.is_resolved);
+        if !is_resolved {
+            return Ok(Recovery::AlreadyActive);
+        }
+        let response = self
+            .reactivate_case()
+            .case_id(case_id)
+            .maybe_event(event)
+            .build()
+            .send()
+            .await?;
+        Ok(Recovery::Reactivated(response))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deleted_statuses_query() {
+        let statuses: Vec<Status> = serde_json::from_value(serde_json::json!([
+            { "ixStatus": 2, "sStatus": "Resolved (Fixed)", "ixCategory": 1, "fResolved": true,
+              "fDuplicate": false, "fDeleted": false, "iOrder": 0 },
+            { "ixStatus": 5, "sStatus": "Resolved (Won't Fix)", "ixCategory": 1, "fResolved": true,
+              "fDuplicate": false, "fDeleted": true, "iOrder": 1 },
+            { "ixStatus": 29, "sStatus": "Won't Review", "ixCategory": 7, "fResolved": true,
+              "fDuplicate": false, "fDeleted": true, "iOrder": 3 },
+        ]))
+        .unwrap();
+        assert_eq!(
+            deleted_statuses_query(&statuses).as_deref(),
+            Some("(status:\"Resolved (Won't Fix)\" OR status:\"Won't Review\")")
+        );
+        assert_eq!(deleted_statuses_query(&statuses[..1]), None);
+    }
+
+    #[tokio::test]
+    async fn test_recovery_search_groups_extra_query() {
+        use std::sync::{Arc, Mutex};
+
+        use crate::interceptor::RequestContext;
+
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let log = sent.clone();
+        let client = FogBugzClient::new("http://localhost:1", "token").with_interceptor(
+            move |request: &mut RequestContext<'_>| {
+                log.lock().unwrap().push(request.payload["q"].clone());
+            },
+        );
+        assert!(
+            client
+                .search_closed_cases(Some("project:Inbox OR project:Ops"))
+                .await
+                .is_err()
+        );
+        let closed = FogBugzSearchBuilder::new()
+            .status(StatusFilter::Closed)
+            .build();
+        assert!(
+            sent.lock().unwrap()[0]
+                .as_str()
+                .unwrap()
+                .starts_with(&format!("{closed} (project:Inbox OR project:Ops)"))
+        );
+    }
+}
@@ -0,0 +1,418 @@
+use std::{collections::BTreeMap, fmt};
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+
+use serde_json::Value;
+
+use crate::{
+    FogBugzClient, ResponseError,
+    case_management::EditCaseRequest,
+    enums::{Column, RequiredColumns},
+    filter::{FogBugzSearchBuilder, StatusFilter},
+};
+
+/// An open case that hasn't been updated for a while
+#[derive(Debug, Deserialize, Serialize)]
+pub struct StaleCase {
+    #[serde(rename = "ixBug")]
+    pub case_id: u64,
+    #[serde(rename = "sTitle")]
+    pub title: String,
+    #[serde(rename = "sProject")]
+    pub project: String,
+    #[serde(rename = "sPersonAssignedTo")]
+    pub assigned_to: String,
+    #[serde(rename = "ixPersonAssignedTo")]
+    pub assigned_to_id: u64,
+    #[serde(rename = "dtLastUpdated")]
+    pub last_updated: DateTime<Utc>,
+}
+
+impl RequiredColumns for StaleCase {
+    fn required_columns() -> &'static [Column] {
+        &[
+            Column::CaseId,
+            Column::Title,
+            Column::Project,
+            Column::PersonAssignedTo,
+            Column::PersonAssignedToId,
+            Column::LastUpdated,
+        ]
+    }
+}
+
+impl StaleCase {
+    /// Whole days since the case was last updated
+    pub fn idle_days(&self, now: DateTime<Utc>) -> i64 {
+        (now - self.last_updated).num_days()
+    }
+}
+
+impl fmt::Display for StaleCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} [{}] {} (last updated {})",
+            self.case_id,
+            self.project,
+            self.title,
+            self.last_updated.format("%Y-%m-%d")
+        )
+    }
+}
+
+/// Open cases untouched for at least `idle_days`, grouped by assignee name
+#[derive(Debug, Serialize)]
+pub struct StaleCasesReport {
+    pub idle_days: u32,
+    pub cutoff: DateTime<Utc>,
+    pub by_assignee: BTreeMap<String, Vec<StaleCase>>,
+}
+
+impl StaleCasesReport {
+    fn new(cases: Vec<StaleCase>, idle_days: u32, now: DateTime<Utc>) -> Self {
+        let cutoff = now - Duration::days(idle_days.into());
+        let mut by_assignee: BTreeMap<String, Vec<StaleCase>> = BTreeMap::new();
+        for case in cases.into_iter().filter(|case| case.last_updated <= cutoff) {
+            by_assignee
+                .entry(case.assigned_to.clone())
+                .or_default()
+                .push(case);
+        }
+        for cases in by_assignee.values_mut() {
+            cases.sort_by_key(|case| case.last_updated);
+        }
+        Self {
+            idle_days,
+            cutoff,
+            by_assignee,
+        }
+    }
+
+    /// Total number of stale cases across all assignees
+    pub fn total(&self) -> usize {
+        self.by_assignee.values().map(Vec::len).sum()
+    }
+
+    /// Build one comment (edit) request per stale case, e.g. for automated nagging.
+    /// Nothing is sent until the caller calls `send()` on the requests.
+    pub fn nag_requests(
+        &self,
+        client: &FogBugzClient,
+        message: impl Fn(&StaleCase) -> String,
+    ) -> Vec<EditCaseRequest> {
+        self.by_assignee
+            .values()
+            .flatten()
+            .map(|case| {
+                client
+                    .edit_case()
+                    .case_id(case.case_id)
+                    .event(message(case))
+                    .build()
+            })
+            .collect()
+    }
+}
+
+/// How a report's result is written out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// The groups and their cases as one JSON document
+    #[default]
+    Json,
+    /// One case per line, with its group under `group`
+    Ndjson,
+    /// A header row of column names, then one row per case
+    Csv,
+}
+
+/// A report kept as configuration, e.g. a library of JSON files in git:
+/// which cases, which columns, how they're grouped and the output format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Definition {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The search, stored as builder components so it can be edited structurally
+    pub query: FogBugzSearchBuilder,
+    /// Columns of each case; `ixBug` is always included
+    #[serde(default)]
+    pub columns: Vec<Column>,
+    /// Column whose value groups the cases (optional)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group_by: Option<Column>,
+    #[serde(default)]
+    pub format: OutputFormat,
+}
+
+impl Definition {
+    /// Columns to request: `ixBug`, the report's columns, then the grouping column
+    fn request_columns(&self) -> Vec<Column> {
+        let mut columns = vec![Column::CaseId];
+        for column in self.columns.iter().chain(self.group_by.as_ref()) {
+            if !columns.contains(column) {
+                columns.push(*column);
+            }
+        }
+        columns
+    }
+
+    pub async fn run(&self, client: &FogBugzClient) -> Result<ReportResult, ResponseError> {
+        let cases = client
+            .search()
+            .query(self.query.to_string())
+            .cols(
+                self.request_columns()
+                    .iter()
+                    .map(Column::to_string)
+                    .collect(),
+            )
+            .build()
+            .send_all()
+            .await?;
+        Ok(ReportResult::new(self, cases))
+    }
+}
+
+/// Cases sharing a value of the grouping column
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReportGroup {
+    /// `None` when the report isn't grouped
+    pub key: Option<String>,
+    pub cases: Vec<Value>,
+}
+
+/// Result of running a `Definition`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ReportResult {
+    pub name: String,
+    pub columns: Vec<Column>,
+    #[serde(skip)]
+    pub format: OutputFormat,
+    /// By group key
+    pub groups: Vec<ReportGroup>,
+}
+
+/// Text of a cell; lists are joined with commas
+fn cell_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(cell_text).collect::<Vec<_>>().join(","),
+        other => other.to_string(),
+    }
+}
+
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+impl ReportResult {
+    fn new(definition: &Definition, cases: Vec<Value>) -> Self {
+        let groups = match definition.group_by {
+            Some(column) => {
+                let mut groups: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+                for case in cases {
+                    groups
+                        .entry(cell_text(&case[column.as_ref()]))
+                        .or_default()
+                        .push(case);
+                }
+                groups
+                    .into_iter()
+                    .map(|(key, cases)| ReportGroup {
+                        key: Some(key),
+                        cases,
+                    })
+                    .collect()
+            }
+            None => vec![ReportGroup { key: None, cases }],
+        };
+        Self {
+            name: definition.name.clone(),
+            columns: definition.request_columns(),
+            format: definition.format,
+            groups,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.groups.iter().map(|group| group.cases.len()).sum()
+    }
+
+    /// The result in the definition's output format
+    pub fn render(&self) -> Result<String, serde_json::Error> {
+        match self.format {
+            OutputFormat::Json => serde_json::to_string_pretty(self),
+            OutputFormat::Ndjson => {
+                let mut lines = String::new();
+                for group in &self.groups {
+                    for case in &group.cases {
+                        let mut line = case.clone();
+                        if let (Some(key), Value::Object(fields)) = (&group.key, &mut line) {
+                            fields.insert("group".to_string(), key.clone().into());
+                        }
+                        lines.push_str(&serde_json::to_string(&line)?);
+                        lines.push('\n');
+                    }
+                }
+                Ok(lines)
+            }
+            OutputFormat::Csv => {
+                let header: Vec<String> = self
+                    .columns
+                    .iter()
+                    .map(|column| csv_field(column.as_ref()))
+                    .collect();
+                let mut csv = header.join(",") + "\n";
+                for case in self.groups.iter().flat_map(|group| &group.cases) {
+                    let row: Vec<String> = self
+                        .columns
+                        .iter()
+                        .map(|column| csv_field(&cell_text(&case[column.as_ref()])))
+                        .collect();
+                    csv.push_str(&row.join(","));
+                    csv.push('\n');
+                }
+                Ok(csv)
+            }
+        }
+    }
+}
+
+/// Search for open cases matching `filter` last updated by the day after
+/// `cutoff`. FogBugz reads the date in the user's time zone, so the search runs
+/// a day late; `StaleCasesReport::new` drops the cases updated after `cutoff`.
+fn stale_query(filter: &str, cutoff: DateTime<Utc>) -> String {
+    let until = cutoff + Duration::days(1);
+    let mut query = FogBugzSearchBuilder::new()
+        .status(StatusFilter::Open)
+        .edited_date(&format!("..{}", until.format("%m/%d/%Y")))
+        .build();
+    if !filter.trim().is_empty() {
+        query = format!("({}) {query}", filter.trim());
+    }
+    query
+}
+
+impl FogBugzClient {
+    /// Run a saved report definition
+    pub async fn run_report(&self, definition: &Definition) -> Result<ReportResult, ResponseError> {
+        definition.run(self).await
+    }
+
+    /// Find open cases matching `filter` (a FogBugz search query) that haven't been
+    /// updated for at least `idle_days`, grouped by assignee
+    pub async fn stale_cases(
+        &self,
+        filter: &str,
+        idle_days: u32,
+    ) -> Result<StaleCasesReport, ResponseError> {
+        let now = Utc::now();
+        let cases = self
+            .search()
+            .query(stale_query(filter, now - Duration::days(idle_days.into())))
+            .cols(Vec::new())
+            .build()
+            .require_columns_for::<StaleCase>()
+            .send_all()
+            .await?;
+        let cases: Vec<StaleCase> = ResponseError::deserialize_cases(Value::Array(cases))?;
+        Ok(StaleCasesReport::new(cases, idle_days, now))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn case(case_id: u64, assigned_to: &str, last_updated: &str) -> StaleCase {
+        serde_json::from_value(serde_json::json!({
+            "ixBug": case_id,
+            "sTitle": format!("Case {}", case_id),
+            "sProject": "Widget Factory",
+            "sPersonAssignedTo": assigned_to,
+            "ixPersonAssignedTo": 1,
+            "dtLastUpdated": last_updated
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_stale_cases_grouped_by_assignee() {
+        let now = "2024-03-31T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let cases = vec![
+            case(1, "Jane Doe", "2024-03-30T12:00:00Z"),
+            case(2, "Jane Doe", "2024-01-01T12:00:00Z"),
+            case(3, "John Smith", "2024-02-01T12:00:00Z"),
+            case(4, "Jane Doe", "2023-12-01T12:00:00Z"),
+        ];
+
+        let report = StaleCasesReport::new(cases, 30, now);
+
+        assert_eq!(report.total(), 3);
+        let jane: Vec<u64> = report.by_assignee["Jane Doe"]
+            .iter()
+            .map(|case| case.case_id)
+            .collect();
+        // Oldest first
+        assert_eq!(jane, vec![4, 2]);
+        assert_eq!(report.by_assignee["John Smith"][0].idle_days(now), 59);
+    }
+
+    #[test]
+    fn test_stale_query_puts_cutoff_in_search() {
+        let cutoff = "2024-03-01T12:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        assert_eq!(
+            stale_query("project:Inbox OR project:Ops", cutoff),
+            "(project:Inbox OR project:Ops) status:open edited:\"..03/02/2024\""
+        );
+        assert_eq!(
+            stale_query(" ", cutoff),
+            "status:open edited:\"..03/02/2024\""
+        );
+    }
+
+    #[test]
+    fn test_definition_groups_and_renders() {
+        let definition: Definition = serde_json::from_value(serde_json::json!({
+            "name": "Open bugs by area",
+            "query": [
+                { "axis": { "axis": "project", "query": "Widget" } },
+                { "axis": { "axis": "status", "query": "open" } }
+            ],
+            "columns": ["sTitle", "sArea"],
+            "group_by": "sArea",
+            "format": "csv"
+        }))
+        .unwrap();
+        assert_eq!(definition.query.to_string(), "project:Widget status:open");
+        assert_eq!(
+            definition.request_columns(),
+            vec![Column::CaseId, Column::Title, Column::Area]
+        );
+
+        let cases = vec![
+            serde_json::json!({ "ixBug": 2, "sTitle": "Crash, again", "sArea": "UI" }),
+            serde_json::json!({ "ixBug": 1, "sTitle": "Slow", "sArea": "Backend" }),
+        ];
+        let result = ReportResult::new(&definition, cases);
+        assert_eq!(result.total(), 2);
+        assert_eq!(result.groups[0].key.as_deref(), Some("Backend"));
+        assert_eq!(
+            result.render().unwrap(),
+            "ixBug,sTitle,sArea\n1,Slow,Backend\n2,\"Crash, again\",UI\n"
+        );
+
+        let restored: Definition =
+            serde_json::from_str(&serde_json::to_string(&definition).unwrap()).unwrap();
+        assert_eq!(restored, definition);
+    }
+}
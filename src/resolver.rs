@@ -0,0 +1,75 @@
+use crate::{
+    FogBugzClient, ResponseError,
+    organization::{PersonRef, Project},
+};
+
+/// The first item whose name matches `name`, ignoring case and surrounding whitespace
+fn find_named<'a, T>(items: &'a [T], name: &str, name_of: impl Fn(&T) -> &str) -> Option<&'a T> {
+    let needle = name.trim();
+    items
+        .iter()
+        .find(|item| name_of(item).eq_ignore_ascii_case(needle))
+}
+
+/// Turn the names people type into the IDs commands need. Lookups go through
+/// the metadata lists, so configure `metadata_cache_ttl` to avoid a request per call.
+impl FogBugzClient {
+    /// `ixPerson` of a person, by full name or email
+    pub async fn resolve_person(&self, name_or_email: &str) -> Result<u32, ResponseError> {
+        self.resolve_person_ref(&PersonRef::Name(name_or_email.to_string()))
+            .await
+    }
+
+    /// `ixProject` of a project that isn't deleted, by name
+    pub async fn resolve_project(&self, name: &str) -> Result<u32, ResponseError> {
+        let projects: Vec<Project> = self
+            .list_projects()
+            .await?
+            .into_iter()
+            .filter(|project| !project.is_deleted)
+            .collect();
+        find_named(&projects, name, |project| &project.name)
+            .map(|project| project.id)
+            .ok_or_else(|| ResponseError::ProjectNotFound(name.to_string()))
+    }
+
+    /// `ixFixFor` of a milestone usable in a project, by project and milestone name
+    pub async fn resolve_milestone(&self, project: &str, name: &str) -> Result<u32, ResponseError> {
+        let project_id = self.resolve_project(project).await?;
+        let milestones = self.list_milestones(Some(project_id)).await?;
+        let milestones: Vec<_> = milestones
+            .into_iter()
+            .filter(|milestone| !milestone.is_deleted)
+            .collect();
+        find_named(&milestones, name, |milestone| &milestone.name)
+            .map(|milestone| milestone.id)
+            .ok_or_else(|| ResponseError::MilestoneNotFound(format!("{project}: {name}")))
+    }
+
+    /// `ixArea` of an area of a project, by project and area name
+    pub async fn resolve_area(&self, project: &str, name: &str) -> Result<u32, ResponseError> {
+        let project_id = self.resolve_project(project).await?;
+        let areas = self.list_areas(Some(project_id)).await?;
+        find_named(&areas, name, |area| &area.name)
+            .map(|area| area.id)
+            .ok_or_else(|| ResponseError::AreaNotFound(format!("{project}: {name}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::organization::Area;
+
+    #[test]
+    fn test_find_named() {
+        let areas: Vec<Area> = serde_json::from_value(serde_json::json!([
+            { "ixArea": 1, "sArea": "Misc", "ixProject": 2, "ixPersonOwner": 0, "sPersonOwner": "", "nType": 0 },
+            { "ixArea": 3, "sArea": "Billing", "ixProject": 2, "ixPersonOwner": 0, "sPersonOwner": "", "nType": 0 },
+        ]))
+        .unwrap();
+        let found = find_named(&areas, " billing ", |area| &area.name);
+        assert_eq!(found.map(|area| area.id), Some(3));
+        assert!(find_named(&areas, "Bill", |area| &area.name).is_none());
+    }
+}
@@ -0,0 +1,202 @@
+use std::{
+    collections::hash_map::RandomState,
+    future::Future,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use crate::{FogBugzClient, ResponseError, api_client::FogBugzApiError};
+
+/// How to retry requests that failed for a transient reason: 5xx responses,
+/// dropped connections, timeouts and FogBugz "server too busy" errors.
+/// Every command is retried, so a create whose response was lost may be
+/// applied twice; keep `max_retries` low for such workloads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+    /// Delay before the first retry
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between attempts
+    pub max_backoff: Duration,
+    /// Factor the delay grows by after each retry
+    pub multiplier: f64,
+    /// Random share (0.0-1.0) taken off each delay so clients don't retry in lockstep
+    pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.2,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32) -> Self {
+        Self {
+            max_retries,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_initial_backoff(self, initial_backoff: Duration) -> Self {
+        Self {
+            initial_backoff,
+            ..self
+        }
+    }
+
+    pub fn with_max_backoff(self, max_backoff: Duration) -> Self {
+        Self {
+            max_backoff,
+            ..self
+        }
+    }
+
+    pub fn with_multiplier(self, multiplier: f64) -> Self {
+        Self { multiplier, ..self }
+    }
+
+    pub fn with_jitter(self, jitter: f64) -> Self {
+        Self {
+            jitter: jitter.clamp(0.0, 1.0),
+            ..self
+        }
+    }
+
+    /// Delay before retry number `retry` (0-based), before jitter
+    fn backoff(&self, retry: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .max(1.0)
+            .powi(retry.min(i32::MAX as u32) as i32);
+        self.initial_backoff
+            .mul_f64(factor.min(u32::MAX.into()))
+            .min(self.max_backoff)
+    }
+
+    pub(crate) fn delay(&self, retry: u32) -> Duration {
+        self.backoff(retry)
+            .mul_f64(1.0 - self.jitter * random_fraction())
+    }
+}
+
+/// Uniform-ish number in 0.0..1.0, good enough to spread retries
+fn random_fraction() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(0);
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// Whether an error is likely to go away if the request is sent again
+pub(crate) fn is_transient(error: &ResponseError) -> bool {
+    match error {
+        ResponseError::RequestError(e) => {
+            e.is_connect()
+                || e.is_timeout()
+                || e.status().is_some_and(|status| status.is_server_error())
+                || is_connection_reset(e)
+        }
+        ResponseError::FogbugzError(FogBugzApiError::RateLimited { .. }) => true,
+        ResponseError::FogbugzError(FogBugzApiError::ServerError { status, .. }) => *status >= 500,
+        _ => false,
+    }
+}
+
+fn is_connection_reset(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = error.source();
+    while let Some(error) = source {
+        if let Some(io) = error.downcast_ref::<std::io::Error>() {
+            return matches!(
+                io.kind(),
+                std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::UnexpectedEof
+            );
+        }
+        source = error.source();
+    }
+    false
+}
+
+impl FogBugzClient {
+    /// Run `send`, retrying transient failures according to the client's `retry_policy`
+    pub(crate) async fn with_retries<T, F, Fut>(&self, send: F) -> Result<T, ResponseError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<T, ResponseError>>,
+    {
+        let Some(policy) = &self.retry_policy else {
+            return send().await;
+        };
+        let mut retry = 0;
+        loop {
+            match send().await {
+                Err(error) if retry < policy.max_retries && is_transient(&error) => {
+                    tokio::time::sleep(policy.delay(retry)).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let policy = RetryPolicy::new(5)
+            .with_initial_backoff(Duration::from_millis(100))
+            .with_max_backoff(Duration::from_millis(500));
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(3), Duration::from_millis(500));
+
+        let delay = policy.with_jitter(0.5).delay(1);
+        assert!(delay > Duration::from_millis(100) && delay <= Duration::from_millis(200));
+    }
+
+    #[tokio::test]
+    async fn test_retries_only_transient_errors() {
+        let api = FogBugzClient::builder()
+            .url("https://example.com")
+            .api_key("key")
+            .retry_policy(RetryPolicy::new(2).with_initial_backoff(Duration::ZERO))
+            .build();
+
+        let attempts = AtomicU32::new(0);
+        let result: Result<(), _> = api
+            .with_retries(|| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(FogBugzApiError::RateLimited {
+                    message: "Server too busy".to_string(),
+                }
+                .into())
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        attempts.store(0, Ordering::SeqCst);
+        let result: Result<(), _> = api
+            .with_retries(|| async {
+                attempts.fetch_add(1, Ordering::SeqCst);
+                Err(ResponseError::PersonNotFound("jane".to_string()))
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
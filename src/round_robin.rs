@@ -0,0 +1,113 @@
+use crate::{
+    FogBugzClient, ResponseError,
+    case_management::AssignCaseResponse,
+    progress::{ProgressCallback, ProgressTracker},
+    state::StateStore,
+};
+
+/// Outcome of assigning a single case during a round-robin rotation
+#[derive(Debug)]
+pub struct RoundRobinAssignment {
+    pub case_id: u64,
+    pub person_id: u64,
+    pub result: Result<AssignCaseResponse, ResponseError>,
+}
+
+/// State store key for a rotation over a given set of people.
+/// Different sets of people rotate independently.
+fn rotation_key(person_ids: &[u64]) -> String {
+    let mut ids = person_ids.to_vec();
+    ids.sort_unstable();
+    let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+    format!("round_robin:{}", ids.join(","))
+}
+
+/// Index of the person that should receive the next case, i.e. the one after
+/// the last assigned person (or the first person if nobody was assigned yet)
+fn next_index(person_ids: &[u64], last_assigned: Option<u64>) -> usize {
+    last_assigned
+        .and_then(|last| person_ids.iter().position(|id| *id == last))
+        .map(|position| (position + 1) % person_ids.len())
+        .unwrap_or(0)
+}
+
+impl FogBugzClient {
+    /// Distribute cases evenly across people, continuing the rotation from where the
+    /// previous run (as recorded in `store`) left off.
+    /// A failed assignment does not consume the person's turn.
+    pub async fn assign_round_robin(
+        &self,
+        case_ids: &[u64],
+        person_ids: &[u64],
+        store: &dyn StateStore,
+    ) -> Result<Vec<RoundRobinAssignment>, ResponseError> {
+        self.assign_round_robin_with_progress(case_ids, person_ids, store, None)
+            .await
+    }
+
+    /// Same as `assign_round_robin`, reporting progress after each case
+    pub async fn assign_round_robin_with_progress(
+        &self,
+        case_ids: &[u64],
+        person_ids: &[u64],
+        store: &dyn StateStore,
+        on_progress: Option<&ProgressCallback>,
+    ) -> Result<Vec<RoundRobinAssignment>, ResponseError> {
+        if person_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let key = rotation_key(person_ids);
+        let last_assigned = store
+            .get(&key)
+            .await?
+            .and_then(|value| value.parse::<u64>().ok());
+        let mut index = next_index(person_ids, last_assigned);
+
+        let mut assignments = Vec::with_capacity(case_ids.len());
+        let mut tracker =
+            ProgressTracker::new(on_progress, case_ids.len(), self.min_request_interval());
+        for &case_id in case_ids {
+            let person_id = person_ids[index];
+            let result = self
+                .assign_case()
+                .case_id(case_id)
+                .assigned_to_id(person_id)
+                .build()
+                .send()
+                .await;
+            if result.is_ok() {
+                store.put(&key, person_id.to_string()).await?;
+                index = (index + 1) % person_ids.len();
+            }
+            tracker.advance(format_args!("case {} -> person {}", case_id, person_id));
+            assignments.push(RoundRobinAssignment {
+                case_id,
+                person_id,
+                result,
+            });
+        }
+        Ok(assignments)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_continues_after_last_assigned() {
+        let people = [10, 20, 30];
+        assert_eq!(next_index(&people, None), 0);
+        assert_eq!(next_index(&people, Some(10)), 1);
+        assert_eq!(next_index(&people, Some(30)), 0);
+        // Person removed from the rotation since the last run
+        assert_eq!(next_index(&people, Some(99)), 0);
+    }
+
+    #[test]
+    fn test_rotation_key_ignores_person_order() {
+        assert_eq!(rotation_key(&[3, 1, 2]), rotation_key(&[1, 2, 3]));
+        assert_ne!(rotation_key(&[1, 2]), rotation_key(&[1, 2, 3]));
+    }
+}
@@ -0,0 +1,140 @@
+use bon::Builder;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{FogBugzClient, ResponseError, enums::Column};
+
+/// An automated crash report, submitted the way BugzScout does: reports with the
+/// same `description` are added to one open case as occurrences instead of each
+/// opening a new case.
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct ScoutReport {
+    /// Identifies the crash, e.g. exception type and location; also the title of
+    /// a new case, so keep it free of per-occurrence details (required)
+    #[serde(rename = "sScoutDescription")]
+    #[builder(into)]
+    description: String,
+
+    /// Details of this occurrence, e.g. stack trace, version and machine (required)
+    #[serde(rename = "sEvent")]
+    #[builder(into)]
+    extra: String,
+
+    /// Project name to create the case in (optional)
+    #[serde(rename = "sProject", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    project: Option<String>,
+
+    /// Area name within the project (optional)
+    #[serde(rename = "sArea", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    area: Option<String>,
+
+    /// Full name of the person to assign a new case to (optional)
+    #[serde(rename = "sPersonAssignedTo", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    assigned_to: Option<String>,
+
+    /// Email of the user who hit the crash (optional)
+    #[serde(rename = "sCustomerEmail", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    customer_email: Option<String>,
+
+    /// Message for users who hit the crash, kept on a new case until someone
+    /// edits it in FogBugz (optional)
+    #[serde(rename = "sScoutMessage", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    default_message: Option<String>,
+
+    /// Ask FogBugz to stop counting further occurrences on a new case
+    #[serde(rename = "fScoutStopReporting")]
+    #[builder(default)]
+    stop_reporting: bool,
+
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+/// The case a scout report ended up in, with what to tell the user
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ScoutResponse {
+    #[serde(rename = "ixBug")]
+    pub case_id: u64,
+    /// Message currently set on the case, e.g. "Fixed in 2.1, please upgrade"
+    #[serde(rename = "sScoutMessage", default)]
+    pub message: Option<String>,
+    /// Whether the application should stop sending this report
+    #[serde(rename = "fScoutStopReporting", default)]
+    pub stop_reporting: bool,
+    /// Reports counted on the case so far
+    #[serde(rename = "c", default)]
+    pub occurrences: Option<u32>,
+}
+
+impl ScoutResponse {
+    fn from_response(mut response: Value) -> Result<Self, ResponseError> {
+        let mut response: Self = serde_json::from_value(response["data"]["case"].take())?;
+        // FogBugz returns an empty string for cases without a message
+        response.message = response.message.filter(|message| !message.is_empty());
+        Ok(response)
+    }
+}
+
+impl ScoutReport {
+    /// Submit the report, creating a case or counting an occurrence on the
+    /// existing one
+    pub async fn send(&self) -> Result<ScoutResponse, ResponseError> {
+        let mut params = serde_json::to_value(self)?;
+        params["sTitle"] = self.description.clone().into();
+        params["cols"] = serde_json::json!([
+            Column::ScoutMessage,
+            Column::ScoutStopReporting,
+            Column::Occurrences,
+        ]);
+        let response = self.client.send_command("new", params).await?;
+        ScoutResponse::from_response(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scout_report_params() {
+        let report = ScoutReport::builder()
+            .client(FogBugzClient::new("http://localhost:1", "token"))
+            .description("NullReferenceException in Checkout.Submit")
+            .extra("v2.0.3 on WIN-42")
+            .default_message("We're on it")
+            .build();
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({
+                "sScoutDescription": "NullReferenceException in Checkout.Submit",
+                "sEvent": "v2.0.3 on WIN-42",
+                "sScoutMessage": "We're on it",
+                "fScoutStopReporting": false,
+            })
+        );
+
+        let response = ScoutResponse::from_response(serde_json::json!({
+            "data": { "case": {
+                "ixBug": 42, "sScoutMessage": "", "fScoutStopReporting": true, "c": 7,
+                "operations": ["edit"]
+            }}
+        }))
+        .unwrap();
+        assert_eq!(
+            response,
+            ScoutResponse {
+                case_id: 42,
+                message: None,
+                stop_reporting: true,
+                occurrences: Some(7),
+            }
+        );
+    }
+}
@@ -1,7 +1,21 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
 use bon::Builder;
-use serde::{Deserialize, Serialize};
+use futures::{Stream, StreamExt, TryStreamExt, stream};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use serde_json::Value;
 
-use crate::{FogBugzClient, ResponseError, enums::Column};
+use crate::{
+    FogBugzClient, ResponseError,
+    api_client::search_batches,
+    budget::{Budget, BudgetTracker},
+    enums::{Column, RequiredColumns, add_required_columns},
+    list_cases::Case,
+    paging::{DEFAULT_PAGE_SIZE, pages},
+};
 
 #[derive(Debug, Serialize, Builder)]
 #[builder(state_mod(vis = "pub(crate)"))]
@@ -11,10 +25,140 @@ pub struct SearchRequest {
     query: String,
     #[builder(default = vec![Column::CaseId.to_string(), Column::Title.to_string()])]
     cols: Vec<String>,
+    /// Append `OrderBy:ixBug` as a final tiebreaker so paged results are deterministic
+    #[serde(skip)]
+    #[builder(default)]
+    stable_order: bool,
+    /// Maximum number of cases to return; the page size for `paged()`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max: Option<u32>,
+    /// Offset of the first case to return
+    #[serde(skip_serializing_if = "Option::is_none")]
+    start: Option<u32>,
+    /// Explicit case IDs (see `for_cases`); searched in batches and merged in this
+    /// order. An empty list matches no cases, without asking FogBugz.
+    #[serde(skip)]
+    #[builder(skip)]
+    case_ids: Option<Vec<u64>>,
+    /// Fail with `BudgetExceeded` instead of fetching more cases or sending more
+    /// requests than this allows
+    #[serde(skip)]
+    budget: Option<Budget>,
     #[serde(skip)]
     client: FogBugzClient,
 }
 
+/// Case IDs per `search` call when searching by explicit ID list.
+/// FogBugz rejects overly long queries, so long lists are split.
+pub(crate) const MAX_IDS_PER_QUERY: usize = 100;
+
+/// The `1,2,3` query form for a list of case IDs
+pub(crate) fn case_id_query(case_ids: &[u64]) -> String {
+    let ids: Vec<String> = case_ids.iter().map(|id| id.to_string()).collect();
+    ids.join(",")
+}
+
+/// Order cases from several responses by `case_ids`, dropping IDs that weren't found
+fn merge_in_order(case_ids: &[u64], cases: Vec<Value>) -> Vec<Value> {
+    let mut by_id: HashMap<u64, Value> = cases
+        .into_iter()
+        .filter_map(|case| Some((case["ixBug"].as_u64()?, case)))
+        .collect();
+    case_ids.iter().filter_map(|id| by_id.remove(id)).collect()
+}
+
+/// Append `OrderBy:ixBug` as the last sort order unless the query already ends with it.
+/// FogBugz's default ordering is not stable across pages, so paging without a unique
+/// tiebreaker can skip or repeat cases.
+pub(crate) fn with_stable_order(query: &str) -> String {
+    let query = query.trim();
+    if query.ends_with("OrderBy:ixBug") {
+        query.to_string()
+    } else if query.is_empty() {
+        "OrderBy:ixBug".to_string()
+    } else {
+        format!("{} OrderBy:ixBug", query)
+    }
+}
+
+/// Check requested columns against `T`: every column it requires must be
+/// requested, and every requested column must be one it has a field for
+pub(crate) fn check_columns<T: RequiredColumns>(cols: &[String]) -> Result<(), ResponseError> {
+    if let Some(missing) = T::required_columns()
+        .iter()
+        .find(|column| !cols.contains(&column.to_string()))
+    {
+        return Err(ResponseError::MissingColumn(*missing));
+    }
+    if let Some(known) = T::known_columns()
+        && let Some(unexpected) = cols.iter().find(|col| {
+            !col.parse::<Column>()
+                .is_ok_and(|column| known.contains(&column))
+        })
+    {
+        return Err(ResponseError::UnexpectedColumn(unexpected.clone()));
+    }
+    Ok(())
+}
+
+/// One response's worth of typed search results
+#[derive(Debug)]
+pub struct SearchPage<T> {
+    pub items: Vec<T>,
+    /// Cases matching the query, whether or not they were returned
+    pub total_hits: u64,
+    /// Cases in this response
+    pub returned: usize,
+}
+
+impl<T: DeserializeOwned> SearchPage<T> {
+    fn from_response(response: &mut Value) -> Result<Self, ResponseError> {
+        let items: Vec<T> = ResponseError::deserialize_cases(response["data"]["cases"].take())?;
+        let returned = response["data"]["count"]
+            .as_u64()
+            .map_or(items.len(), |count| count as usize);
+        let total_hits = response["data"]["totalHits"]
+            .as_u64()
+            .unwrap_or(returned as u64);
+        Ok(Self {
+            items,
+            total_hits,
+            returned,
+        })
+    }
+}
+
+impl<T> SearchPage<T> {
+    /// Whether more cases matched than were returned
+    pub fn is_truncated(&self) -> bool {
+        (self.returned as u64) < self.total_hits
+    }
+}
+
+/// Result of `SearchRequest::explain`
+#[derive(Debug)]
+pub struct SearchExplain<T> {
+    /// Query string sent as `q` by each request, in order. Explicit case lists
+    /// and over-long case ID queries are split into several searches.
+    pub queries: Vec<String>,
+    pub cols: Vec<String>,
+    pub max: Option<u32>,
+    pub start: Option<u32>,
+    /// Time until the whole response was received
+    pub elapsed: Duration,
+    /// Raw response with `data.cases` taken out: counts, warnings, errors
+    pub envelope: Value,
+    /// The cases, or why they couldn't be deserialized
+    pub cases: Result<Vec<T>, ResponseError>,
+}
+
+impl<T> SearchExplain<T> {
+    /// `totalHits` reported by FogBugz
+    pub fn total_hits(&self) -> Option<u64> {
+        self.envelope["data"]["totalHits"].as_u64()
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct Event {
     #[serde(rename = "evtDescription")]
@@ -38,11 +182,243 @@ pub struct CaseDetails {
 
 impl SearchRequest {
     pub async fn send(&self) -> Result<serde_json::Value, ResponseError> {
-        let params = serde_json::json!({
-            "q": self.query,
+        let mut budget = BudgetTracker::new(self.budget);
+        if let Some(case_ids) = &self.case_ids {
+            return self.send_for_cases(case_ids, &mut budget).await;
+        }
+        let mut params = serde_json::json!({
+            "q": self.query_string(),
             "cols": self.cols,
         });
-        self.client.send_search(params).await
+        if let Some(max) = self
+            .budget
+            .map_or(self.max, |budget| budget.cap_max(self.max))
+        {
+            params["max"] = max.into();
+        }
+        if let Some(start) = self.start {
+            params["start"] = start.into();
+        }
+        budget.request()?;
+        let response = self.client.send_search(params).await?;
+        budget.cases(response["data"]["cases"].as_array().map_or(0, Vec::len))?;
+        Ok(response)
+    }
+
+    /// Iterate over all matching cases a page at a time (`max` cases per page),
+    /// starting at `start`. Pages are always ordered by case ID as a tiebreaker
+    /// so no case is skipped or repeated between pages. Searches for explicit
+    /// case IDs (`for_cases`) give one page per batch of IDs, in their order.
+    pub fn paged(&self) -> impl Stream<Item = Result<Vec<Value>, ResponseError>> + '_ {
+        self.paged_with_cols(self.cols.clone())
+    }
+
+    /// Stream matching cases one at a time, fetching the next page only when the
+    /// current one is used up, so large result sets never sit in memory at once.
+    /// The columns `Case` needs are added to the requested ones.
+    pub fn stream(&self) -> impl Stream<Item = Result<Case, ResponseError>> + '_ {
+        let mut cols = self.cols.clone();
+        add_required_columns::<Case>(&mut cols);
+        self.paged_with_cols(cols)
+            .map_ok(|page| stream::iter(page.into_iter().map(ResponseError::deserialize_cases)))
+            .try_flatten()
+    }
+
+    fn paged_with_cols(
+        &self,
+        cols: Vec<String>,
+    ) -> impl Stream<Item = Result<Vec<Value>, ResponseError>> + '_ {
+        if let Some(case_ids) = &self.case_ids {
+            return self.paged_for_cases(case_ids, cols).left_stream();
+        }
+        let query = with_stable_order(&self.query);
+        pages(
+            self.start.unwrap_or(0),
+            self.max.unwrap_or(DEFAULT_PAGE_SIZE),
+            self.budget,
+            move |start, max| {
+                let params = serde_json::json!({
+                    "q": query,
+                    "cols": cols,
+                    "start": start,
+                    "max": max,
+                });
+                async move {
+                    let mut response = self.client.send_search(params).await?;
+                    match response["data"]["cases"].take() {
+                        Value::Array(cases) => Ok(cases),
+                        _ => Ok(Vec::new()),
+                    }
+                }
+            },
+        )
+        .right_stream()
+    }
+
+    /// One page per batch of `case_ids`, in their order
+    fn paged_for_cases<'a>(
+        &'a self,
+        case_ids: &'a [u64],
+        cols: Vec<String>,
+    ) -> impl Stream<Item = Result<Vec<Value>, ResponseError>> + 'a {
+        let state = (
+            case_ids.chunks(MAX_IDS_PER_QUERY),
+            BudgetTracker::new(self.budget),
+        );
+        stream::try_unfold(state, move |(mut batches, mut budget)| {
+            let cols = cols.clone();
+            async move {
+                let Some(batch) = batches.next() else {
+                    return Ok(None);
+                };
+                budget.request()?;
+                let cases = self.search_batch(batch, &cols).await?;
+                budget.cases(cases.len())?;
+                Ok(Some((cases, (batches, budget))))
+            }
+        })
+    }
+
+    /// Cases of one batch of IDs, in the batch's order
+    async fn search_batch(
+        &self,
+        batch: &[u64],
+        cols: &[String],
+    ) -> Result<Vec<Value>, ResponseError> {
+        let params = serde_json::json!({
+            "q": case_id_query(batch),
+            "cols": cols,
+        });
+        let mut response = self.client.send_search(params).await?;
+        match response["data"]["cases"].take() {
+            Value::Array(cases) => Ok(merge_in_order(batch, cases)),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    /// Fetch every matching case, following pages until the server is exhausted
+    pub async fn send_all(&self) -> Result<Vec<Value>, ResponseError> {
+        self.paged().try_concat().await
+    }
+
+    /// Send the search and deserialize the cases as `T`, keeping FogBugz's hit
+    /// counts so callers can tell when `max` cut the results short
+    pub async fn send_page<T: DeserializeOwned>(&self) -> Result<SearchPage<T>, ResponseError> {
+        let mut response = self.send().await?;
+        SearchPage::from_response(&mut response)
+    }
+
+    /// Send the search and deserialize the cases as `T`, after checking that the
+    /// requested columns are the ones `T` needs and can hold
+    pub async fn send_typed<T: DeserializeOwned + RequiredColumns>(
+        &self,
+    ) -> Result<SearchPage<T>, ResponseError> {
+        check_columns::<T>(&self.cols)?;
+        self.send_page().await
+    }
+
+    /// Send the search once and report what was sent and received alongside the
+    /// cases deserialized as `T`, for finding out why a query returns nothing
+    pub async fn explain<T: DeserializeOwned>(&self) -> Result<SearchExplain<T>, ResponseError> {
+        let queries = self.sent_queries()?;
+        let started = Instant::now();
+        let mut envelope = self.send().await?;
+        let elapsed = started.elapsed();
+        let cases = ResponseError::deserialize_cases(envelope["data"]["cases"].take());
+        Ok(SearchExplain {
+            queries,
+            cols: self.cols.clone(),
+            max: self
+                .budget
+                .map_or(self.max, |budget| budget.cap_max(self.max)),
+            start: self.start,
+            elapsed,
+            envelope,
+            cases,
+        })
+    }
+
+    /// The `q` of each search `send` makes
+    fn sent_queries(&self) -> Result<Vec<String>, ResponseError> {
+        let Some(case_ids) = &self.case_ids else {
+            return search_batches(&self.query_string());
+        };
+        let mut queries = Vec::new();
+        for batch in case_ids.chunks(MAX_IDS_PER_QUERY) {
+            queries.extend(search_batches(&case_id_query(batch))?);
+        }
+        Ok(queries)
+    }
+
+    async fn send_for_cases(
+        &self,
+        case_ids: &[u64],
+        budget: &mut BudgetTracker,
+    ) -> Result<serde_json::Value, ResponseError> {
+        let mut cases = Vec::with_capacity(case_ids.len());
+        for batch in case_ids.chunks(MAX_IDS_PER_QUERY) {
+            budget.request()?;
+            let batch_cases = self.search_batch(batch, &self.cols).await?;
+            budget.cases(batch_cases.len())?;
+            cases.extend(batch_cases);
+        }
+        Ok(serde_json::json!({
+            "data": {
+                "cases": cases,
+                "count": cases.len(),
+                "totalHits": cases.len()
+            },
+            "errors": []
+        }))
+    }
+
+    /// Search for specific cases by ID. Long lists are split into several `search`
+    /// calls; the merged cases come back in the order of `case_ids`, duplicates removed.
+    /// No IDs means no cases and no request.
+    pub fn for_cases(client: &FogBugzClient, case_ids: &[u64]) -> Self {
+        let mut seen = HashSet::new();
+        let case_ids: Vec<u64> = case_ids
+            .iter()
+            .copied()
+            .filter(|id| seen.insert(*id))
+            .collect();
+        Self {
+            query: case_id_query(&case_ids),
+            cols: vec![Column::CaseId.to_string(), Column::Title.to_string()],
+            stable_order: false,
+            max: None,
+            start: None,
+            case_ids: Some(case_ids),
+            budget: None,
+            client: client.clone(),
+        }
+    }
+
+    /// Replace the requested columns
+    pub fn with_cols(mut self, cols: &[Column]) -> Self {
+        self.cols = cols.iter().map(|col| col.to_string()).collect();
+        self
+    }
+
+    /// Limit how many cases and requests this search may use
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Add the columns `T` needs to deserialize, keeping any already requested
+    pub fn require_columns_for<T: RequiredColumns>(mut self) -> Self {
+        add_required_columns::<T>(&mut self.cols);
+        self
+    }
+
+    /// The query string as sent to FogBugz
+    pub fn query_string(&self) -> String {
+        if self.stable_order {
+            with_stable_order(&self.query)
+        } else {
+            self.query.clone()
+        }
     }
 
     /// Create a search request specifically for time tracking data
@@ -59,6 +435,11 @@ impl SearchRequest {
                 Column::PersonAssignedTo.to_string(),
                 Column::LastUpdated.to_string(),
             ],
+            stable_order: false,
+            max: None,
+            start: None,
+            case_ids: None,
+            budget: None,
             client: client.clone(),
         }
     }
@@ -78,7 +459,172 @@ impl SearchRequest {
 
 #[cfg(test)]
 mod tests {
-    use crate::{FogBugzClient, date::PointInTime, query::Query};
+    use std::sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    };
+
+    use super::{SearchPage, SearchRequest, merge_in_order, with_stable_order};
+    use crate::{
+        FogBugzClient, ResponseError, api_client::MAX_QUERY_LENGTH, date::PointInTime,
+        enums::Column, interceptor::RequestContext, list_cases::Case, query::Query,
+    };
+
+    #[test]
+    fn test_search_page_reports_truncation() {
+        let mut response = serde_json::json!({
+            "data": {
+                "cases": [
+                    { "ixBug": 1, "ixProject": 2, "sProject": "Widgets", "sTitle": "One" }
+                ],
+                "count": 1,
+                "totalHits": 40
+            }
+        });
+        let page: SearchPage<Case> = SearchPage::from_response(&mut response).unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.total_hits, 40);
+        assert!(page.is_truncated());
+    }
+
+    #[test]
+    fn test_for_cases_merges_in_input_order() {
+        let api = FogBugzClient::new("https://example.com", "test_key");
+        let request = SearchRequest::for_cases(&api, &[3, 1, 3, 2]);
+        assert_eq!(request.query_string(), "3,1,2");
+
+        let cases = vec![
+            serde_json::json!({"ixBug": 1}),
+            serde_json::json!({"ixBug": 2}),
+            serde_json::json!({"ixBug": 3}),
+        ];
+        let merged = merge_in_order(&[3, 1, 4, 2], cases);
+        let ids: Vec<u64> = merged
+            .iter()
+            .filter_map(|case| case["ixBug"].as_u64())
+            .collect();
+        assert_eq!(ids, vec![3, 1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_paging_many_case_ids() {
+        use futures::TryStreamExt;
+
+        use crate::mock_server::MockServer;
+
+        // Answers with the requested cases in reverse order
+        let server = MockServer::start(|payload| {
+            let cases: Vec<serde_json::Value> = payload["q"]
+                .as_str()
+                .unwrap()
+                .split(',')
+                .rev()
+                .map(|id| serde_json::json!({ "ixBug": id.parse::<u64>().unwrap() }))
+                .collect();
+            serde_json::json!({ "data": { "cases": cases }, "errors": [] })
+        })
+        .await;
+        let api = FogBugzClient::new(server.url(), "token");
+        let ids: Vec<u64> = (1..=500).map(|id| 100_000 + id).collect();
+        let request = SearchRequest::for_cases(&api, &ids);
+
+        let pages: Vec<Vec<serde_json::Value>> = request.paged().try_collect().await.unwrap();
+        assert_eq!(pages.len(), 5);
+        let found: Vec<u64> = pages
+            .iter()
+            .flatten()
+            .filter_map(|case| case["ixBug"].as_u64())
+            .collect();
+        assert_eq!(found, ids);
+        assert!(server.requests().iter().all(|payload| {
+            let query = payload["q"].as_str().unwrap();
+            !query.contains("OrderBy") && query.split(',').count() <= 100
+        }));
+
+        let cases = request.send_all().await.unwrap();
+        assert_eq!(cases.len(), 500);
+    }
+
+    #[tokio::test]
+    async fn test_no_case_ids_finds_no_cases() {
+        let api = FogBugzClient::new("http://localhost:1", "token");
+        let request = SearchRequest::for_cases(&api, &[]);
+        let response = request.send().await.unwrap();
+        assert_eq!(response["data"]["cases"], serde_json::json!([]));
+        assert!(request.send_all().await.unwrap().is_empty());
+        assert!(request.sent_queries().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_send_typed_rejects_missing_column() {
+        let sent = Arc::new(AtomicUsize::new(0));
+        let counter = sent.clone();
+        let api = FogBugzClient::new("http://localhost:1", "token").with_interceptor(
+            move |_: &mut RequestContext<'_>| {
+                counter.fetch_add(1, Ordering::SeqCst);
+            },
+        );
+        let request = api
+            .search()
+            .query("status:Active")
+            .cols(vec!["ixBug".to_string(), "sTitle".to_string()])
+            .build();
+        let result = request.send_typed::<Case>().await;
+        assert!(matches!(
+            result,
+            Err(ResponseError::MissingColumn(Column::ProjectId))
+        ));
+        assert_eq!(sent.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn test_sent_queries_follow_batches() {
+        let api = FogBugzClient::new("https://example.com", "test_key");
+        let ids: Vec<u64> = (1..=150).collect();
+        let queries = SearchRequest::for_cases(&api, &ids).sent_queries().unwrap();
+        assert_eq!(queries.len(), 2);
+        assert!(queries[0].starts_with("1,2,") && queries[0].ends_with(",100"));
+        assert!(queries[1].starts_with("101,") && queries[1].ends_with(",150"));
+
+        let ids: Vec<String> = (10_000..10_500).map(|id: u64| id.to_string()).collect();
+        let request = api.search().query(ids.join(" OR ")).build();
+        let queries = request.sent_queries().unwrap();
+        assert!(queries.len() > 1);
+        assert!(queries.iter().all(|query| query.len() <= MAX_QUERY_LENGTH));
+        assert_eq!(queries.join(","), ids.join(","));
+
+        let request = api.search().query("status:Active").build();
+        assert_eq!(request.sent_queries().unwrap(), ["status:Active"]);
+        let request = api
+            .search()
+            .query(format!("title:\"{}\"", "x".repeat(MAX_QUERY_LENGTH)))
+            .build();
+        assert!(matches!(
+            request.sent_queries(),
+            Err(crate::ResponseError::TooLongQuery { .. })
+        ));
+    }
+
+    #[test]
+    fn test_stable_order_appends_case_id_tiebreaker() {
+        assert_eq!(
+            with_stable_order("status:Active OrderBy:Priority"),
+            "status:Active OrderBy:Priority OrderBy:ixBug"
+        );
+        assert_eq!(with_stable_order(""), "OrderBy:ixBug");
+        assert_eq!(
+            with_stable_order("status:Active OrderBy:ixBug"),
+            "status:Active OrderBy:ixBug"
+        );
+
+        let api = FogBugzClient::new("https://example.com", "test_key");
+        let request = api
+            .search()
+            .query("status:Active")
+            .stable_order(true)
+            .build();
+        assert_eq!(request.query_string(), "status:Active OrderBy:ixBug");
+    }
 
     #[tokio::test]
     async fn test_search_request() {
@@ -0,0 +1,200 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::{
+    enums::{Column, RequiredColumns},
+    estimates::parse_child_ids,
+};
+
+/// A case with every standard search column, each present only if it was
+/// requested. Use with `SearchRequest::send_typed`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SearchCase {
+    #[serde(rename = "ixBug")]
+    pub case_id: Option<u64>,
+    #[serde(rename = "sTitle")]
+    pub title: Option<String>,
+    #[serde(rename = "sHtmlBody")]
+    pub body: Option<String>,
+    pub events: Option<Vec<Value>>,
+    #[serde(rename = "sProject")]
+    pub project: Option<String>,
+    #[serde(rename = "ixProject")]
+    pub project_id: Option<u64>,
+    #[serde(rename = "sArea")]
+    pub area: Option<String>,
+    #[serde(rename = "ixPriority")]
+    pub priority: Option<u64>,
+    #[serde(rename = "ixStatus")]
+    pub status_id: Option<u64>,
+    #[serde(rename = "ixCategory")]
+    pub category_id: Option<u64>,
+    #[serde(rename = "fOpen")]
+    pub is_open: Option<bool>,
+    #[serde(rename = "customFields")]
+    pub custom_fields: Option<Value>,
+    #[serde(rename = "hrsElapsed")]
+    pub hours_elapsed: Option<f64>,
+    #[serde(rename = "hrsCurrEst")]
+    pub hours_current_estimate: Option<f64>,
+    #[serde(rename = "hrsOrigEst")]
+    pub hours_original_estimate: Option<f64>,
+    #[serde(rename = "sPersonAssignedTo")]
+    pub assigned_to: Option<String>,
+    #[serde(rename = "ixPersonAssignedTo")]
+    pub assigned_to_id: Option<u64>,
+    #[serde(rename = "ixBugParent")]
+    pub parent_id: Option<u64>,
+    /// Subcase IDs; case ID lists are accepted as arrays or comma-separated strings
+    #[serde(
+        rename = "ixBugChildren",
+        default,
+        deserialize_with = "deserialize_case_ids"
+    )]
+    pub children: Option<Vec<u64>>,
+    #[serde(rename = "dtLastUpdated")]
+    pub last_updated: Option<DateTime<Utc>>,
+    pub tags: Option<Vec<String>>,
+    #[serde(rename = "dtOpened")]
+    pub opened: Option<DateTime<Utc>>,
+    #[serde(rename = "dtResolved")]
+    pub resolved: Option<DateTime<Utc>>,
+    #[serde(rename = "dtClosed")]
+    pub closed: Option<DateTime<Utc>>,
+    #[serde(rename = "dtDue")]
+    pub due: Option<DateTime<Utc>>,
+    #[serde(rename = "sFixFor")]
+    pub milestone: Option<String>,
+    #[serde(rename = "ixFixFor")]
+    pub milestone_id: Option<u64>,
+    #[serde(rename = "sStatus")]
+    pub status: Option<String>,
+    #[serde(rename = "sCategory")]
+    pub category: Option<String>,
+    #[serde(rename = "sPriority")]
+    pub priority_name: Option<String>,
+    #[serde(rename = "ixArea")]
+    pub area_id: Option<u64>,
+    #[serde(rename = "sLatestTextSummary")]
+    pub latest_text_summary: Option<String>,
+    #[serde(rename = "ixPersonOpenedBy")]
+    pub opened_by_id: Option<u64>,
+    #[serde(rename = "ixPersonResolvedBy")]
+    pub resolved_by_id: Option<u64>,
+    #[serde(rename = "ixPersonClosedBy")]
+    pub closed_by_id: Option<u64>,
+    #[serde(rename = "sCustomerEmail")]
+    pub customer_email: Option<String>,
+    #[serde(rename = "ixBugOriginal")]
+    pub original_id: Option<u64>,
+    #[serde(
+        rename = "ixBugDuplicates",
+        default,
+        deserialize_with = "deserialize_case_ids"
+    )]
+    pub duplicates: Option<Vec<u64>>,
+    #[serde(
+        rename = "ixRelatedBugs",
+        default,
+        deserialize_with = "deserialize_case_ids"
+    )]
+    pub related: Option<Vec<u64>>,
+    #[serde(rename = "sVersion")]
+    pub version: Option<String>,
+    #[serde(rename = "sComputer")]
+    pub computer: Option<String>,
+    #[serde(rename = "ixBugEventLatest")]
+    pub latest_event_id: Option<u64>,
+}
+
+fn deserialize_case_ids<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<u64>>, D::Error> {
+    let value = Value::deserialize(deserializer)?;
+    Ok(Some(parse_child_ids(&value)))
+}
+
+impl RequiredColumns for SearchCase {
+    fn required_columns() -> &'static [Column] {
+        &[]
+    }
+
+    fn known_columns() -> Option<&'static [Column]> {
+        Some(&[
+            Column::CaseId,
+            Column::Title,
+            Column::Body,
+            Column::Events,
+            Column::Project,
+            Column::ProjectId,
+            Column::Area,
+            Column::Priority,
+            Column::Status,
+            Column::Category,
+            Column::IsOpen,
+            Column::CustomFields,
+            Column::HoursElapsed,
+            Column::HoursCurrentEstimate,
+            Column::HoursOriginalEstimate,
+            Column::PersonAssignedTo,
+            Column::PersonAssignedToId,
+            Column::ParentId,
+            Column::Children,
+            Column::LastUpdated,
+            Column::Tags,
+            Column::DateOpened,
+            Column::DateResolved,
+            Column::DateClosed,
+            Column::DateDue,
+            Column::Milestone,
+            Column::MilestoneId,
+            Column::StatusName,
+            Column::CategoryName,
+            Column::PriorityName,
+            Column::AreaId,
+            Column::LatestTextSummary,
+            Column::PersonOpenedById,
+            Column::PersonResolvedById,
+            Column::PersonClosedById,
+            Column::CustomerEmail,
+            Column::OriginalId,
+            Column::Duplicates,
+            Column::RelatedCases,
+            Column::Version,
+            Column::Computer,
+            Column::LatestEventId,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ResponseError, list_cases::Case, search::check_columns};
+
+    #[test]
+    fn test_search_case_columns() {
+        let case: SearchCase = serde_json::from_value(serde_json::json!({
+            "ixBug": 7,
+            "sTitle": "Crash on save",
+            "ixBugChildren": "8, 9",
+            "hrsElapsed": 1.5,
+        }))
+        .unwrap();
+        assert_eq!(case.case_id, Some(7));
+        assert_eq!(case.children, Some(vec![8, 9]));
+        assert_eq!(case.project, None);
+
+        let cols = |cols: &[&str]| cols.iter().map(|col| col.to_string()).collect::<Vec<_>>();
+        assert!(check_columns::<SearchCase>(&cols(&["ixBug", "tags"])).is_ok());
+        assert!(matches!(
+            check_columns::<SearchCase>(&cols(&["ixBug", "plugin_xyz"])),
+            Err(ResponseError::UnexpectedColumn(col)) if col == "plugin_xyz"
+        ));
+        assert!(matches!(
+            check_columns::<Case>(&cols(&["ixBug", "sTitle"])),
+            Err(ResponseError::MissingColumn(Column::ProjectId))
+        ));
+    }
+}
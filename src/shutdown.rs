@@ -0,0 +1,95 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::sync::watch;
+
+/// Tells background components (`ChangeFeed`, `AssignmentWatcher`) to stop.
+/// Components finish the request in flight, hand out what it found, flush their
+/// state and then end their streams.
+#[derive(Debug, Clone)]
+pub struct ShutdownHandle {
+    sender: Arc<watch::Sender<bool>>,
+}
+
+/// Passed to a component's builder; fires once `ShutdownHandle::shutdown` is called
+#[derive(Debug, Clone)]
+pub struct ShutdownSignal {
+    receiver: watch::Receiver<bool>,
+}
+
+impl ShutdownHandle {
+    pub fn new() -> Self {
+        let (sender, _) = watch::channel(false);
+        Self {
+            sender: Arc::new(sender),
+        }
+    }
+
+    /// Signal for a component to watch; any number can share one handle
+    pub fn signal(&self) -> ShutdownSignal {
+        ShutdownSignal {
+            receiver: self.sender.subscribe(),
+        }
+    }
+
+    pub fn shutdown(&self) {
+        self.sender.send_replace(true);
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        *self.sender.borrow()
+    }
+}
+
+impl Default for ShutdownHandle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShutdownSignal {
+    pub fn is_shutdown(&self) -> bool {
+        *self.receiver.borrow()
+    }
+
+    /// Resolves once shutdown is requested; never if the handle is dropped without it
+    pub async fn wait(&self) {
+        let mut receiver = self.receiver.clone();
+        if receiver.wait_for(|stopped| *stopped).await.is_err() {
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+/// Sleep between polls, cut short by `shutdown`. Returns whether to stop.
+pub(crate) async fn sleep_unless_shutdown(
+    duration: Duration,
+    shutdown: Option<&ShutdownSignal>,
+) -> bool {
+    let Some(shutdown) = shutdown else {
+        tokio::time::sleep(duration).await;
+        return false;
+    };
+    tokio::select! {
+        _ = tokio::time::sleep(duration) => shutdown.is_shutdown(),
+        _ = shutdown.wait() => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_interrupts_sleep() {
+        let handle = ShutdownHandle::new();
+        let signal = handle.signal();
+        assert!(!sleep_unless_shutdown(Duration::from_millis(1), Some(&signal)).await);
+
+        let waiting = tokio::spawn(async move {
+            sleep_unless_shutdown(Duration::from_secs(3600), Some(&signal)).await
+        });
+        handle.shutdown();
+        assert!(waiting.await.unwrap());
+        assert!(handle.signal().is_shutdown());
+    }
+}
@@ -0,0 +1,76 @@
+use std::{fmt, sync::Arc};
+
+use reqwest::header::HeaderMap;
+use serde_json::Value;
+
+use crate::FogBugzClient;
+
+/// Hook that can change a request right before it is sent, e.g. to add a
+/// signature header for a reverse proxy. It sees the final JSON payload, with
+/// `cmd` and `token` already set, and the headers to send along with it.
+/// Attachment downloads have no body and pass `Value::Null`.
+#[derive(Clone)]
+pub struct RequestHook(Arc<HookFn>);
+
+type HookFn = dyn Fn(&mut Value, &mut HeaderMap) + Send + Sync;
+
+impl RequestHook {
+    pub fn new(hook: impl Fn(&mut Value, &mut HeaderMap) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+}
+
+impl<F: Fn(&mut Value, &mut HeaderMap) + Send + Sync + 'static> From<F> for RequestHook {
+    fn from(hook: F) -> Self {
+        Self::new(hook)
+    }
+}
+
+impl fmt::Debug for RequestHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RequestHook")
+    }
+}
+
+/// Run `hook`, if any, on `payload`; returns the headers it added
+pub(crate) fn sign(hook: Option<&RequestHook>, payload: &mut Value) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    if let Some(hook) = hook {
+        (hook.0)(payload, &mut headers);
+    }
+    headers
+}
+
+impl FogBugzClient {
+    /// Run the client's `request_hook` on `payload`; returns the headers it added
+    pub(crate) fn sign_request(&self, payload: &mut Value) -> HeaderMap {
+        sign(self.request_hook.as_ref(), payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+
+    use super::*;
+
+    #[test]
+    fn test_request_hook_signs_final_payload() {
+        let api = FogBugzClient::builder()
+            .url("https://example.com")
+            .api_key("key")
+            .request_hook(|payload: &mut Value, headers: &mut HeaderMap| {
+                let signature = format!("{}:{}", payload["cmd"], payload["token"]);
+                headers.insert("X-Signature", HeaderValue::from_str(&signature).unwrap());
+                payload["nonce"] = 1.into();
+            })
+            .build();
+        let mut payload = serde_json::json!({ "cmd": "search", "token": "key" });
+        let headers = api.sign_request(&mut payload);
+        assert_eq!(headers["X-Signature"], "\"search\":\"key\"");
+        assert_eq!(payload["nonce"], 1);
+
+        let unsigned = FogBugzClient::new("https://example.com", "key");
+        assert!(unsigned.sign_request(&mut payload).is_empty());
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::BTreeSet;
+
+use serde::Serialize;
+
+use crate::{FogBugzClient, ResponseError, enums::Column, filter::FogBugzSearchBuilder};
+
+/// Words too common to say anything about a case
+const STOPWORDS: &[&str] = &[
+    "a", "about", "after", "all", "an", "and", "any", "are", "as", "at", "be", "been", "but", "by",
+    "can", "cannot", "cant", "does", "doesnt", "dont", "for", "from", "get", "gets", "has", "have",
+    "how", "if", "in", "into", "is", "isnt", "it", "its", "not", "of", "on", "or", "please",
+    "should", "so", "that", "the", "then", "there", "this", "to", "too", "was", "what", "when",
+    "where", "which", "while", "why", "will", "with", "wont", "you",
+];
+
+/// Most candidates fetched for ranking
+const MAX_CANDIDATES: u32 = 50;
+
+/// An existing case that looks like the one about to be created
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimilarCase {
+    pub case_id: u64,
+    pub title: String,
+    pub is_open: bool,
+    /// Share of the title's significant terms the case's title contains, 0 to 1
+    pub score: f64,
+}
+
+/// Lowercased words of a title that aren't stopwords or shorter than 3 characters
+pub fn significant_terms(title: &str) -> BTreeSet<String> {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|word| word.chars().count() >= 3 && !STOPWORDS.contains(&word.as_str()))
+        .collect()
+}
+
+fn score(terms: &BTreeSet<String>, title: &str) -> f64 {
+    if terms.is_empty() {
+        return 0.0;
+    }
+    let candidate = significant_terms(title);
+    terms.intersection(&candidate).count() as f64 / terms.len() as f64
+}
+
+/// Candidates ranked by score, open cases first among equals
+fn rank(terms: &BTreeSet<String>, cases: &[serde_json::Value]) -> Vec<SimilarCase> {
+    let mut similar: Vec<SimilarCase> = cases
+        .iter()
+        .filter_map(|case| {
+            let title = case["sTitle"].as_str().unwrap_or_default();
+            Some(SimilarCase {
+                case_id: case["ixBug"].as_u64()?,
+                title: title.to_string(),
+                is_open: case["fOpen"].as_bool().unwrap_or_default(),
+                score: score(terms, title),
+            })
+        })
+        .filter(|case| case.score > 0.0)
+        .collect();
+    similar.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then(b.is_open.cmp(&a.is_open))
+            .then(b.case_id.cmp(&a.case_id))
+    });
+    similar
+}
+
+impl FogBugzClient {
+    /// Existing cases whose titles share terms with `title`, best matches first,
+    /// for suggesting duplicates before a new case is created
+    pub async fn find_similar_cases(
+        &self,
+        title: &str,
+        project: Option<&str>,
+    ) -> Result<Vec<SimilarCase>, ResponseError> {
+        let terms = significant_terms(title);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut query = FogBugzSearchBuilder::new();
+        if let Some(project) = project {
+            query = query.project(project);
+        }
+        let query = query
+            .or(|group| {
+                terms
+                    .iter()
+                    .fold(group, |group, term| group.axis("title", term))
+            })
+            .build();
+        let response = self
+            .search()
+            .query(query)
+            .cols(vec![
+                Column::CaseId.to_string(),
+                Column::Title.to_string(),
+                Column::IsOpen.to_string(),
+            ])
+            .max(MAX_CANDIDATES)
+            .build()
+            .send()
+            .await?;
+        let cases = response["data"]["cases"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        Ok(rank(&terms, cases))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rank_similar_cases() {
+        let terms = significant_terms("The export to CSV fails for large reports");
+        assert_eq!(
+            terms.iter().map(String::as_str).collect::<Vec<_>>(),
+            vec!["csv", "export", "fails", "large", "reports"]
+        );
+        let cases = vec![
+            serde_json::json!({"ixBug": 1, "sTitle": "Login page typo", "fOpen": true}),
+            serde_json::json!({"ixBug": 2, "sTitle": "CSV export fails", "fOpen": false}),
+            serde_json::json!({"ixBug": 3, "sTitle": "Export of CSV fails", "fOpen": true}),
+            serde_json::json!({"ixBug": 4, "sTitle": "Large reports are slow", "fOpen": true}),
+        ];
+        let ranked = rank(&terms, &cases);
+        let ids: Vec<u64> = ranked.iter().map(|case| case.case_id).collect();
+        assert_eq!(ids, vec![3, 2, 4]);
+        assert!((ranked[0].score - 0.6).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,231 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use async_trait::async_trait;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StateStoreError {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("State store error: {0}")]
+    Backend(String),
+    #[error("State store keys must not be empty")]
+    EmptyKey,
+}
+
+/// Pluggable key/value persistence for helpers that need to remember progress
+/// between runs (watermarks, round-robin rotation). Implement it on top of Redis,
+/// Postgres or anything else; the crate only needs string keys and values.
+#[async_trait]
+pub trait StateStore: Send + Sync {
+    async fn get(&self, key: &str) -> Result<Option<String>, StateStoreError>;
+    async fn put(&self, key: &str, value: String) -> Result<(), StateStoreError>;
+}
+
+#[async_trait]
+impl<T: StateStore + ?Sized> StateStore for Arc<T> {
+    async fn get(&self, key: &str) -> Result<Option<String>, StateStoreError> {
+        (**self).get(key).await
+    }
+
+    async fn put(&self, key: &str, value: String) -> Result<(), StateStoreError> {
+        (**self).put(key, value).await
+    }
+}
+
+/// In-memory state store, useful for tests and short-lived processes
+#[derive(Debug, Default)]
+pub struct MemoryStateStore {
+    values: Mutex<HashMap<String, String>>,
+}
+
+impl MemoryStateStore {
+    pub fn new() -> Self {
+        Default::default()
+    }
+}
+
+#[async_trait]
+impl StateStore for MemoryStateStore {
+    async fn get(&self, key: &str) -> Result<Option<String>, StateStoreError> {
+        let values = self
+            .values
+            .lock()
+            .map_err(|e| StateStoreError::Backend(e.to_string()))?;
+        Ok(values.get(key).cloned())
+    }
+
+    async fn put(&self, key: &str, value: String) -> Result<(), StateStoreError> {
+        let mut values = self
+            .values
+            .lock()
+            .map_err(|e| StateStoreError::Backend(e.to_string()))?;
+        values.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+/// Filesystem state store keeping one file per key in a directory
+#[derive(Debug, Clone)]
+pub struct FileStateStore {
+    dir: PathBuf,
+}
+
+impl FileStateStore {
+    /// Use `dir` for state files; it is created on first write
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    /// Keys may contain any characters, so escape everything that isn't
+    /// safe in a file name, dots included so no key maps to `.`, `..` or
+    /// another key's temporary file. Escaped keys too long for a file name are
+    /// cut short and end in `~` and a hash of the whole key instead; `~` is
+    /// always escaped, so such names can't collide with a short key's.
+    fn path_for(&self, key: &str) -> Result<PathBuf, StateStoreError> {
+        if key.is_empty() {
+            return Err(StateStoreError::EmptyKey);
+        }
+        let mut file_name = String::with_capacity(key.len() + 6);
+        for byte in key.bytes() {
+            if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_') {
+                file_name.push(byte as char);
+            } else {
+                file_name.push_str(&format!("%{:02X}", byte));
+            }
+        }
+        if file_name.len() > MAX_FILE_STEM {
+            file_name.truncate(MAX_FILE_STEM - 17);
+            file_name.push_str(&format!("~{:016x}", fnv1a(key.as_bytes())));
+        }
+        file_name.push_str(".value");
+        Ok(self.dir.join(file_name))
+    }
+}
+
+/// Longest escaped key used as is, leaving room in the usual 255-byte file name
+/// limit for the `.value` suffix and a temporary file's suffix
+const MAX_FILE_STEM: usize = 160;
+
+/// 64-bit FNV-1a, which unlike `DefaultHasher` is the same in every Rust release
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Distinguishes temporary files of concurrent `put`s within one process
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+#[async_trait]
+impl StateStore for FileStateStore {
+    async fn get(&self, key: &str) -> Result<Option<String>, StateStoreError> {
+        match tokio::fs::read_to_string(self.path_for(key)?).await {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn put(&self, key: &str, value: String) -> Result<(), StateStoreError> {
+        let path = self.path_for(key)?;
+        tokio::fs::create_dir_all(&self.dir).await?;
+        // Write to a temporary file first so a crash never leaves a half-written value
+        let mut tmp_name = path.clone().into_os_string();
+        tmp_name.push(format!(
+            ".{}.{}.tmp",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let tmp_path = PathBuf::from(tmp_name);
+        tokio::fs::write(&tmp_path, value).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_file_state_store() {
+        let dir = std::env::temp_dir().join(format!("fogbugz-ox-state-{}", std::process::id()));
+        let store = FileStateStore::new(&dir);
+        assert_eq!(store.get("round_robin:1,2").await.unwrap(), None);
+
+        store.put("round_robin:1,2", "2".to_string()).await.unwrap();
+        assert_eq!(
+            store.get("round_robin:1,2").await.unwrap().as_deref(),
+            Some("2")
+        );
+        // A fresh store over the same directory sees the persisted value
+        let reopened = FileStateStore::new(&dir);
+        assert_eq!(
+            reopened.get("round_robin:1,2").await.unwrap().as_deref(),
+            Some("2")
+        );
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_file_state_store_keys_stay_in_dir() {
+        let dir = std::env::temp_dir().join(format!("fogbugz-ox-keys-{}", std::process::id()));
+        let store = FileStateStore::new(&dir);
+        assert!(matches!(
+            store.put("", "x".to_string()).await,
+            Err(StateStoreError::EmptyKey)
+        ));
+        for key in [".", "..", "a.x", "a.y", "a.tmp", "a"] {
+            assert_eq!(store.path_for(key).unwrap().parent(), Some(dir.as_path()));
+        }
+        let puts = ["a.x", "a.y", "a.tmp", ".."].map(|key| store.put(key, key.to_string()));
+        for result in futures::future::join_all(puts).await {
+            result.unwrap();
+        }
+        for key in ["a.x", "a.y", "a.tmp", ".."] {
+            assert_eq!(store.get(key).await.unwrap().as_deref(), Some(key));
+        }
+        assert_eq!(store.get("a").await.unwrap(), None);
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_file_state_store_long_keys() {
+        let dir = std::env::temp_dir().join(format!("fogbugz-ox-long-{}", std::process::id()));
+        let store = FileStateStore::new(&dir);
+        let key = "feed:".repeat(60);
+        assert_eq!(key.len(), 300);
+        let path = store.path_for(&key).unwrap();
+        assert!(path.file_name().unwrap().len() <= 255);
+        assert_ne!(path, store.path_for(&format!("{key}x")).unwrap());
+
+        store.put(&key, "watermark".to_string()).await.unwrap();
+        assert_eq!(store.get(&key).await.unwrap().as_deref(), Some("watermark"));
+
+        tokio::fs::remove_dir_all(&dir).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_memory_state_store() {
+        let store = MemoryStateStore::new();
+        assert_eq!(store.get("missing").await.unwrap(), None);
+
+        store.put("key", "value".to_string()).await.unwrap();
+        assert_eq!(store.get("key").await.unwrap().as_deref(), Some("value"));
+
+        store.put("key", "updated".to_string()).await.unwrap();
+        assert_eq!(store.get("key").await.unwrap().as_deref(), Some("updated"));
+    }
+}
@@ -0,0 +1,99 @@
+use bon::Builder;
+use serde::Serialize;
+
+use crate::{FogBugzClient, ResponseError};
+
+/// What a subscription or view applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum NotificationTarget {
+    #[serde(rename = "ixBug")]
+    Case(u64),
+    /// Wiki article (`ixWikiPage`)
+    #[serde(rename = "ixWikiPage")]
+    Article(u32),
+}
+
+/// Request to subscribe the current user to email notifications about a case or article
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct SubscribeRequest {
+    /// Case or article to subscribe to (required)
+    #[serde(flatten)]
+    target: NotificationTarget,
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl SubscribeRequest {
+    pub async fn send(&self) -> Result<(), ResponseError> {
+        self.client.send_command("subscribe", self).await?;
+        Ok(())
+    }
+}
+
+/// Request to stop the current user's notifications about a case or article
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct UnsubscribeRequest {
+    /// Case or article to unsubscribe from (required)
+    #[serde(flatten)]
+    target: NotificationTarget,
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl UnsubscribeRequest {
+    pub async fn send(&self) -> Result<(), ResponseError> {
+        self.client.send_command("unsubscribe", self).await?;
+        Ok(())
+    }
+}
+
+/// Request to mark a case or article as viewed by the current user, so it no
+/// longer shows as updated
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct MarkViewedRequest {
+    /// Case or article to mark (required)
+    #[serde(flatten)]
+    target: NotificationTarget,
+    /// For cases, the event read up to; the latest if not set (optional)
+    #[serde(rename = "ixBugEvent", skip_serializing_if = "Option::is_none")]
+    event_id: Option<u64>,
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl MarkViewedRequest {
+    pub async fn send(&self) -> Result<(), ResponseError> {
+        self.client.send_command("view", self).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_notification_target_params() {
+        let api = FogBugzClient::new("https://example.com", "test_key");
+        let request = api
+            .mark_viewed(NotificationTarget::Case(7))
+            .event_id(42)
+            .build();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({ "ixBug": 7, "ixBugEvent": 42 })
+        );
+
+        let request = api.subscribe(NotificationTarget::Article(12)).build();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({ "ixWikiPage": 12 })
+        );
+    }
+}
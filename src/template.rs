@@ -0,0 +1,139 @@
+use std::collections::HashMap;
+
+use thiserror::Error;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("Missing template variable: {0}")]
+    MissingVariable(String),
+    #[error("Unterminated placeholder starting at byte {0}")]
+    UnterminatedPlaceholder(usize),
+}
+
+/// A text template with `{{name}}` placeholders
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    source: String,
+}
+
+impl Template {
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Names of all placeholders used in the template, in order of appearance
+    pub fn placeholders(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        let mut rest = self.source.as_str();
+        while let Some(start) = rest.find("{{") {
+            let after = &rest[start + 2..];
+            let Some(end) = after.find("}}") else {
+                break;
+            };
+            names.push(after[..end].trim());
+            rest = &after[end + 2..];
+        }
+        names
+    }
+
+    /// Replace every `{{name}}` with its value; unknown names are an error
+    pub fn render(&self, vars: &HashMap<String, String>) -> Result<String, TemplateError> {
+        self.render_with(vars, str::to_string)
+    }
+
+    /// Like `render`, but HTML-escapes the substituted values (not the template itself),
+    /// so case fields can't inject markup into HTML bodies
+    pub fn render_html(&self, vars: &HashMap<String, String>) -> Result<String, TemplateError> {
+        self.render_with(vars, escape_html)
+    }
+
+    fn render_with(
+        &self,
+        vars: &HashMap<String, String>,
+        escape: impl Fn(&str) -> String,
+    ) -> Result<String, TemplateError> {
+        let mut output = String::with_capacity(self.source.len());
+        let mut rest = self.source.as_str();
+        while let Some(start) = rest.find("{{") {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after.find("}}").ok_or_else(|| {
+                TemplateError::UnterminatedPlaceholder(self.source.len() - rest.len() + start)
+            })?;
+            let name = after[..end].trim();
+            let value = vars
+                .get(name)
+                .ok_or_else(|| TemplateError::MissingVariable(name.to_string()))?;
+            output.push_str(&escape(value));
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+}
+
+/// Escape text for inclusion in HTML
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl From<&str> for Template {
+    fn from(source: &str) -> Self {
+        Template::new(source)
+    }
+}
+
+impl From<String> for Template {
+    fn from(source: String) -> Self {
+        Template::new(source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_template() {
+        let template = Template::new("Hi {{ assigned_to }}, case {{case_id}} needs an update.");
+        let vars = HashMap::from([
+            ("assigned_to".to_string(), "Jane".to_string()),
+            ("case_id".to_string(), "62020".to_string()),
+        ]);
+        assert_eq!(
+            template.render(&vars).unwrap(),
+            "Hi Jane, case 62020 needs an update."
+        );
+        assert_eq!(template.placeholders(), vec!["assigned_to", "case_id"]);
+    }
+
+    #[test]
+    fn test_render_template_errors() {
+        let vars = HashMap::new();
+        assert_eq!(
+            Template::new("Hi {{name}}").render(&vars),
+            Err(TemplateError::MissingVariable("name".to_string()))
+        );
+        assert_eq!(
+            Template::new("Hi {{name").render(&vars),
+            Err(TemplateError::UnterminatedPlaceholder(3))
+        );
+    }
+}
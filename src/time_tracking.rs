@@ -1,9 +1,11 @@
+use std::fmt;
+
 use bon::Builder;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::{FogBugzClient, ResponseError};
+use crate::{FogBugzClient, ResponseError, api_client::FogBugzApiError, hours::Hours};
 
 /// Request to start working on a case (start the stopwatch)
 #[derive(Debug, Serialize, Builder)]
@@ -18,9 +20,19 @@ pub struct StartWorkRequest {
 }
 
 impl StartWorkRequest {
-    /// Start working on the case
-    pub async fn send(&self) -> Result<Value, ResponseError> {
-        self.client.send_command("startWork", self).await
+    /// Start working on the case; the response carries the interval the server
+    /// opened. Costs an extra `listIntervals` request when `startWork` doesn't
+    /// return the interval itself.
+    pub async fn send(&self) -> Result<StartWorkResponse, ResponseError> {
+        let response = self.client.send_command("startWork", self).await?;
+        let interval = self
+            .client
+            .affected_interval(&response, Some(self.case_id), None)
+            .await?;
+        Ok(StartWorkResponse {
+            case_id: self.case_id,
+            interval,
+        })
     }
 }
 
@@ -34,14 +46,130 @@ pub struct StopWorkRequest {
 }
 
 impl StopWorkRequest {
-    /// Stop working
-    pub async fn send(&self) -> Result<Value, ResponseError> {
-        self.client
+    /// Stop working; the response carries the interval the server closed.
+    /// Costs an extra `listIntervals` request when `stopWork` doesn't return
+    /// the interval itself.
+    pub async fn send(&self) -> Result<StopWorkResponse, ResponseError> {
+        let sent_at = Utc::now();
+        let response = self
+            .client
             .send_command("stopWork", serde_json::json!({}))
-            .await
+            .await?;
+        let interval = self
+            .client
+            .affected_interval(&response, None, Some(sent_at))
+            .await?;
+        Ok(StopWorkResponse { interval })
+    }
+}
+
+/// An interval opened by `startWork` or closed by `stopWork`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WorkInterval {
+    pub interval_id: u32,
+    pub case_id: u32,
+    pub start_time: DateTime<Utc>,
+    /// `None` while the stopwatch is running
+    pub end_time: Option<DateTime<Utc>>,
+}
+
+impl WorkInterval {
+    fn from_json(interval: &Value) -> Option<Self> {
+        let id = |key: &str| interval[key].as_u64().and_then(|id| u32::try_from(id).ok());
+        Some(Self {
+            interval_id: id("ixInterval")?,
+            case_id: id("ixBug")?,
+            start_time: interval["dtStart"].as_str()?.parse().ok()?,
+            end_time: interval["dtEnd"].as_str().and_then(|end| end.parse().ok()),
+        })
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StartWorkResponse {
+    pub case_id: u32,
+    /// The running interval; `None` if the server didn't report one
+    pub interval: Option<WorkInterval>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StopWorkResponse {
+    /// The interval that was stopped; `None` if no stopwatch was running
+    pub interval: Option<WorkInterval>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SwitchWorkResponse {
+    /// The interval that was stopped; `None` if no stopwatch was running
+    pub stopped: Option<WorkInterval>,
+    pub started: StartWorkResponse,
+}
+
+/// FogBugz error code for "time tracking problem"
+const CODE_TIME_TRACKING: &str = "7";
+
+/// Whether `stopWork` failed only because no stopwatch was running: a time
+/// tracking error saying the user isn't working on anything
+fn is_idle_stopwatch_error(error: &ResponseError) -> bool {
+    let ResponseError::FogbugzError(FogBugzApiError::Other {
+        code: Some(code),
+        message,
+    }) = error
+    else {
+        return false;
+    };
+    let message = message.to_lowercase();
+    code == CODE_TIME_TRACKING
+        && (message.contains("not working") || message.contains("not currently working"))
+}
+
+/// What `switch_work` does with the outcome of stopping: carry on after an
+/// idle stopwatch, stop on any other failure
+fn stopped_before_switch(
+    result: Result<StopWorkResponse, ResponseError>,
+) -> Result<Option<WorkInterval>, ResponseError> {
+    match result {
+        Ok(response) => Ok(response.interval),
+        Err(e) if is_idle_stopwatch_error(&e) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// How far back to look for the interval a stopwatch command affected
+const WORK_INTERVAL_LOOKBACK_DAYS: i64 = 7;
+
+/// How far the end of a stopped interval may be from when `stopWork` was
+/// sent, allowing for clock skew between client and server
+const STOPPED_INTERVAL_TOLERANCE_SECONDS: i64 = 15;
+
+/// The most recently started of `intervals`, optionally only those of one case
+fn latest_interval(intervals: &[Value], case_id: Option<u32>) -> Option<WorkInterval> {
+    intervals
+        .iter()
+        .filter_map(WorkInterval::from_json)
+        .filter(|interval| case_id.is_none_or(|case_id| interval.case_id == case_id))
+        .max_by_key(|interval| interval.start_time)
+}
+
+/// The interval a stopwatch command left behind: the latest one if it is
+/// still open after `startWork`, or if it ended around `stopped_at` after
+/// `stopWork`. Anything else is an older interval, not the command's.
+fn affected_in(
+    intervals: &[Value],
+    case_id: Option<u32>,
+    stopped_at: Option<DateTime<Utc>>,
+) -> Option<WorkInterval> {
+    let latest = latest_interval(intervals, case_id)?;
+    let affected = match (stopped_at, latest.end_time) {
+        (None, None) => true,
+        (Some(stopped_at), Some(end)) => {
+            (end - stopped_at).num_seconds().abs() <= STOPPED_INTERVAL_TOLERANCE_SECONDS
+        }
+        _ => false,
+    };
+    affected.then_some(latest)
+}
+
 /// Request to create a new time interval
 #[derive(Debug, Serialize, Builder)]
 #[builder(state_mod(vis = "pub(crate)"))]
@@ -63,6 +191,11 @@ pub struct NewIntervalRequest {
     #[builder(into)]
     title: Option<String>,
 
+    /// Person to log the time for; defaults to the token owner.
+    /// Logging time on behalf of others requires administrator rights (optional)
+    #[serde(rename = "ixPerson", skip_serializing_if = "Option::is_none")]
+    person_id: Option<u32>,
+
     /// API instance
     #[serde(skip)]
     client: FogBugzClient,
@@ -75,6 +208,69 @@ impl NewIntervalRequest {
     }
 }
 
+/// Request to correct a logged time interval; unset fields are left unchanged
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct EditIntervalRequest {
+    /// Interval to edit (required)
+    #[serde(rename = "ixInterval")]
+    interval_id: u32,
+
+    /// Case to move the interval to (optional)
+    #[serde(rename = "ixBug", skip_serializing_if = "Option::is_none")]
+    case_id: Option<u32>,
+
+    /// New start time (optional)
+    #[serde(rename = "dtStart", skip_serializing_if = "Option::is_none")]
+    start_time: Option<DateTime<Utc>>,
+
+    /// New end time (optional)
+    #[serde(rename = "dtEnd", skip_serializing_if = "Option::is_none")]
+    end_time: Option<DateTime<Utc>>,
+
+    /// New description of the work done (optional)
+    #[serde(rename = "sTitle", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    title: Option<String>,
+
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl EditIntervalRequest {
+    /// Save the changes to the interval
+    pub async fn send(&self) -> Result<Value, ResponseError> {
+        self.client.send_command("editInterval", self).await
+    }
+}
+
+/// Request to delete a logged time interval
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct DeleteIntervalRequest {
+    /// Interval to delete (required)
+    #[serde(rename = "ixInterval")]
+    interval_id: u32,
+
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl DeleteIntervalRequest {
+    fn params(&self) -> Value {
+        serde_json::json!({ "ixInterval": self.interval_id, "fDeleted": true })
+    }
+
+    /// Delete the interval; it is kept with `fDeleted` set and no longer counts
+    pub async fn send(&self) -> Result<Value, ResponseError> {
+        self.client
+            .send_command("editInterval", self.params())
+            .await
+    }
+}
+
 /// A time interval record
 #[derive(Debug, Deserialize, Serialize)]
 pub struct TimeInterval {
@@ -94,7 +290,61 @@ pub struct TimeInterval {
     pub is_deleted: bool,
 }
 
+impl TimeInterval {
+    /// Length of the interval
+    pub fn duration(&self) -> Hours {
+        Hours::between(self.start_time, self.end_time)
+    }
+}
+
+impl fmt::Display for TimeInterval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "#{} {} - {} ({}) person {}: {}",
+            self.case_id,
+            self.start_time.format("%Y-%m-%d %H:%M"),
+            self.end_time.format("%H:%M"),
+            self.duration(),
+            self.person_id,
+            self.title
+        )
+    }
+}
+
 impl FogBugzClient {
+    /// Stop the current stopwatch, if one is running, and start work on `case_id`.
+    /// Fails without starting if stopping fails for any other reason.
+    pub async fn switch_work(&self, case_id: u32) -> Result<SwitchWorkResponse, ResponseError> {
+        let stopped = stopped_before_switch(self.stop_work().build().send().await)?;
+        let started = self.start_work().case_id(case_id).build().send().await?;
+        Ok(SwitchWorkResponse { stopped, started })
+    }
+
+    /// The interval a stopwatch command affected: from its response if the
+    /// server included it, otherwise looked up with `listIntervals` (see
+    /// `affected_in`); `stopped_at` is when `stopWork` was sent
+    async fn affected_interval(
+        &self,
+        response: &Value,
+        case_id: Option<u32>,
+        stopped_at: Option<DateTime<Utc>>,
+    ) -> Result<Option<WorkInterval>, ResponseError> {
+        if let Some(interval) = WorkInterval::from_json(&response["data"]["interval"]) {
+            return Ok(Some(interval));
+        }
+        let since = Utc::now() - chrono::Duration::days(WORK_INTERVAL_LOOKBACK_DAYS);
+        let params = serde_json::json!({
+            "dtStart": since.format("%Y-%m-%dT%H:%M:%S").to_string(),
+        });
+        let response = self.send_command("listIntervals", params).await?;
+        let intervals = response["data"]["intervals"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        Ok(affected_in(intervals, case_id, stopped_at))
+    }
+
     /// List time intervals for a specific person and date range
     pub async fn list_time_intervals(
         &self,
@@ -168,6 +418,42 @@ mod tests {
         assert!(true);
     }
 
+    #[test]
+    fn test_new_interval_on_behalf() {
+        let api = FogBugzClient::new("https://example.com", "test_key");
+        let end_time = Utc::now() - Duration::hours(1);
+        let request = api
+            .new_interval()
+            .case_id(123)
+            .start_time(end_time - Duration::hours(1))
+            .end_time(end_time)
+            .person_id(75)
+            .build();
+        let params = serde_json::to_value(&request).unwrap();
+        assert_eq!(params["ixPerson"], 75);
+    }
+
+    #[test]
+    fn test_edit_and_delete_interval_params() {
+        let api = FogBugzClient::new("https://example.com", "test_key");
+        let edit_request = api
+            .edit_interval()
+            .interval_id(9)
+            .end_time(Utc::now() - Duration::hours(1))
+            .title("Corrected")
+            .build();
+        let params = serde_json::to_value(&edit_request).unwrap();
+        assert_eq!(params["ixInterval"], 9);
+        assert_eq!(params["sTitle"], "Corrected");
+        assert!(params.get("dtStart").is_none());
+
+        let delete_request = api.delete_interval().interval_id(9).build();
+        assert_eq!(
+            delete_request.params(),
+            serde_json::json!({ "ixInterval": 9, "fDeleted": true })
+        );
+    }
+
     #[tokio::test]
     async fn test_list_time_intervals() {
         let api_key = std::env::var("FOGBUGZ_API_KEY").unwrap();
@@ -201,4 +487,79 @@ mod tests {
             assert!(interval.start_time < interval.end_time);
         }
     }
+
+    #[test]
+    fn test_latest_interval() {
+        let intervals = [
+            serde_json::json!({"ixInterval": 1, "ixBug": 10, "dtStart": "2024-03-04T09:00:00Z", "dtEnd": "2024-03-04T10:00:00Z"}),
+            serde_json::json!({"ixInterval": 2, "ixBug": 11, "dtStart": "2024-03-04T11:00:00Z", "dtEnd": null}),
+            serde_json::json!({"ixInterval": 3, "ixBug": 10, "dtStart": "2024-03-04T08:00:00Z", "dtEnd": "2024-03-04T08:30:00Z"}),
+        ];
+        let latest = super::latest_interval(&intervals, None).unwrap();
+        assert_eq!(latest.interval_id, 2);
+        assert_eq!(latest.end_time, None);
+
+        let latest = super::latest_interval(&intervals, Some(10)).unwrap();
+        assert_eq!(latest.interval_id, 1);
+        assert_eq!(
+            latest.end_time,
+            Some("2024-03-04T10:00:00Z".parse().unwrap())
+        );
+
+        // startWork: only a still-open interval is the one it opened
+        assert_eq!(
+            super::affected_in(&intervals, None, None).map(|i| i.interval_id),
+            Some(2)
+        );
+        assert_eq!(super::affected_in(&intervals, Some(10), None), None);
+        // stopWork: only an interval that just ended is the one it closed
+        let stopped = [
+            serde_json::json!({"ixInterval": 1, "ixBug": 10, "dtStart": "2024-03-04T09:00:00Z", "dtEnd": "2024-03-04T10:00:03Z"}),
+        ];
+        let at = |time: &str| Some(format!("2024-03-04T{time}Z").parse().unwrap());
+        assert_eq!(
+            super::affected_in(&stopped, None, at("10:00:00")).map(|i| i.interval_id),
+            Some(1)
+        );
+        assert_eq!(super::affected_in(&stopped, None, at("14:00:00")), None);
+        assert_eq!(super::affected_in(&intervals, None, at("11:30:00")), None);
+    }
+
+    #[test]
+    fn test_idle_stopwatch_error() {
+        let idle = crate::api_client::FogBugzApiError::Other {
+            code: Some("7".to_string()),
+            message: "Not working on a case".to_string(),
+        };
+        assert!(super::is_idle_stopwatch_error(&idle.into()));
+        let expired = crate::api_client::FogBugzApiError::InvalidToken {
+            message: "Not logged in".to_string(),
+        };
+        assert!(!super::is_idle_stopwatch_error(&expired.into()));
+
+        let idle = crate::api_client::FogBugzApiError::Other {
+            code: Some("7".to_string()),
+            message: "You are not currently working on a case".to_string(),
+        };
+        assert!(matches!(
+            super::stopped_before_switch(Err(idle.into())),
+            Ok(None)
+        ));
+        for unrelated in [
+            crate::api_client::FogBugzApiError::Other {
+                code: Some("7".to_string()),
+                message: "Interval overlaps another interval".to_string(),
+            },
+            crate::api_client::FogBugzApiError::Other {
+                code: Some("99".to_string()),
+                message: "Not working on a case".to_string(),
+            },
+            crate::api_client::FogBugzApiError::Other {
+                code: None,
+                message: "Internal server error".to_string(),
+            },
+        ] {
+            assert!(super::stopped_before_switch(Err(unrelated.into())).is_err());
+        }
+    }
 }
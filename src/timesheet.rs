@@ -0,0 +1,230 @@
+use bon::Builder;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::{
+    FogBugzClient, ResponseError, enums::Column, filter::FogBugzSearchBuilder, hours::Hours,
+    interval_import::IntervalRecord, organization::PersonRef, working_schedule::WorkingSchedule,
+};
+
+/// Suggestions are rounded to this many hours
+const SUGGESTION_STEP: f64 = 0.25;
+
+/// A case the person worked on that day, with hours to log against it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TimesheetSuggestion {
+    pub case_id: u64,
+    pub title: String,
+    /// Events the person added to the case that day
+    pub events: usize,
+    pub suggested: Hours,
+}
+
+/// A person's day: hours expected by the working schedule, hours already logged,
+/// and cases to log the rest against, most active first
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DaySuggestions {
+    pub person_id: u32,
+    pub date: NaiveDate,
+    pub expected: Hours,
+    pub logged: Hours,
+    pub suggestions: Vec<TimesheetSuggestion>,
+}
+
+impl DaySuggestions {
+    pub fn missing(&self) -> Hours {
+        if self.logged < self.expected {
+            self.expected - self.logged
+        } else {
+            Hours::ZERO
+        }
+    }
+
+    /// The suggestions as back-to-back intervals from `start`, ready for
+    /// `FogBugzClient::import_intervals` once the user confirms them
+    pub fn intervals(&self, start: DateTime<Utc>) -> Vec<IntervalRecord> {
+        let mut next = start;
+        self.suggestions
+            .iter()
+            .map(|suggestion| {
+                let end = next + suggestion.suggested.to_duration();
+                let record = IntervalRecord {
+                    person: PersonRef::Id(self.person_id),
+                    case: suggestion.case_id.to_string(),
+                    start: next,
+                    end,
+                    note: Some(suggestion.title.clone()),
+                };
+                next = end;
+                record
+            })
+            .collect()
+    }
+}
+
+/// Split `missing` hours over the cases `person_id` added events to on `date`,
+/// in proportion to how many events each got
+fn suggest(
+    cases: &[Value],
+    person_id: u32,
+    date: NaiveDate,
+    missing: Hours,
+) -> Vec<TimesheetSuggestion> {
+    let mut activity: Vec<(u64, String, usize)> = cases
+        .iter()
+        .filter_map(|case| {
+            let case_id = case["ixBug"].as_u64()?;
+            let events = case["events"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|event| event["ixPerson"].as_u64() == Some(person_id.into()))
+                .filter(|event| {
+                    event["dt"]
+                        .as_str()
+                        .and_then(|dt| dt.parse::<DateTime<Utc>>().ok())
+                        .is_some_and(|dt| dt.date_naive() == date)
+                })
+                .count();
+            let title = case["sTitle"].as_str().unwrap_or_default().to_string();
+            (events > 0).then_some((case_id, title, events))
+        })
+        .collect();
+    activity.sort_by(|a, b| b.2.cmp(&a.2).then(a.0.cmp(&b.0)));
+
+    let total: usize = activity.iter().map(|(_, _, events)| events).sum();
+    activity
+        .into_iter()
+        .filter_map(|(case_id, title, events)| {
+            let share = missing.as_f64() * events as f64 / total as f64;
+            let suggested = (share / SUGGESTION_STEP).round() * SUGGESTION_STEP;
+            (suggested > 0.0).then(|| TimesheetSuggestion {
+                case_id,
+                title,
+                events,
+                suggested: Hours::new(suggested),
+            })
+        })
+        .collect()
+}
+
+/// Suggest cases to fill a person's missing hours on a day with, based on the
+/// cases they edited or commented on that day
+#[derive(Debug, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct TimesheetAutofillRequest {
+    /// Person whose timesheet to fill (required)
+    person_id: u32,
+    /// Day to fill (required)
+    date: NaiveDate,
+    /// Working schedule to take expected hours from; the site's if not set
+    schedule: Option<WorkingSchedule>,
+    /// API instance
+    client: FogBugzClient,
+}
+
+impl TimesheetAutofillRequest {
+    pub async fn send(&self) -> Result<DaySuggestions, ResponseError> {
+        let schedule = match &self.schedule {
+            Some(schedule) => schedule.clone(),
+            None => self.client.working_schedule().await?,
+        };
+        let day_start = self.date.and_time(Default::default()).and_utc();
+        let day_end = day_start + chrono::Duration::days(1);
+        let logged: Hours = self
+            .client
+            .list_time_intervals(Some(self.person_id), Some(day_start), Some(day_end))
+            .await?
+            .iter()
+            .filter(|interval| !interval.is_deleted)
+            .map(|interval| {
+                Hours::between(
+                    interval.start_time.max(day_start),
+                    interval.end_time.min(day_end),
+                )
+            })
+            .sum();
+
+        let mut day = DaySuggestions {
+            person_id: self.person_id,
+            date: self.date,
+            expected: schedule.working_hours(self.date),
+            logged,
+            suggestions: Vec::new(),
+        };
+        let missing = day.missing();
+        if missing == Hours::ZERO {
+            return Ok(day);
+        }
+
+        let people = self.client.list_people().await?;
+        let Some(person) = people.iter().find(|person| person.id == self.person_id) else {
+            return Err(ResponseError::PersonNotFound(self.person_id.to_string()));
+        };
+        let query = FogBugzSearchBuilder::new()
+            .edited_by(&person.full_name)
+            .edited_date(&self.date.format("%m/%d/%Y").to_string())
+            .build();
+        let cases = self
+            .client
+            .search()
+            .query(query)
+            .cols(vec![
+                Column::CaseId.to_string(),
+                Column::Title.to_string(),
+                Column::Events.to_string(),
+            ])
+            .build()
+            .send_all()
+            .await?;
+        day.suggestions = suggest(&cases, self.person_id, self.date, missing);
+        Ok(day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_suggest_splits_missing_hours_by_activity() {
+        let date = NaiveDate::from_ymd_opt(2024, 3, 4).unwrap();
+        let event = |person: u32, dt: &str| serde_json::json!({ "ixPerson": person, "dt": dt });
+        let cases = [
+            serde_json::json!({ "ixBug": 1, "sTitle": "Login", "events": [
+                event(7, "2024-03-04T09:00:00Z"),
+                event(7, "2024-03-04T15:00:00Z"),
+                event(7, "2024-03-04T16:00:00Z"),
+                event(8, "2024-03-04T10:00:00Z"),
+            ]}),
+            serde_json::json!({ "ixBug": 2, "sTitle": "Export", "events": [
+                event(7, "2024-03-04T11:00:00Z"),
+                event(7, "2024-03-03T11:00:00Z"),
+            ]}),
+            serde_json::json!({ "ixBug": 3, "sTitle": "Other", "events": [
+                event(8, "2024-03-04T11:00:00Z"),
+            ]}),
+        ];
+        let suggestions = suggest(&cases, 7, date, Hours::new(4.0));
+        let hours: Vec<(u64, f64)> = suggestions
+            .iter()
+            .map(|suggestion| (suggestion.case_id, suggestion.suggested.as_f64()))
+            .collect();
+        assert_eq!(hours, vec![(1, 3.0), (2, 1.0)]);
+
+        let day = DaySuggestions {
+            person_id: 7,
+            date,
+            expected: Hours::new(8.0),
+            logged: Hours::new(4.0),
+            suggestions,
+        };
+        let intervals = day.intervals("2024-03-04T13:00:00Z".parse().unwrap());
+        assert_eq!(intervals[1].case, "2");
+        assert_eq!(
+            intervals[1].start,
+            "2024-03-04T16:00:00Z".parse::<DateTime<Utc>>().unwrap()
+        );
+    }
+}
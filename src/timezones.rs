@@ -0,0 +1,284 @@
+use std::ops::Range;
+
+use bon::Builder;
+use chrono::{DateTime, FixedOffset, Timelike, Utc};
+
+use crate::{FogBugzClient, ResponseError, organization::Person, time_tracking::TimeInterval};
+
+/// Standard (non-DST) UTC offset in minutes of common Windows time zone keys,
+/// as stored in `sTimeZoneKey`
+const STANDARD_OFFSETS: &[(&str, i32)] = &[
+    ("Dateline Standard Time", -720),
+    ("UTC-11", -660),
+    ("Hawaiian Standard Time", -600),
+    ("Alaskan Standard Time", -540),
+    ("Pacific Standard Time", -480),
+    ("US Mountain Standard Time", -420),
+    ("Mountain Standard Time", -420),
+    ("Central America Standard Time", -360),
+    ("Central Standard Time", -360),
+    ("Canada Central Standard Time", -360),
+    ("Eastern Standard Time", -300),
+    ("US Eastern Standard Time", -300),
+    ("SA Pacific Standard Time", -300),
+    ("Atlantic Standard Time", -240),
+    ("Newfoundland Standard Time", -210),
+    ("E. South America Standard Time", -180),
+    ("SA Eastern Standard Time", -180),
+    ("Argentina Standard Time", -180),
+    ("UTC", 0),
+    ("GMT Standard Time", 0),
+    ("Greenwich Standard Time", 0),
+    ("W. Europe Standard Time", 60),
+    ("Central Europe Standard Time", 60),
+    ("Central European Standard Time", 60),
+    ("Romance Standard Time", 60),
+    ("GTB Standard Time", 120),
+    ("E. Europe Standard Time", 120),
+    ("FLE Standard Time", 120),
+    ("South Africa Standard Time", 120),
+    ("Israel Standard Time", 120),
+    ("Egypt Standard Time", 120),
+    ("Russian Standard Time", 180),
+    ("Turkey Standard Time", 180),
+    ("Arab Standard Time", 180),
+    ("Arabic Standard Time", 180),
+    ("Iran Standard Time", 210),
+    ("Arabian Standard Time", 240),
+    ("Pakistan Standard Time", 300),
+    ("India Standard Time", 330),
+    ("Nepal Standard Time", 345),
+    ("Bangladesh Standard Time", 360),
+    ("SE Asia Standard Time", 420),
+    ("China Standard Time", 480),
+    ("Singapore Standard Time", 480),
+    ("Taipei Standard Time", 480),
+    ("W. Australia Standard Time", 480),
+    ("Tokyo Standard Time", 540),
+    ("Korea Standard Time", 540),
+    ("AUS Central Standard Time", 570),
+    ("AUS Eastern Standard Time", 600),
+    ("E. Australia Standard Time", 600),
+    ("Tasmania Standard Time", 600),
+    ("New Zealand Standard Time", 720),
+];
+
+/// Standard UTC offset of a Windows time zone key, ignoring daylight saving time
+pub fn standard_offset(key: &str) -> Option<FixedOffset> {
+    STANDARD_OFFSETS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key.trim()))
+        .and_then(|(_, minutes)| FixedOffset::east_opt(minutes * 60))
+}
+
+/// Look for people whose time intervals mostly fall outside working hours in
+/// their own time zone, a sign of timestamps that were offset twice (or not at
+/// all) by an import. Daylight saving time is ignored, so keep `working_hours`
+/// an hour wider than the real working day.
+#[derive(Debug, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct IntervalTimezoneCheck {
+    start_date: Option<DateTime<Utc>>,
+    end_date: Option<DateTime<Utc>>,
+    /// Local hours in which intervals are expected to start
+    #[builder(default = 7..20)]
+    working_hours: Range<u32>,
+    /// Share of intervals outside working hours above which a person is flagged
+    #[builder(default = 0.5)]
+    threshold: f64,
+    /// People with fewer intervals are not judged
+    #[builder(default = 5)]
+    min_intervals: usize,
+    /// API instance
+    client: FogBugzClient,
+}
+
+/// Likely cause of a person's intervals falling outside working hours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Diagnosis {
+    /// Shifting by the person's offset once more fixes the data
+    OffsetAppliedTwice,
+    /// The data looks like local time stored as UTC
+    OffsetNotApplied,
+    /// Some other shift fits best, or none does
+    Unexplained,
+}
+
+#[derive(Debug)]
+pub struct TimezoneFinding {
+    pub person_id: u32,
+    pub person: String,
+    pub timezone_key: String,
+    pub offset: FixedOffset,
+    pub intervals: usize,
+    pub outside_working_hours: Vec<u32>,
+    /// Hours to add to the stored timestamps so the most intervals fall in working hours
+    pub suggested_shift: Option<i32>,
+    pub diagnosis: Diagnosis,
+}
+
+#[derive(Debug, Default)]
+pub struct TimezoneReport {
+    pub findings: Vec<TimezoneFinding>,
+    /// People skipped because their time zone key isn't known, with the key
+    pub unknown_timezones: Vec<(u32, String)>,
+}
+
+fn local_hour(time: DateTime<Utc>, offset: FixedOffset, shift_hours: i32) -> u32 {
+    (time.with_timezone(&offset) + chrono::Duration::hours(shift_hours.into())).hour()
+}
+
+/// Judge one person's intervals against their working hours
+fn check_person(
+    person: &Person,
+    timezone_key: &str,
+    offset: FixedOffset,
+    intervals: &[&TimeInterval],
+    working_hours: &Range<u32>,
+    threshold: f64,
+) -> Option<TimezoneFinding> {
+    let outside: Vec<u32> = intervals
+        .iter()
+        .filter(|interval| !working_hours.contains(&local_hour(interval.start_time, offset, 0)))
+        .map(|interval| interval.id)
+        .collect();
+    if intervals.is_empty() || (outside.len() as f64) / (intervals.len() as f64) <= threshold {
+        return None;
+    }
+
+    let inside_with_shift = |shift: i32| {
+        intervals
+            .iter()
+            .filter(|interval| {
+                working_hours.contains(&local_hour(interval.start_time, offset, shift))
+            })
+            .count()
+    };
+    let inside = intervals.len() - outside.len();
+    let fixes = |shift: i32| {
+        let fixed = inside_with_shift(shift);
+        fixed > inside && (fixed as f64) / (intervals.len() as f64) > 1.0 - threshold
+    };
+
+    // Check the two import mistakes first; a broad working day fits many shifts
+    let offset_hours = offset.local_minus_utc() / 3600;
+    let (diagnosis, suggested_shift) = if offset_hours != 0 && fixes(offset_hours) {
+        (Diagnosis::OffsetAppliedTwice, Some(offset_hours))
+    } else if offset_hours != 0 && fixes(-offset_hours) {
+        (Diagnosis::OffsetNotApplied, Some(-offset_hours))
+    } else {
+        // Prefer the smallest shift among equally good ones
+        let best = (-12..=12)
+            .map(|shift: i32| (inside_with_shift(shift), -shift.abs(), shift))
+            .max()
+            .filter(|(fixed, _, _)| *fixed > inside)
+            .map(|(_, _, shift)| shift);
+        (Diagnosis::Unexplained, best)
+    };
+
+    Some(TimezoneFinding {
+        person_id: person.id,
+        person: person.full_name.clone(),
+        timezone_key: timezone_key.to_string(),
+        offset,
+        intervals: intervals.len(),
+        outside_working_hours: outside,
+        suggested_shift,
+        diagnosis,
+    })
+}
+
+impl IntervalTimezoneCheck {
+    pub async fn run(&self) -> Result<TimezoneReport, ResponseError> {
+        let site_timezone = self.client.view_settings().await?.timezone;
+        let people = self.client.list_people().await?;
+        let mut report = TimezoneReport::default();
+
+        for person in people.iter().filter(|p| !p.is_deleted && !p.is_community) {
+            // `*` means the person uses the site time zone
+            let key = match person.timezone.trim() {
+                "" | "*" => site_timezone.clone().unwrap_or_default(),
+                key => key.to_string(),
+            };
+            let Some(offset) = standard_offset(&key) else {
+                report.unknown_timezones.push((person.id, key));
+                continue;
+            };
+            let intervals = self
+                .client
+                .list_time_intervals(Some(person.id), self.start_date, self.end_date)
+                .await?;
+            let intervals: Vec<&TimeInterval> = intervals
+                .iter()
+                .filter(|interval| !interval.is_deleted)
+                .collect();
+            if intervals.len() < self.min_intervals {
+                continue;
+            }
+            if let Some(finding) = check_person(
+                person,
+                &key,
+                offset,
+                &intervals,
+                &self.working_hours,
+                self.threshold,
+            ) {
+                report.findings.push(finding);
+            }
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn interval(id: u32, hour: u32) -> TimeInterval {
+        let start = Utc.with_ymd_and_hms(2024, 3, 4, hour, 0, 0).unwrap();
+        TimeInterval {
+            id,
+            person_id: 7,
+            case_id: 1,
+            start_time: start,
+            end_time: start + chrono::Duration::hours(1),
+            title: "Work".to_string(),
+            is_deleted: false,
+        }
+    }
+
+    #[test]
+    fn test_standard_offset() {
+        assert_eq!(
+            standard_offset("pacific standard time"),
+            FixedOffset::west_opt(8 * 3600)
+        );
+        assert_eq!(standard_offset("Mars Standard Time"), None);
+    }
+
+    #[test]
+    fn test_double_offset_is_diagnosed() {
+        let person: Person = serde_json::from_value(serde_json::json!({
+            "ixPerson": 7, "sFullName": "Jane Doe", "sEmail": "jane@example.com",
+            "sPhone": "", "fAdministrator": false, "fCommunity": false, "fVirtual": false,
+            "fDeleted": false, "fNotify": true, "sHomepage": "", "sLocale": "en-us",
+            "sLanguage": "en-us", "sTimeZoneKey": "Eastern Standard Time"
+        }))
+        .unwrap();
+        let offset = standard_offset("Eastern Standard Time").unwrap();
+
+        // Local 9-17 stored as if it were UTC shows up at 4-12 in New York
+        let shifted: Vec<TimeInterval> = (9..17).map(|hour| interval(hour, hour)).collect();
+        let shifted: Vec<&TimeInterval> = shifted.iter().collect();
+        let finding = check_person(&person, "EST", offset, &shifted, &(9..18), 0.5).unwrap();
+        assert_eq!(finding.outside_working_hours.len(), 5);
+        assert_eq!(finding.suggested_shift, Some(5));
+        assert_eq!(finding.diagnosis, Diagnosis::OffsetNotApplied);
+
+        let correct: Vec<TimeInterval> = (14..22).map(|hour| interval(hour, hour)).collect();
+        let correct: Vec<&TimeInterval> = correct.iter().collect();
+        assert!(check_person(&person, "EST", offset, &correct, &(9..18), 0.5).is_none());
+    }
+}
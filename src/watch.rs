@@ -0,0 +1,28 @@
+use std::time::Duration;
+
+use futures::Stream;
+
+use crate::{
+    FogBugzClient, ResponseError,
+    change_feed::{CaseChange, ChangeFeed},
+};
+
+impl FogBugzClient {
+    /// Push-like feed of the cases matching `query` (a search or a saved
+    /// filter's query) as they are updated, for bots without webhooks. Polls every
+    /// `poll_interval` for cases with `dtLastUpdated` after the last one seen and
+    /// yields each update once. Use `change_feed` for more control, e.g. the
+    /// columns, a persisted watermark or a shutdown signal.
+    pub fn watch_filter(
+        &self,
+        query: impl Into<String>,
+        poll_interval: Duration,
+    ) -> impl Stream<Item = Result<CaseChange, ResponseError>> + 'static {
+        ChangeFeed::builder()
+            .client(self.clone())
+            .query(query)
+            .interval(poll_interval)
+            .build()
+            .into_stream()
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt;
+
+use bon::Builder;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{FogBugzClient, ResponseError};
+
+/// A FogBugz wiki
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Wiki {
+    #[serde(rename = "ixWiki")]
+    pub id: u32,
+    #[serde(rename = "sWiki")]
+    pub name: String,
+    #[serde(rename = "sTagLineHTML", default)]
+    pub tagline: Option<String>,
+    /// Article shown as the wiki's front page
+    #[serde(rename = "ixWikiPageRoot", default)]
+    pub root_article_id: Option<u32>,
+}
+
+impl fmt::Display for Wiki {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {}", self.id, self.name)
+    }
+}
+
+/// A wiki article; `body` is only filled in by `view_article`
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WikiArticle {
+    #[serde(rename = "ixWikiPage")]
+    pub id: u32,
+    #[serde(rename = "ixWiki", default)]
+    pub wiki_id: Option<u32>,
+    #[serde(rename = "sHeadline")]
+    pub headline: String,
+    /// HTML body
+    #[serde(rename = "sBody", default)]
+    pub body: Option<String>,
+    #[serde(rename = "nRevision", default)]
+    pub revision: Option<u32>,
+    #[serde(rename = "dtUpdated", default)]
+    pub updated: Option<DateTime<Utc>>,
+}
+
+impl fmt::Display for WikiArticle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{} {}", self.id, self.headline)
+    }
+}
+
+/// Articles come back as `articles` or, on older sites, `wikidocuments`
+fn take_articles(response: &mut Value) -> Value {
+    match response["data"]["articles"].take() {
+        Value::Null => response["data"]["wikidocuments"].take(),
+        articles => articles,
+    }
+}
+
+/// Request to list all wikis
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct ListWikisRequest {
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl ListWikisRequest {
+    pub async fn send(&self) -> Result<Vec<Wiki>, ResponseError> {
+        let mut response = self.client.send_command("listWikis", self).await?;
+        Ok(serde_json::from_value(response["data"]["wikis"].take())?)
+    }
+}
+
+/// Request to list the articles of a wiki
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct ListArticlesRequest {
+    /// Wiki to list (required)
+    #[serde(rename = "ixWiki")]
+    wiki_id: u32,
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl ListArticlesRequest {
+    pub async fn send(&self) -> Result<Vec<WikiArticle>, ResponseError> {
+        let mut response = self.client.send_command("listArticles", self).await?;
+        Ok(serde_json::from_value(take_articles(&mut response))?)
+    }
+}
+
+/// Request to view an article with its body
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct ViewArticleRequest {
+    /// Article to view (required)
+    #[serde(rename = "ixWikiPage")]
+    article_id: u32,
+    /// Older revision to view instead of the latest (optional)
+    #[serde(rename = "nRevision", skip_serializing_if = "Option::is_none")]
+    revision: Option<u32>,
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl ViewArticleRequest {
+    pub async fn send(&self) -> Result<WikiArticle, ResponseError> {
+        let mut response = self.client.send_command("viewArticle", self).await?;
+        Ok(serde_json::from_value(response["data"]["wikipage"].take())?)
+    }
+}
+
+/// Request to create an article in a wiki
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct NewArticleRequest {
+    /// Wiki to create the article in (required)
+    #[serde(rename = "ixWiki")]
+    wiki_id: u32,
+    /// Article title (required)
+    #[serde(rename = "sHeadline")]
+    #[builder(into)]
+    headline: String,
+    /// HTML body (optional)
+    #[serde(rename = "sBody", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    body: Option<String>,
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl NewArticleRequest {
+    pub async fn send(&self) -> Result<WikiArticle, ResponseError> {
+        let mut response = self.client.send_command("newArticle", self).await?;
+        Ok(serde_json::from_value(response["data"]["wikipage"].take())?)
+    }
+}
+
+/// Request to change an article's title or body
+#[derive(Debug, Serialize, Builder)]
+#[builder(state_mod(vis = "pub(crate)"))]
+pub struct EditArticleRequest {
+    /// Article to edit (required)
+    #[serde(rename = "ixWikiPage")]
+    article_id: u32,
+    /// New title (optional)
+    #[serde(rename = "sHeadline", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    headline: Option<String>,
+    /// New HTML body (optional)
+    #[serde(rename = "sBody", skip_serializing_if = "Option::is_none")]
+    #[builder(into)]
+    body: Option<String>,
+    /// API instance
+    #[serde(skip)]
+    client: FogBugzClient,
+}
+
+impl EditArticleRequest {
+    pub async fn send(&self) -> Result<WikiArticle, ResponseError> {
+        let mut response = self.client.send_command("editArticle", self).await?;
+        Ok(serde_json::from_value(response["data"]["wikipage"].take())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wiki_deserialization() {
+        let mut response = serde_json::json!({
+            "data": {
+                "wikidocuments": [
+                    { "ixWikiPage": 12, "ixWiki": 1, "sHeadline": "Release process" },
+                    { "ixWikiPage": 13, "sHeadline": "On-call", "nRevision": 4 }
+                ]
+            }
+        });
+        let articles: Vec<WikiArticle> =
+            serde_json::from_value(take_articles(&mut response)).unwrap();
+        assert_eq!(articles.len(), 2);
+        assert_eq!(articles[0].to_string(), "#12 Release process");
+        assert_eq!(articles[1].revision, Some(4));
+        assert!(articles[1].body.is_none());
+
+        let wiki: Wiki = serde_json::from_value(serde_json::json!({
+            "ixWiki": 1, "sWiki": "Engineering", "sTagLineHTML": "How we work"
+        }))
+        .unwrap();
+        assert_eq!(wiki.to_string(), "#1 Engineering");
+    }
+}
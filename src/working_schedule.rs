@@ -0,0 +1,181 @@
+use std::collections::{BTreeSet, HashSet};
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, Utc, Weekday};
+use serde::Deserialize;
+
+use crate::{FogBugzClient, ResponseError, hours::Hours};
+
+/// Days off the site's working schedule lists, e.g. public holidays
+#[derive(Debug, Deserialize)]
+struct ScheduleException {
+    #[serde(rename = "dtStart")]
+    start: DateTime<Utc>,
+    #[serde(rename = "dtEnd")]
+    end: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ScheduleResponse {
+    /// Hour of the day work starts, e.g. `9.0`
+    #[serde(rename = "nWorkdayStarts", default)]
+    workday_starts: Option<f64>,
+    /// Hour of the day work ends, e.g. `17.5`
+    #[serde(rename = "nWorkdayEnds", default)]
+    workday_ends: Option<f64>,
+    #[serde(rename = "workingScheduleExceptions", default)]
+    exceptions: Vec<ScheduleException>,
+}
+
+/// Which days count as working days, for computing due dates in business days.
+/// Dates are taken in UTC.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorkingSchedule {
+    pub working_days: HashSet<Weekday>,
+    pub holidays: BTreeSet<NaiveDate>,
+    pub day_starts: NaiveTime,
+    /// Time of day a due date falls on
+    pub day_ends: NaiveTime,
+}
+
+impl Default for WorkingSchedule {
+    /// Monday to Friday from 9:00 to 17:00, without holidays
+    fn default() -> Self {
+        Self {
+            working_days: [
+                Weekday::Mon,
+                Weekday::Tue,
+                Weekday::Wed,
+                Weekday::Thu,
+                Weekday::Fri,
+            ]
+            .into(),
+            holidays: BTreeSet::new(),
+            day_starts: NaiveTime::from_hms_opt(9, 0, 0).unwrap_or_default(),
+            day_ends: NaiveTime::from_hms_opt(17, 0, 0).unwrap_or_default(),
+        }
+    }
+}
+
+impl WorkingSchedule {
+    pub fn with_holidays(mut self, holidays: impl IntoIterator<Item = NaiveDate>) -> Self {
+        self.holidays.extend(holidays);
+        self
+    }
+
+    pub fn is_working_day(&self, date: NaiveDate) -> bool {
+        self.working_days.contains(&date.weekday()) && !self.holidays.contains(&date)
+    }
+
+    /// Hours expected to be worked on `date`
+    pub fn working_hours(&self, date: NaiveDate) -> Hours {
+        if !self.is_working_day(date) || self.day_ends <= self.day_starts {
+            return Hours::ZERO;
+        }
+        Hours::from(self.day_ends - self.day_starts)
+    }
+
+    /// End of the working day `days` working days after `from`'s date.
+    /// With `0`, the end of `from`'s date, or of the next working day if it is a day off.
+    pub fn add_working_days(&self, from: DateTime<Utc>, days: u32) -> DateTime<Utc> {
+        let mut date = from.date_naive();
+        let mut remaining = days;
+        // A schedule without working days would never finish; fall back to calendar days
+        let has_working_days = !self.working_days.is_empty();
+        while has_working_days && !self.is_working_day(date) {
+            date = date + Days::new(1);
+        }
+        while remaining > 0 {
+            date = date + Days::new(1);
+            if !has_working_days || self.is_working_day(date) {
+                remaining -= 1;
+            }
+        }
+        date.and_time(self.day_ends).and_utc()
+    }
+
+    fn from_response(response: ScheduleResponse) -> Self {
+        let mut schedule = Self::default();
+        let time_of_day = |hours: f64| {
+            let minutes = (hours * 60.0).round() as u32;
+            NaiveTime::from_hms_opt(minutes / 60, minutes % 60, 0)
+        };
+        if let Some(time) = response.workday_starts.and_then(time_of_day) {
+            schedule.day_starts = time;
+        }
+        if let Some(time) = response.workday_ends.and_then(time_of_day) {
+            schedule.day_ends = time;
+        }
+        for exception in response.exceptions {
+            let mut date = exception.start.date_naive();
+            while date < exception.end.date_naive() || date == exception.start.date_naive() {
+                schedule.holidays.insert(date);
+                date = date + Days::new(1);
+            }
+        }
+        schedule
+    }
+}
+
+impl FogBugzClient {
+    /// The site's working schedule: when the workday ends and its days off.
+    /// FogBugz doesn't report working weekdays, so Monday to Friday is assumed.
+    pub async fn working_schedule(&self) -> Result<WorkingSchedule, ResponseError> {
+        let response = self
+            .send_cached_command("listWorkingSchedule", serde_json::json!({}))
+            .await?;
+        let schedule = serde_json::from_value(response["data"]["workingSchedule"].clone())?;
+        Ok(WorkingSchedule::from_response(schedule))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_working_days() {
+        let schedule = WorkingSchedule::default()
+            .with_holidays([NaiveDate::from_ymd_opt(2024, 3, 11).unwrap()]);
+        // Thursday
+        let from: DateTime<Utc> = "2024-03-07T10:00:00Z".parse().unwrap();
+        assert_eq!(
+            schedule.add_working_days(from, 3).to_rfc3339(),
+            "2024-03-13T17:00:00+00:00"
+        );
+        // Saturday
+        let from: DateTime<Utc> = "2024-03-09T10:00:00Z".parse().unwrap();
+        assert_eq!(
+            schedule.add_working_days(from, 0).to_rfc3339(),
+            "2024-03-12T17:00:00+00:00"
+        );
+    }
+
+    #[test]
+    fn test_schedule_from_response() {
+        let response: ScheduleResponse = serde_json::from_value(serde_json::json!({
+            "nWorkdayStarts": 9.0,
+            "nWorkdayEnds": 17.5,
+            "workingScheduleExceptions": [
+                { "dtStart": "2024-12-25T00:00:00Z", "dtEnd": "2024-12-27T00:00:00Z" }
+            ]
+        }))
+        .unwrap();
+        let schedule = WorkingSchedule::from_response(response);
+        assert_eq!(
+            schedule.working_hours(NaiveDate::from_ymd_opt(2024, 12, 23).unwrap()),
+            Hours::new(8.5)
+        );
+        assert_eq!(
+            schedule.day_ends,
+            NaiveTime::from_hms_opt(17, 30, 0).unwrap()
+        );
+        assert_eq!(
+            schedule
+                .holidays
+                .iter()
+                .map(|date| date.day())
+                .collect::<Vec<_>>(),
+            vec![25, 26]
+        );
+    }
+}
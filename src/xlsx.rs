@@ -0,0 +1,323 @@
+use std::path::Path;
+
+use chrono::{DateTime, Utc};
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+
+use crate::{
+    hours_report::{CaseHours, ProjectHours},
+    list_cases::{Case, CaseSummary},
+    reports::StaleCase,
+    time_tracking::TimeInterval,
+};
+
+/// A single typed spreadsheet cell
+#[derive(Debug, Clone, PartialEq)]
+pub enum CellValue {
+    Text(String),
+    /// Whole number such as an ID or a count, written without decimals
+    Integer(i64),
+    /// Fractional number such as hours, written with two decimals
+    Number(f64),
+    DateTime(DateTime<Utc>),
+    Bool(bool),
+    Empty,
+}
+
+impl From<String> for CellValue {
+    fn from(value: String) -> Self {
+        CellValue::Text(value)
+    }
+}
+
+impl From<&str> for CellValue {
+    fn from(value: &str) -> Self {
+        CellValue::Text(value.to_string())
+    }
+}
+
+impl<T: Into<CellValue>> From<Option<T>> for CellValue {
+    fn from(value: Option<T>) -> Self {
+        value.map(Into::into).unwrap_or(CellValue::Empty)
+    }
+}
+
+macro_rules! number_cell {
+    ($($ty:ty),*) => {
+        $(impl From<$ty> for CellValue {
+            fn from(value: $ty) -> Self {
+                CellValue::Number(value.into())
+            }
+        })*
+    };
+}
+
+number_cell!(f64, crate::hours::Hours);
+
+impl From<u32> for CellValue {
+    fn from(value: u32) -> Self {
+        CellValue::Integer(value.into())
+    }
+}
+
+impl From<u64> for CellValue {
+    fn from(value: u64) -> Self {
+        i64::try_from(value).map_or(CellValue::Number(value as f64), CellValue::Integer)
+    }
+}
+
+impl From<DateTime<Utc>> for CellValue {
+    fn from(value: DateTime<Utc>) -> Self {
+        CellValue::DateTime(value)
+    }
+}
+
+impl From<bool> for CellValue {
+    fn from(value: bool) -> Self {
+        CellValue::Bool(value)
+    }
+}
+
+/// A record that can be written as one spreadsheet row
+pub trait XlsxRow {
+    /// Column headers, in the same order as `cells`
+    fn headers() -> &'static [&'static str];
+    fn cells(&self) -> Vec<CellValue>;
+}
+
+impl XlsxRow for Case {
+    fn headers() -> &'static [&'static str] {
+        &["Case", "Project ID", "Project", "Title"]
+    }
+
+    fn cells(&self) -> Vec<CellValue> {
+        vec![
+            self.case_id.into(),
+            self.project_id.into(),
+            self.project.as_str().into(),
+            self.title.as_str().into(),
+        ]
+    }
+}
+
+impl XlsxRow for CaseSummary {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "Case",
+            "Title",
+            "Project",
+            "Area",
+            "Status",
+            "Assigned To",
+            "Milestone",
+            "Elapsed (h)",
+            "Current Estimate (h)",
+            "Opened",
+            "Last Updated",
+        ]
+    }
+
+    fn cells(&self) -> Vec<CellValue> {
+        vec![
+            self.case_id.into(),
+            self.title.clone().into(),
+            self.project.clone().into(),
+            self.area.clone().into(),
+            self.status.clone().into(),
+            self.assigned_to.clone().into(),
+            self.milestone.clone().into(),
+            self.hours_elapsed.into(),
+            self.hours_current_estimate.into(),
+            self.opened.into(),
+            self.last_updated.into(),
+        ]
+    }
+}
+
+impl XlsxRow for CaseHours {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "Case",
+            "Title",
+            "Project",
+            "Assigned To",
+            "Elapsed (h)",
+            "Current Estimate (h)",
+            "Original Estimate (h)",
+        ]
+    }
+
+    fn cells(&self) -> Vec<CellValue> {
+        vec![
+            self.case_id.into(),
+            self.title.as_str().into(),
+            self.project.as_str().into(),
+            self.assigned_to.as_str().into(),
+            self.hours_elapsed.into(),
+            self.hours_current_estimate.into(),
+            self.hours_original_estimate.into(),
+        ]
+    }
+}
+
+impl XlsxRow for ProjectHours {
+    fn headers() -> &'static [&'static str] {
+        &["Project", "Elapsed (h)", "Estimate (h)", "Cases"]
+    }
+
+    fn cells(&self) -> Vec<CellValue> {
+        vec![
+            self.project.as_str().into(),
+            self.total_elapsed.into(),
+            self.total_estimate.into(),
+            self.case_count.into(),
+        ]
+    }
+}
+
+impl XlsxRow for StaleCase {
+    fn headers() -> &'static [&'static str] {
+        &["Case", "Title", "Project", "Assigned To", "Last Updated"]
+    }
+
+    fn cells(&self) -> Vec<CellValue> {
+        vec![
+            self.case_id.into(),
+            self.title.as_str().into(),
+            self.project.as_str().into(),
+            self.assigned_to.as_str().into(),
+            self.last_updated.into(),
+        ]
+    }
+}
+
+impl XlsxRow for TimeInterval {
+    fn headers() -> &'static [&'static str] {
+        &[
+            "Interval", "Case", "Person", "Title", "Start", "End", "Hours", "Deleted",
+        ]
+    }
+
+    fn cells(&self) -> Vec<CellValue> {
+        vec![
+            self.id.into(),
+            self.case_id.into(),
+            self.person_id.into(),
+            self.title.as_str().into(),
+            self.start_time.into(),
+            self.end_time.into(),
+            self.duration().into(),
+            self.is_deleted.into(),
+        ]
+    }
+}
+
+fn write_sheet<T: XlsxRow>(worksheet: &mut Worksheet, rows: &[T]) -> Result<(), XlsxError> {
+    let header_format = Format::new().set_bold();
+    let datetime_format = Format::new().set_num_format("yyyy-mm-dd hh:mm");
+    let integer_format = Format::new().set_num_format("0");
+    let number_format = Format::new().set_num_format("0.00");
+
+    for (col, header) in T::headers().iter().enumerate() {
+        worksheet.write_string_with_format(0, col as u16, *header, &header_format)?;
+    }
+
+    for (index, row) in rows.iter().enumerate() {
+        let row_num = index as u32 + 1;
+        for (col, cell) in row.cells().into_iter().enumerate() {
+            let col = col as u16;
+            match cell {
+                CellValue::Text(text) => {
+                    worksheet.write_string(row_num, col, text)?;
+                }
+                CellValue::Integer(number) => {
+                    worksheet.write_number_with_format(
+                        row_num,
+                        col,
+                        number as f64,
+                        &integer_format,
+                    )?;
+                }
+                CellValue::Number(number) => {
+                    worksheet.write_number_with_format(row_num, col, number, &number_format)?;
+                }
+                CellValue::DateTime(datetime) => {
+                    worksheet.write_datetime_with_format(
+                        row_num,
+                        col,
+                        datetime.naive_utc(),
+                        &datetime_format,
+                    )?;
+                }
+                CellValue::Bool(value) => {
+                    worksheet.write_boolean(row_num, col, value)?;
+                }
+                CellValue::Empty => {}
+            }
+        }
+    }
+
+    worksheet.set_freeze_panes(1, 0)?;
+    worksheet.autofit();
+    Ok(())
+}
+
+fn build_workbook<T: XlsxRow>(rows: &[T], sheet_name: &str) -> Result<Workbook, XlsxError> {
+    let mut workbook = Workbook::new();
+    let worksheet = workbook.add_worksheet();
+    worksheet.set_name(sheet_name)?;
+    write_sheet(worksheet, rows)?;
+    Ok(workbook)
+}
+
+/// Render rows as an xlsx workbook with a single sheet and a bold header row
+pub fn to_xlsx_bytes<T: XlsxRow>(rows: &[T], sheet_name: &str) -> Result<Vec<u8>, XlsxError> {
+    build_workbook(rows, sheet_name)?.save_to_buffer()
+}
+
+/// Write rows to an xlsx file at `path`
+pub fn write_xlsx<T: XlsxRow>(
+    rows: &[T],
+    sheet_name: &str,
+    path: impl AsRef<Path>,
+) -> Result<(), XlsxError> {
+    build_workbook(rows, sheet_name)?.save(path.as_ref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hours::Hours;
+
+    #[test]
+    fn test_project_hours_to_xlsx() {
+        let rows = vec![ProjectHours {
+            project: "Backend".to_string(),
+            total_elapsed: Hours::new(12.5),
+            total_estimate: Hours::new(20.0),
+            case_count: 3,
+        }];
+        assert_eq!(rows[0].cells().len(), ProjectHours::headers().len());
+        assert_eq!(rows[0].cells()[1], CellValue::Number(12.5));
+        assert_eq!(rows[0].cells()[3], CellValue::Integer(3));
+
+        let bytes = to_xlsx_bytes(&rows, "Hours").unwrap();
+        // xlsx files are zip archives
+        assert!(bytes.starts_with(b"PK"));
+    }
+
+    #[test]
+    fn test_case_summary_to_xlsx() {
+        let rows = vec![CaseSummary {
+            case_id: Some(42),
+            title: Some("Crash on save".to_string()),
+            hours_elapsed: Some(1.5),
+            ..Default::default()
+        }];
+        let cells = rows[0].cells();
+        assert_eq!(cells.len(), CaseSummary::headers().len());
+        assert_eq!(cells[0], CellValue::Integer(42));
+        assert_eq!(cells[2], CellValue::Empty);
+        assert_eq!(cells[7], CellValue::Number(1.5));
+        assert!(to_xlsx_bytes(&rows, "Cases").unwrap().starts_with(b"PK"));
+    }
+}